@@ -0,0 +1,44 @@
+const crypto = require('crypto');
+
+/**
+ * Coalesces concurrent identical jobs. Callers with the same key share one in-flight promise, and
+ * the entry is evicted once it settles so a later request starts a fresh job.
+ */
+class InFlightRequests {
+  constructor() {
+    this.pending = new Map();
+  }
+
+  /**
+   * Key for a job from the content that determines its result.
+   */
+  static keyFor(content) {
+    return crypto.createHash('sha256').update(JSON.stringify(content)).digest('hex');
+  }
+
+  has(key) {
+    return this.pending.has(key);
+  }
+
+  get size() {
+    return this.pending.size;
+  }
+
+  /**
+   * Run `job` unless an identical one is already in flight, in which case share its result.
+   */
+  run(key, job) {
+    const existing = this.pending.get(key);
+    if (existing) {
+      return existing;
+    }
+
+    const promise = Promise.resolve()
+      .then(job)
+      .finally(() => this.pending.delete(key));
+    this.pending.set(key, promise);
+    return promise;
+  }
+}
+
+module.exports = { InFlightRequests };
@@ -0,0 +1,45 @@
+const test = require('node:test');
+const assert = require('node:assert');
+const { InFlightRequests } = require('./inflight');
+
+test('concurrent identical requests share one submission', async () => {
+  const inFlight = new InFlightRequests();
+  let submissions = 0;
+  let finish;
+  const submit = () => {
+    submissions += 1;
+    return new Promise((resolve) => {
+      finish = resolve;
+    });
+  };
+
+  const key = InFlightRequests.keyFor(['--transaction-hash', '0xabc']);
+  const first = inFlight.run(key, submit);
+  const second = inFlight.run(InFlightRequests.keyFor(['--transaction-hash', '0xabc']), submit);
+  await Promise.resolve();
+  finish({ fixture: 'proof' });
+
+  assert.deepStrictEqual(await Promise.all([first, second]), [{ fixture: 'proof' }, { fixture: 'proof' }]);
+  assert.strictEqual(submissions, 1);
+  assert.strictEqual(inFlight.size, 0);
+});
+
+test('completed and failed requests are evicted', async () => {
+  const inFlight = new InFlightRequests();
+  const key = InFlightRequests.keyFor(['--transaction-hash', '0xabc']);
+
+  await assert.rejects(inFlight.run(key, () => Promise.reject(new Error('network down'))));
+  assert.strictEqual(inFlight.has(key), false);
+
+  let submissions = 0;
+  await inFlight.run(key, async () => { submissions += 1; });
+  await inFlight.run(key, async () => { submissions += 1; });
+  assert.strictEqual(submissions, 2);
+});
+
+test('different requests do not coalesce', () => {
+  assert.notStrictEqual(
+    InFlightRequests.keyFor(['--transaction-hash', '0xabc']),
+    InFlightRequests.keyFor(['--transaction-hash', '0xdef'])
+  );
+});
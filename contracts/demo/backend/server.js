@@ -4,6 +4,7 @@ const cors = require('cors');
 const path = require('path');
 const fs = require('fs').promises;
 const { ethers } = require('ethers');
+const { InFlightRequests } = require('./inflight');
 
 // Load environment variables from root directory
 require('dotenv').config({ path: path.join(__dirname, '../../../.env') });
@@ -14,6 +15,9 @@ const port = process.env.PORT || 3001;
 app.use(cors());
 app.use(express.json());
 
+// Proof generations in flight, keyed by the prover arguments that determine the input
+const proofRequests = new InFlightRequests();
+
 // Path to the Rust binary
 const RUST_BINARY_PATH = path.join(__dirname, '../../../target/release/evm');
 
@@ -325,6 +329,72 @@ app.post('/api/owner/register-canonical-block', async (req, res) => {
   }
 });
 
+/**
+ * Run the Rust prover with `args` and read back the fixture it wrote.
+ */
+async function runProofGeneration(args) {
+  // Create modified environment
+  const env = {
+    ...process.env,
+    RUST_LOG: 'info',
+    SP1_PROVER: 'network'
+  };
+
+  // Execute Rust binary
+  const rustProcess = spawn(RUST_BINARY_PATH, args, {
+    env,
+    cwd: path.join(__dirname, '../../../script'),
+    stdio: ['pipe', 'pipe', 'pipe']
+  });
+
+  let stdout = '';
+  let stderr = '';
+
+  rustProcess.stdout.on('data', (data) => {
+    stdout += data.toString();
+    console.log('Rust stdout:', data.toString());
+  });
+
+  rustProcess.stderr.on('data', (data) => {
+    stderr += data.toString();
+    console.error('Rust stderr:', data.toString());
+  });
+
+  // Wait for process completion with timeout
+  const result = await new Promise((resolve, reject) => {
+    const timeout = setTimeout(() => {
+      rustProcess.kill();
+      reject(new Error('Proof generation timed out (20 minutes)'));
+    }, 20 * 60 * 1000); // 20 minutes timeout
+
+    rustProcess.on('close', (code) => {
+      clearTimeout(timeout);
+      if (code === 0) {
+        resolve({ stdout, stderr });
+      } else {
+        reject(new Error(`Rust process failed with code ${code}: ${stderr}`));
+      }
+    });
+
+    rustProcess.on('error', (error) => {
+      clearTimeout(timeout);
+      reject(error);
+    });
+  });
+
+  // Try to read the generated fixture file
+  let fixture = null;
+  try {
+    const fixturePath = path.join(__dirname, '../../../contracts/src/fixtures/groth16-fixture.json');
+    const fixtureContent = await fs.readFile(fixturePath, 'utf8');
+    fixture = JSON.parse(fixtureContent);
+  } catch (fixtureError) {
+    console.warn('Could not read fixture file:', fixtureError.message);
+  }
+
+  return { fixture, stdout, stderr };
+}
+
 /**
  * Generate a real-time proof using the Rust Succinct prover
  */
@@ -365,8 +435,10 @@ app.post('/api/generate-proof', async (req, res) => {
     });
 
     // Build command arguments
+    // The fixture is read back from the legacy path below, not the vkey-keyed default
     const args = [
-      '--eth-rpc-url', getNetwork(chainId).rpcUrl
+      '--eth-rpc-url', getNetwork(chainId).rpcUrl,
+      '--legacy-fixture-name'
     ];
     if (isAbsenceProof) {
       args.push('--absence-block-number', String(blockNumber));
@@ -379,68 +451,18 @@ app.post('/api/generate-proof', async (req, res) => {
       }
     }
 
-    // Create modified environment
-    const env = {
-      ...process.env,
-      RUST_LOG: 'info',
-      SP1_PROVER: 'network'
-    };
-
-    // Execute Rust binary
-    const rustProcess = spawn(RUST_BINARY_PATH, args, {
-      env,
-      cwd: path.join(__dirname, '../../../script'),
-      stdio: ['pipe', 'pipe', 'pipe']
-    });
-
-    let stdout = '';
-    let stderr = '';
-
-    rustProcess.stdout.on('data', (data) => {
-      stdout += data.toString();
-      console.log('Rust stdout:', data.toString());
-    });
-
-    rustProcess.stderr.on('data', (data) => {
-      stderr += data.toString();
-      console.error('Rust stderr:', data.toString());
-    });
-
-    // Wait for process completion with timeout
-    const result = await new Promise((resolve, reject) => {
-      const timeout = setTimeout(() => {
-        rustProcess.kill();
-        reject(new Error('Proof generation timed out (20 minutes)'));
-      }, 20 * 60 * 1000); // 20 minutes timeout
-
-      rustProcess.on('close', (code) => {
-        clearTimeout(timeout);
-        if (code === 0) {
-          resolve({ stdout, stderr });
-        } else {
-          reject(new Error(`Rust process failed with code ${code}: ${stderr}`));
-        }
-      });
-
-      rustProcess.on('error', (error) => {
-        clearTimeout(timeout);
-        reject(error);
-      });
-    });
-
-    // Try to read the generated fixture file
-    let fixture = null;
-    try {
-      const fixturePath = path.join(__dirname, '../../../contracts/src/fixtures/groth16-fixture.json');
-      const fixtureContent = await fs.readFile(fixturePath, 'utf8');
-      fixture = JSON.parse(fixtureContent);
-    } catch (fixtureError) {
-      console.warn('Could not read fixture file:', fixtureError.message);
+    // Identical requests while a proof is in flight share it instead of submitting twice
+    const requestKey = InFlightRequests.keyFor(args);
+    const coalesced = proofRequests.has(requestKey);
+    if (coalesced) {
+      console.log('Joining in-flight proof generation for identical request');
     }
+    const { fixture, stdout, stderr } = await proofRequests.run(requestKey, () => runProofGeneration(args));
 
     res.json({
       success: true,
       message: 'Proof generated successfully',
+      coalesced,
       fixture,
       logs: {
         stdout,
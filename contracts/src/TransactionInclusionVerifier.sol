@@ -23,6 +23,136 @@ struct PublicValuesStruct {
     bytes32 verifiedAgainstRoot;
 }
 
+/// @notice Appended after `PublicValuesStruct` by every proof.
+struct PublicValuesExtensionStruct {
+    /// @notice Length of the block header RLP encoding whose keccak256 is `blockHash`.
+    /// @dev Lets a contract that parses the header itself cross-check its own parse.
+    uint32 headerRlpLength;
+    /// @notice EIP-2718 type of the included transaction, checked to decode in the guest.
+    /// @dev Zero (legacy) is also committed when no inclusion was proved; check `isIncluded` first.
+    uint8 txType;
+    /// @notice Which optional section follows: 0 none, 1 `AdjacentPublicValuesStruct`, 2
+    ///         `SenderOrderingPublicValuesStruct`, 3 `GasUsedPublicValuesStruct`,
+    ///         4 `TransactionCountPublicValuesStruct`, 5 `CrossBlockPublicValuesStruct`.
+    uint8 appendedSection;
+    /// @notice Transactions root supplied by the prover and checked against the header in the guest; zero when
+    ///         none was claimed.
+    /// @dev A contract holding a trusted root (e.g. from a header relay) compares it with this value. For a
+    ///      relayed-root proof (`headerUnverified` set) it is the only binding to a block, so the contract must check
+    ///      it against a root it has proven itself, e.g. from an L1 storage slot.
+    bytes32 claimedTransactionsRoot;
+    /// @notice Per-gas price the included transaction paid at the block's base fee: `gasPrice` for legacy and
+    ///         EIP-2930 transactions, `min(maxFeePerGas, baseFee + maxPriorityFeePerGas)` for EIP-1559, blob and
+    ///         set-code transactions. Blob gas is not included.
+    /// @dev Zero when no inclusion was proved.
+    uint256 effectiveGasPrice;
+    /// @notice True when the proof was generated from a partial header of only the block number and transactions
+    ///         root. `blockHash`, `headerRlpLength` and `effectiveGasPrice` are then zero and
+    ///         `transactionCanBeIncluded` is false.
+    /// @dev Nothing binds such a proof to a canonical block; slashing contracts must reject it, unless it is a
+    ///      relayed-root proof whose `claimedTransactionsRoot` the contract verifies separately.
+    bool headerUnverified;
+    /// @notice True when a lenient-mode proof committed a decode failure: the committed transaction did not decode
+    ///         or its signer did not recover, so `transactionCanBeIncluded` is false, or the verified leaf did not
+    ///         decode, so `txType` and `effectiveGasPrice` are zero.
+    /// @dev Always false for strict-mode proofs, where such failures abort proving.
+    bool decodeFailed;
+    /// @notice Gas limit of the block header that hashes to `blockHash`; zero for a partial-header proof.
+    uint64 blockGasLimit;
+    /// @notice Gas used by the whole block, from the same header; zero for a partial-header proof.
+    uint64 blockGasUsed;
+    /// @notice Length in bytes of the included transaction's EIP-2718 encoding, the trie leaf the proof verified.
+    /// @dev Zero when no inclusion was proved.
+    uint32 transactionSizeBytes;
+    /// @notice Beacon slot the block was proposed in, for commitments keyed by slot.
+    /// @dev The guest checks the block timestamp is the start of the slot under `beaconGenesisTime`; pin that to the
+    /// expected network's genesis time. Both are zero when no slot was claimed.
+    uint64 slot;
+    uint64 beaconGenesisTime;
+    /// @notice Whether the block is proof-of-stake, for slashing rules that differ across the Merge.
+    /// @dev Read from the header's zero difficulty (EIP-3675), so it is bound to `blockHash`. False for a partial header.
+    bool postMerge;
+    /// @notice Number of transactions trie proof nodes the guest verified, for bounding proof depth.
+    uint8 proofDepth;
+    /// @notice keccak256 of the included transaction's input data, for disputes over what it was meant to do.
+    /// @dev The empty-input hash for a plain transfer; zero when no inclusion was proved or the leaf failed to decode.
+    bytes32 calldataHash;
+    /// @notice Whether the proofs are against the parent of the supplied block, for commitments that name a block by its parent.
+    /// @dev The block hash, number and every header field are then the parent's, and `transactionCanBeIncluded` is false.
+    bool provedInParent;
+    /// @notice Access-list entry the prover was asked about, for attesting that a transaction declared it.
+    /// @dev Both zero when none was asked about.
+    address accessListAddress;
+    bytes32 accessListStorageKey;
+    /// @notice Whether the included transaction's access list holds `accessListStorageKey` under `accessListAddress`.
+    /// @dev False for absence proofs and legacy transactions, which carry no access list.
+    bool hasAccessListEntry;
+}
+
+uint8 constant APPENDED_SECTION_ADJACENT = 1;
+uint8 constant APPENDED_SECTION_SENDER_ORDERING = 2;
+uint8 constant APPENDED_SECTION_GAS_USED = 3;
+uint8 constant APPENDED_SECTION_TRANSACTION_COUNT = 4;
+uint8 constant APPENDED_SECTION_CROSS_BLOCK = 5;
+
+/// @notice Appended after `PublicValuesExtensionStruct` when the proof also covers the transaction at `transactionIndex + 1`.
+struct AdjacentPublicValuesStruct {
+    bytes32 transactionHash;
+    bytes32 nextTransactionHash;
+    uint64 nextTransactionIndex;
+    /// @notice True when both transactions were proved included at consecutive indices of the same block.
+    bool areAdjacent;
+}
+
+/// @notice Appended after `PublicValuesExtensionStruct` when the proof covers two transactions from one sender.
+struct SenderOrderingPublicValuesStruct {
+    /// @notice Sender recovered from both transactions in the guest.
+    address sender;
+    bytes32 firstTransactionHash;
+    uint64 firstIndex;
+    bytes32 secondTransactionHash;
+    uint64 secondIndex;
+    /// @notice True when the first transaction appears before the second in the block.
+    bool inOrder;
+}
+
+/// @notice Appended after `PublicValuesExtensionStruct` when the proof also covers the transaction's receipts.
+struct GasUsedPublicValuesStruct {
+    bytes32 transactionHash;
+    uint64 transactionIndex;
+    /// @notice Difference between the cumulative gas used of the transaction's receipt and the previous receipt,
+    ///         both proved against the header's receipts root.
+    uint64 gasUsed;
+    /// @notice Status of the transaction's receipt, read in the guest from the verified legacy or typed receipt.
+    /// @dev False for pre-Byzantium receipts, which carry `postStateRoot` instead of a status.
+    bool transactionSucceeded;
+    /// @notice Post-state root of a pre-Byzantium receipt; zero for a receipt with a status.
+    bytes32 postStateRoot;
+}
+
+/// @notice Appended after `PublicValuesExtensionStruct` when the proof also fixes the block's transaction count.
+struct TransactionCountPublicValuesStruct {
+    bytes32 transactionHash;
+    uint64 transactionIndex;
+    /// @notice Proved by including the transaction at `transactionCount - 1` and excluding `transactionCount`.
+    uint64 transactionCount;
+    /// @notice `transactionCount - 1 - transactionIndex`; zero for the last transaction in the block.
+    uint64 indexFromEnd;
+}
+
+/// @notice Appended after `PublicValuesExtensionStruct` when the proof also covers a transaction in the next block.
+struct CrossBlockPublicValuesStruct {
+    uint64 firstBlockNumber;
+    bytes32 firstTransactionHash;
+    uint64 secondBlockNumber;
+    /// @notice Hash of the second block, whose parent hash the guest checked equals `blockHash`.
+    bytes32 secondBlockHash;
+    bytes32 secondTransactionHash;
+    uint64 secondTransactionIndex;
+    /// @notice True when both transactions are included, each against its own block's transactions root.
+    bool aIsEarlierBlock;
+}
+
 interface ITransactionInclusionVerifier {
     function verifyTransactionInclusionView(bytes calldata _publicValues, bytes calldata _proofBytes)
         external
@@ -48,6 +178,7 @@ contract TransactionInclusionVerifier {
     bytes32 public txInclusionProgramVKey;
 
     error OnlyOwner();
+    error UnexpectedAppendedSection(uint8 expected, uint8 actual);
 
     /// @notice Event emitted when a transaction inclusion proof is verified
     event TransactionInclusionVerified(
@@ -158,6 +289,103 @@ contract TransactionInclusionVerifier {
         return abi.decode(_publicValues, (PublicValuesStruct));
     }
 
+    /// @notice Decode public values together with the appended extension values.
+    /// @dev Reverts for proofs generated before `PublicValuesExtensionStruct` was committed.
+    function decodePublicValuesExtension(bytes calldata _publicValues)
+        external
+        pure
+        returns (PublicValuesStruct memory, PublicValuesExtensionStruct memory)
+    {
+        return abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct));
+    }
+
+    /// @notice Decode public values committed by an adjacency proof.
+    /// @dev Reverts when `_publicValues` does not carry the appended `AdjacentPublicValuesStruct`.
+    function decodeAdjacentPublicValues(bytes calldata _publicValues)
+        external
+        pure
+        returns (PublicValuesStruct memory, PublicValuesExtensionStruct memory, AdjacentPublicValuesStruct memory)
+    {
+        (PublicValuesStruct memory base, PublicValuesExtensionStruct memory extension) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct));
+        _requireAppendedSection(extension, APPENDED_SECTION_ADJACENT);
+        (,, AdjacentPublicValuesStruct memory adjacent) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct, AdjacentPublicValuesStruct));
+        return (base, extension, adjacent);
+    }
+
+    /// @notice Decode public values committed by a sender-ordering proof.
+    /// @dev Reverts when `_publicValues` does not carry the appended `SenderOrderingPublicValuesStruct`.
+    function decodeSenderOrderingPublicValues(bytes calldata _publicValues)
+        external
+        pure
+        returns (PublicValuesStruct memory, PublicValuesExtensionStruct memory, SenderOrderingPublicValuesStruct memory)
+    {
+        (PublicValuesStruct memory base, PublicValuesExtensionStruct memory extension) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct));
+        _requireAppendedSection(extension, APPENDED_SECTION_SENDER_ORDERING);
+        (,, SenderOrderingPublicValuesStruct memory ordering) = abi.decode(
+            _publicValues, (PublicValuesStruct, PublicValuesExtensionStruct, SenderOrderingPublicValuesStruct)
+        );
+        return (base, extension, ordering);
+    }
+
+    /// @notice Decode public values committed by a gas-used proof.
+    /// @dev Reverts when `_publicValues` does not carry the appended `GasUsedPublicValuesStruct`.
+    function decodeGasUsedPublicValues(bytes calldata _publicValues)
+        external
+        pure
+        returns (PublicValuesStruct memory, PublicValuesExtensionStruct memory, GasUsedPublicValuesStruct memory)
+    {
+        (PublicValuesStruct memory base, PublicValuesExtensionStruct memory extension) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct));
+        _requireAppendedSection(extension, APPENDED_SECTION_GAS_USED);
+        (,, GasUsedPublicValuesStruct memory gasUsed) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct, GasUsedPublicValuesStruct));
+        return (base, extension, gasUsed);
+    }
+
+    /// @notice Decode public values committed by a transaction-count proof.
+    /// @dev Reverts when `_publicValues` does not carry the appended `TransactionCountPublicValuesStruct`.
+    function decodeTransactionCountPublicValues(bytes calldata _publicValues)
+        external
+        pure
+        returns (
+            PublicValuesStruct memory,
+            PublicValuesExtensionStruct memory,
+            TransactionCountPublicValuesStruct memory
+        )
+    {
+        (PublicValuesStruct memory base, PublicValuesExtensionStruct memory extension) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct));
+        _requireAppendedSection(extension, APPENDED_SECTION_TRANSACTION_COUNT);
+        (,, TransactionCountPublicValuesStruct memory count) = abi.decode(
+            _publicValues, (PublicValuesStruct, PublicValuesExtensionStruct, TransactionCountPublicValuesStruct)
+        );
+        return (base, extension, count);
+    }
+
+    /// @notice Decode public values committed by a cross-block ordering proof.
+    /// @dev Reverts when `_publicValues` does not carry the appended `CrossBlockPublicValuesStruct`.
+    function decodeCrossBlockPublicValues(bytes calldata _publicValues)
+        external
+        pure
+        returns (PublicValuesStruct memory, PublicValuesExtensionStruct memory, CrossBlockPublicValuesStruct memory)
+    {
+        (PublicValuesStruct memory base, PublicValuesExtensionStruct memory extension) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct));
+        _requireAppendedSection(extension, APPENDED_SECTION_CROSS_BLOCK);
+        (,, CrossBlockPublicValuesStruct memory crossBlock) =
+            abi.decode(_publicValues, (PublicValuesStruct, PublicValuesExtensionStruct, CrossBlockPublicValuesStruct));
+        return (base, extension, crossBlock);
+    }
+
+    function _requireAppendedSection(PublicValuesExtensionStruct memory _extension, uint8 _expected) internal pure {
+        if (_extension.appendedSection != _expected) {
+            revert UnexpectedAppendedSection(_expected, _extension.appendedSection);
+        }
+    }
+
     function _verifyProofAndDecodePublicValues(bytes calldata _publicValues, bytes calldata _proofBytes)
         internal
         view
@@ -4,8 +4,14 @@ pragma solidity ^0.8.20;
 import {Test} from "forge-std/Test.sol";
 import {stdJson} from "forge-std/StdJson.sol";
 import {
+    AdjacentPublicValuesStruct,
+    CrossBlockPublicValuesStruct,
+    GasUsedPublicValuesStruct,
+    PublicValuesExtensionStruct,
+    SenderOrderingPublicValuesStruct,
     ITransactionInclusionVerifier,
     PublicValuesStruct,
+    TransactionCountPublicValuesStruct,
     TransactionInclusionVerifier
 } from "../src/TransactionInclusionVerifier.sol";
 import {SP1VerifierGateway} from "@sp1-contracts/SP1VerifierGateway.sol";
@@ -119,6 +125,164 @@ abstract contract TransactionInclusionVerifierTestBase is Test {
         _assertDecodedValues(fixture, publicValues);
     }
 
+    function test_DecodePublicValuesExtension() public view {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+        PublicValuesStruct memory base = txInclusionVerifier.decodePublicValues(fixture.publicValues);
+        PublicValuesExtensionStruct memory header =
+            PublicValuesExtensionStruct({headerRlpLength: 540, txType: 2, appendedSection: 0, claimedTransactionsRoot: base.verifiedAgainstRoot, effectiveGasPrice: 15 gwei, headerUnverified: false, decodeFailed: false, blockGasLimit: 30_000_000, blockGasUsed: 12_000_000, transactionSizeBytes: 112, slot: 4_700_013, beaconGenesisTime: 1_606_824_023, postMerge: true, proofDepth: 4, calldataHash: keccak256(hex"abababab"), provedInParent: false, accessListAddress: address(0), accessListStorageKey: bytes32(0), hasAccessListEntry: false});
+
+        (PublicValuesStruct memory decodedBase, PublicValuesExtensionStruct memory decodedHeader) =
+            txInclusionVerifier.decodePublicValuesExtension(abi.encode(base, header));
+
+        _assertDecodedValues(fixture, decodedBase);
+        assertEq(decodedHeader.headerRlpLength, 540);
+        assertEq(decodedHeader.txType, 2);
+        assertEq(decodedHeader.effectiveGasPrice, 15 gwei);
+        assertFalse(decodedHeader.headerUnverified);
+        assertFalse(decodedHeader.decodeFailed);
+        assertEq(decodedHeader.blockGasLimit, 30_000_000);
+        assertEq(decodedHeader.blockGasUsed, 12_000_000);
+        assertEq(decodedHeader.transactionSizeBytes, 112);
+        assertEq(decodedHeader.slot, 4_700_013);
+        assertTrue(decodedHeader.postMerge);
+        assertEq(decodedHeader.proofDepth, 4);
+        assertEq(decodedHeader.calldataHash, keccak256(hex"abababab"));
+    }
+
+    function test_DecodeAdjacentPublicValues() public view {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+        PublicValuesStruct memory base = txInclusionVerifier.decodePublicValues(fixture.publicValues);
+        AdjacentPublicValuesStruct memory adjacent = AdjacentPublicValuesStruct({
+            transactionHash: base.transactionHash,
+            nextTransactionHash: keccak256("next"),
+            nextTransactionIndex: base.transactionIndex + 1,
+            areAdjacent: true
+        });
+
+        PublicValuesExtensionStruct memory header =
+            PublicValuesExtensionStruct({headerRlpLength: 540, txType: 2, appendedSection: 1, claimedTransactionsRoot: base.verifiedAgainstRoot, effectiveGasPrice: 15 gwei, headerUnverified: false, decodeFailed: false, blockGasLimit: 30_000_000, blockGasUsed: 12_000_000, transactionSizeBytes: 112, slot: 4_700_013, beaconGenesisTime: 1_606_824_023, postMerge: true, proofDepth: 4, calldataHash: keccak256(hex"abababab"), provedInParent: false, accessListAddress: address(0), accessListStorageKey: bytes32(0), hasAccessListEntry: false});
+
+        (
+            PublicValuesStruct memory decodedBase,
+            PublicValuesExtensionStruct memory decodedHeader,
+            AdjacentPublicValuesStruct memory decodedAdjacent
+        ) = txInclusionVerifier.decodeAdjacentPublicValues(abi.encode(base, header, adjacent));
+
+        _assertDecodedValues(fixture, decodedBase);
+        assertEq(decodedHeader.headerRlpLength, 540);
+        assertEq(decodedAdjacent.transactionHash, base.transactionHash);
+        assertEq(decodedAdjacent.nextTransactionHash, keccak256("next"));
+        assertEq(decodedAdjacent.nextTransactionIndex, base.transactionIndex + 1);
+        assertTrue(decodedAdjacent.areAdjacent);
+    }
+
+    function test_DecodeSenderOrderingPublicValues() public view {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+        PublicValuesStruct memory base = txInclusionVerifier.decodePublicValues(fixture.publicValues);
+        PublicValuesExtensionStruct memory extension =
+            PublicValuesExtensionStruct({headerRlpLength: 540, txType: 2, appendedSection: 2, claimedTransactionsRoot: base.verifiedAgainstRoot, effectiveGasPrice: 15 gwei, headerUnverified: false, decodeFailed: false, blockGasLimit: 30_000_000, blockGasUsed: 12_000_000, transactionSizeBytes: 112, slot: 4_700_013, beaconGenesisTime: 1_606_824_023, postMerge: true, proofDepth: 4, calldataHash: keccak256(hex"abababab"), provedInParent: false, accessListAddress: address(0), accessListStorageKey: bytes32(0), hasAccessListEntry: false});
+        SenderOrderingPublicValuesStruct memory ordering = SenderOrderingPublicValuesStruct({
+            sender: address(0xCAFE),
+            firstTransactionHash: keccak256("first"),
+            firstIndex: 3,
+            secondTransactionHash: keccak256("second"),
+            secondIndex: 7,
+            inOrder: true
+        });
+
+        (,, SenderOrderingPublicValuesStruct memory decoded) =
+            txInclusionVerifier.decodeSenderOrderingPublicValues(abi.encode(base, extension, ordering));
+
+        assertEq(decoded.sender, address(0xCAFE));
+        assertEq(decoded.firstIndex, 3);
+        assertEq(decoded.secondIndex, 7);
+        assertTrue(decoded.inOrder);
+    }
+
+    function testRevert_DecodeSenderOrderingPublicValues_WrongSection() public {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+        PublicValuesStruct memory base = txInclusionVerifier.decodePublicValues(fixture.publicValues);
+        PublicValuesExtensionStruct memory extension =
+            PublicValuesExtensionStruct({headerRlpLength: 540, txType: 2, appendedSection: 1, claimedTransactionsRoot: base.verifiedAgainstRoot, effectiveGasPrice: 15 gwei, headerUnverified: false, decodeFailed: false, blockGasLimit: 30_000_000, blockGasUsed: 12_000_000, transactionSizeBytes: 112, slot: 4_700_013, beaconGenesisTime: 1_606_824_023, postMerge: true, proofDepth: 4, calldataHash: keccak256(hex"abababab"), provedInParent: false, accessListAddress: address(0), accessListStorageKey: bytes32(0), hasAccessListEntry: false});
+        AdjacentPublicValuesStruct memory adjacent = AdjacentPublicValuesStruct({
+            transactionHash: base.transactionHash,
+            nextTransactionHash: keccak256("next"),
+            nextTransactionIndex: base.transactionIndex + 1,
+            areAdjacent: true
+        });
+        bytes memory publicValues = abi.encode(base, extension, adjacent, bytes32(0), bytes32(0));
+
+        vm.expectRevert(abi.encodeWithSelector(TransactionInclusionVerifier.UnexpectedAppendedSection.selector, 2, 1));
+        txInclusionVerifier.decodeSenderOrderingPublicValues(publicValues);
+    }
+
+    function test_DecodeGasUsedPublicValues() public view {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+        PublicValuesStruct memory base = txInclusionVerifier.decodePublicValues(fixture.publicValues);
+        PublicValuesExtensionStruct memory extension =
+            PublicValuesExtensionStruct({headerRlpLength: 540, txType: 2, appendedSection: 3, claimedTransactionsRoot: bytes32(0), effectiveGasPrice: 15 gwei, headerUnverified: false, decodeFailed: false, blockGasLimit: 30_000_000, blockGasUsed: 12_000_000, transactionSizeBytes: 112, slot: 4_700_013, beaconGenesisTime: 1_606_824_023, postMerge: true, proofDepth: 4, calldataHash: keccak256(hex"abababab"), provedInParent: false, accessListAddress: address(0), accessListStorageKey: bytes32(0), hasAccessListEntry: false});
+        GasUsedPublicValuesStruct memory gasUsed = GasUsedPublicValuesStruct({
+            transactionHash: base.transactionHash,
+            transactionIndex: base.transactionIndex,
+            gasUsed: 21000,
+            transactionSucceeded: true,
+            postStateRoot: bytes32(0)
+        });
+
+        (,, GasUsedPublicValuesStruct memory decoded) =
+            txInclusionVerifier.decodeGasUsedPublicValues(abi.encode(base, extension, gasUsed));
+
+        assertEq(decoded.transactionHash, base.transactionHash);
+        assertEq(decoded.transactionIndex, base.transactionIndex);
+        assertEq(decoded.gasUsed, 21000);
+        assertTrue(decoded.transactionSucceeded);
+        assertEq(decoded.postStateRoot, bytes32(0));
+    }
+
+    function test_DecodeTransactionCountPublicValues() public view {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+        PublicValuesStruct memory base = txInclusionVerifier.decodePublicValues(fixture.publicValues);
+        PublicValuesExtensionStruct memory extension =
+            PublicValuesExtensionStruct({headerRlpLength: 540, txType: 2, appendedSection: 4, claimedTransactionsRoot: bytes32(0), effectiveGasPrice: 15 gwei, headerUnverified: false, decodeFailed: false, blockGasLimit: 30_000_000, blockGasUsed: 12_000_000, transactionSizeBytes: 112, slot: 4_700_013, beaconGenesisTime: 1_606_824_023, postMerge: true, proofDepth: 4, calldataHash: keccak256(hex"abababab"), provedInParent: false, accessListAddress: address(0), accessListStorageKey: bytes32(0), hasAccessListEntry: false});
+        TransactionCountPublicValuesStruct memory count = TransactionCountPublicValuesStruct({
+            transactionHash: base.transactionHash,
+            transactionIndex: base.transactionIndex,
+            transactionCount: base.transactionIndex + 1,
+            indexFromEnd: 0
+        });
+
+        (,, TransactionCountPublicValuesStruct memory decoded) =
+            txInclusionVerifier.decodeTransactionCountPublicValues(abi.encode(base, extension, count));
+
+        assertEq(decoded.transactionIndex, base.transactionIndex);
+        assertEq(decoded.transactionCount, base.transactionIndex + 1);
+        assertEq(decoded.indexFromEnd, 0);
+    }
+
+    function test_DecodeCrossBlockPublicValues() public view {
+        SP1ProofFixtureJson memory fixture = loadFixture();
+        PublicValuesStruct memory base = txInclusionVerifier.decodePublicValues(fixture.publicValues);
+        PublicValuesExtensionStruct memory extension =
+            PublicValuesExtensionStruct({headerRlpLength: 540, txType: 2, appendedSection: 5, claimedTransactionsRoot: bytes32(0), effectiveGasPrice: 15 gwei, headerUnverified: false, decodeFailed: false, blockGasLimit: 30_000_000, blockGasUsed: 12_000_000, transactionSizeBytes: 112, slot: 4_700_013, beaconGenesisTime: 1_606_824_023, postMerge: true, proofDepth: 4, calldataHash: keccak256(hex"abababab"), provedInParent: false, accessListAddress: address(0), accessListStorageKey: bytes32(0), hasAccessListEntry: false});
+        CrossBlockPublicValuesStruct memory crossBlock = CrossBlockPublicValuesStruct({
+            firstBlockNumber: base.blockNumber,
+            firstTransactionHash: base.transactionHash,
+            secondBlockNumber: base.blockNumber + 1,
+            secondBlockHash: keccak256("next-block"),
+            secondTransactionHash: keccak256("next-transaction"),
+            secondTransactionIndex: 0,
+            aIsEarlierBlock: true
+        });
+
+        (,, CrossBlockPublicValuesStruct memory decoded) =
+            txInclusionVerifier.decodeCrossBlockPublicValues(abi.encode(base, extension, crossBlock));
+
+        assertEq(decoded.firstBlockNumber, base.blockNumber);
+        assertEq(decoded.secondBlockNumber, base.blockNumber + 1);
+        assertEq(decoded.secondBlockHash, keccak256("next-block"));
+        assertTrue(decoded.aIsEarlierBlock);
+    }
+
     function test_UpdateVerificationKey() public {
         SP1ProofFixtureJson memory fixture = loadFixture();
         bytes32 newVKey = keccak256("new-vkey");
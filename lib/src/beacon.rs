@@ -0,0 +1,195 @@
+use alloy::transports::http::reqwest;
+use alloy_primitives::B256;
+use eyre::Result;
+use serde_json::Value;
+
+use crate::SlotInput;
+
+/// The execution block a beacon slot maps to, checked to be canonical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotBlock {
+    pub slot: SlotInput,
+    pub block_number: u64,
+    pub block_hash: B256,
+}
+
+/// A beacon node's REST API, for mapping consensus-layer slots to execution blocks.
+#[derive(Debug, Clone)]
+pub struct BeaconClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl BeaconClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// `data` of a GET response, or `None` when the node has no such resource.
+    async fn get(&self, path: &str) -> Result<Option<Value>> {
+        let response = self
+            .http
+            .get(format!("{}{}", self.base_url, path))
+            .send()
+            .await
+            .map_err(|e| eyre::eyre!("Beacon API request {} failed: {}", path, e))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(eyre::eyre!(
+                "Beacon API request {} returned {}",
+                path,
+                response.status()
+            ));
+        }
+        let body: Value = serde_json::from_str(&response.text().await?)?;
+        Ok(Some(body["data"].clone()))
+    }
+
+    pub async fn genesis_time(&self) -> Result<u64> {
+        let genesis = self
+            .get("/eth/v1/beacon/genesis")
+            .await?
+            .ok_or_else(|| eyre::eyre!("Beacon node returned no genesis"))?;
+        json_u64(&genesis["genesis_time"], "genesis_time")
+    }
+
+    /// The execution block proposed in `slot`, failing when the slot was missed or its block was
+    /// orphaned.
+    pub async fn execution_block_at_slot(&self, slot: u64) -> Result<SlotBlock> {
+        let beacon_genesis_time = self.genesis_time().await?;
+        let header = self
+            .get(&format!("/eth/v1/beacon/headers/{}", slot))
+            .await?;
+        let block = self.get(&format!("/eth/v2/beacon/blocks/{}", slot)).await?;
+        let (block_number, block_hash) =
+            execution_block_from_responses(slot, header.as_ref(), block.as_ref())?;
+
+        println!(
+            "Slot {} maps to execution block {} ({:?})",
+            slot, block_number, block_hash
+        );
+        Ok(SlotBlock {
+            slot: SlotInput {
+                slot,
+                beacon_genesis_time,
+            },
+            block_number,
+            block_hash,
+        })
+    }
+}
+
+/// Execution block number and hash from the `data` of a slot's beacon header and block responses,
+/// `None` where the node had no header or block for the slot.
+pub fn execution_block_from_responses(
+    slot: u64,
+    header: Option<&Value>,
+    block: Option<&Value>,
+) -> Result<(u64, B256)> {
+    let (Some(header), Some(block)) = (header, block) else {
+        return Err(eyre::eyre!("Slot {} was missed: no beacon block", slot));
+    };
+    if header["canonical"].as_bool() != Some(true) {
+        return Err(eyre::eyre!(
+            "The beacon block at slot {} is not canonical; it was orphaned",
+            slot
+        ));
+    }
+
+    let message = &block["message"];
+    let block_slot = json_u64(&message["slot"], "message.slot")?;
+    if block_slot != slot {
+        return Err(eyre::eyre!(
+            "Beacon node returned the block for slot {} when asked for slot {}",
+            block_slot,
+            slot
+        ));
+    }
+
+    let payload = &message["body"]["execution_payload"];
+    if payload.is_null() {
+        return Err(eyre::eyre!(
+            "The beacon block at slot {} has no execution payload; is it before the merge?",
+            slot
+        ));
+    }
+    let block_number = json_u64(&payload["block_number"], "execution_payload.block_number")?;
+    let block_hash = payload["block_hash"]
+        .as_str()
+        .ok_or_else(|| eyre::eyre!("Beacon block has no execution_payload.block_hash"))?
+        .parse::<B256>()?;
+    Ok((block_number, block_hash))
+}
+
+/// The beacon API encodes integers as decimal strings.
+fn json_u64(value: &Value, field: &str) -> Result<u64> {
+    value
+        .as_str()
+        .ok_or_else(|| eyre::eyre!("Beacon response has no {}", field))?
+        .parse()
+        .map_err(|e| eyre::eyre!("Beacon response {} is not an integer: {}", field, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn header(canonical: bool) -> Value {
+        json!({ "root": format!("{:?}", B256::repeat_byte(1)), "canonical": canonical })
+    }
+
+    fn block(slot: u64) -> Value {
+        json!({
+            "message": {
+                "slot": slot.to_string(),
+                "body": {
+                    "execution_payload": {
+                        "block_number": "15537394",
+                        "block_hash": format!("{:?}", B256::repeat_byte(2)),
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn slots_map_to_their_canonical_execution_block() {
+        assert_eq!(
+            execution_block_from_responses(4_700_013, Some(&header(true)), Some(&block(4_700_013)))
+                .unwrap(),
+            (15_537_394, B256::repeat_byte(2))
+        );
+
+        let missed = execution_block_from_responses(4_700_013, None, None).unwrap_err();
+        assert!(missed.to_string().contains("was missed"));
+
+        let orphaned = execution_block_from_responses(
+            4_700_013,
+            Some(&header(false)),
+            Some(&block(4_700_013)),
+        )
+        .unwrap_err();
+        assert!(orphaned.to_string().contains("orphaned"));
+
+        assert!(execution_block_from_responses(
+            4_700_013,
+            Some(&header(true)),
+            Some(&block(4_700_012))
+        )
+        .is_err());
+
+        let pre_merge = json!({ "message": { "slot": "10", "body": {} } });
+        assert!(
+            execution_block_from_responses(10, Some(&header(true)), Some(&pre_merge))
+                .unwrap_err()
+                .to_string()
+                .contains("no execution payload")
+        );
+    }
+}
@@ -0,0 +1,353 @@
+use alloy_consensus::Header;
+use alloy_primitives::{Bytes, B256};
+
+use crate::{
+    AccessListEntryInput, AccountState, CrossBlockInput, DecodeMode, GasUsedInput, ProvedHeader,
+    SenderOrderingInput, SlotInput, TransactionCountInput, TransactionInclusionInput,
+    TransactionProofInput, TrieBackendKind,
+};
+
+/// Intrinsic gas every transaction pays, which bounds how many fit under a block's gas limit.
+const MIN_TRANSACTION_GAS: u64 = 21_000;
+
+/// Why [`TransactionInclusionInputBuilder::build`] rejected an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputBuildError {
+    /// A required field was never set.
+    MissingField(&'static str),
+    /// The claim needs proof nodes but `merkle_proof` is empty. Only absence from the empty trie
+    /// is settled without any.
+    EmptyProof,
+    /// An included transaction's index is past the most transactions the block's gas limit admits.
+    IndexOutOfRange { index: u64, max_transactions: u64 },
+}
+
+impl std::fmt::Display for InputBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputBuildError::MissingField(field) => write!(f, "Missing required field: {}", field),
+            InputBuildError::EmptyProof => write!(f, "Merkle proof is empty"),
+            InputBuildError::IndexOutOfRange {
+                index,
+                max_transactions,
+            } => write!(
+                f,
+                "Transaction index {} does not fit in a block of at most {} transactions",
+                index, max_transactions
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InputBuildError {}
+
+/// Fluent construction of a [`TransactionInclusionInput`] that checks its invariants on `build`.
+#[derive(Debug, Clone, Default)]
+pub struct TransactionInclusionInputBuilder {
+    block_header: Option<Header>,
+    parent_block_header: Option<Header>,
+    committed_raw_transaction: Option<Bytes>,
+    sender_account: Option<AccountState>,
+    sender_account_proof: Vec<Bytes>,
+    raw_transaction: Bytes,
+    transaction_index: Option<u64>,
+    merkle_proof: Vec<Bytes>,
+    prove_absence: bool,
+    trie_backend: TrieBackendKind,
+    expected_block_hash: Option<B256>,
+    adjacent_transaction: Option<TransactionProofInput>,
+    sender_ordering: Option<SenderOrderingInput>,
+    claimed_root: Option<B256>,
+    gas_used: Option<GasUsedInput>,
+    transaction_count: Option<TransactionCountInput>,
+    cross_block: Option<CrossBlockInput>,
+    decode_mode: DecodeMode,
+    slot: Option<SlotInput>,
+    proved_header: ProvedHeader,
+    access_list_entry: Option<AccessListEntryInput>,
+}
+
+impl TransactionInclusionInputBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn block_header(mut self, header: Header) -> Self {
+        self.block_header = Some(header);
+        self
+    }
+
+    pub fn parent_block_header(mut self, header: Header) -> Self {
+        self.parent_block_header = Some(header);
+        self
+    }
+
+    pub fn committed_raw_transaction(mut self, raw_transaction: Bytes) -> Self {
+        self.committed_raw_transaction = Some(raw_transaction);
+        self
+    }
+
+    /// The sender account state and its proof against the parent state root.
+    pub fn sender_account(mut self, account: AccountState, proof: Vec<Bytes>) -> Self {
+        self.sender_account = Some(account);
+        self.sender_account_proof = proof;
+        self
+    }
+
+    /// Claim `raw_transaction` is stored at `transaction_index`.
+    pub fn transaction(
+        mut self,
+        transaction_index: u64,
+        raw_transaction: Bytes,
+        merkle_proof: Vec<Bytes>,
+    ) -> Self {
+        self.transaction_index = Some(transaction_index);
+        self.raw_transaction = raw_transaction;
+        self.merkle_proof = merkle_proof;
+        self.prove_absence = false;
+        self
+    }
+
+    /// Claim no transaction is stored at `transaction_index`.
+    pub fn absence(mut self, transaction_index: u64, merkle_proof: Vec<Bytes>) -> Self {
+        self.transaction_index = Some(transaction_index);
+        self.raw_transaction = Bytes::new();
+        self.merkle_proof = merkle_proof;
+        self.prove_absence = true;
+        self
+    }
+
+    pub fn trie_backend(mut self, trie_backend: TrieBackendKind) -> Self {
+        self.trie_backend = trie_backend;
+        self
+    }
+
+    pub fn expected_block_hash(mut self, block_hash: B256) -> Self {
+        self.expected_block_hash = Some(block_hash);
+        self
+    }
+
+    pub fn claimed_root(mut self, root: B256) -> Self {
+        self.claimed_root = Some(root);
+        self
+    }
+
+    pub fn adjacent_transaction(mut self, transaction: TransactionProofInput) -> Self {
+        self.adjacent_transaction = Some(transaction);
+        self
+    }
+
+    pub fn sender_ordering(mut self, sender_ordering: SenderOrderingInput) -> Self {
+        self.sender_ordering = Some(sender_ordering);
+        self
+    }
+
+    pub fn gas_used(mut self, gas_used: GasUsedInput) -> Self {
+        self.gas_used = Some(gas_used);
+        self
+    }
+
+    pub fn transaction_count(mut self, transaction_count: TransactionCountInput) -> Self {
+        self.transaction_count = Some(transaction_count);
+        self
+    }
+
+    pub fn cross_block(mut self, cross_block: CrossBlockInput) -> Self {
+        self.cross_block = Some(cross_block);
+        self
+    }
+
+    pub fn decode_mode(mut self, decode_mode: DecodeMode) -> Self {
+        self.decode_mode = decode_mode;
+        self
+    }
+
+    pub fn slot(mut self, slot: SlotInput) -> Self {
+        self.slot = Some(slot);
+        self
+    }
+
+    pub fn proved_header(mut self, proved_header: ProvedHeader) -> Self {
+        self.proved_header = proved_header;
+        self
+    }
+
+    pub fn access_list_entry(mut self, entry: AccessListEntryInput) -> Self {
+        self.access_list_entry = Some(entry);
+        self
+    }
+
+    /// Check the input's invariants and assemble it. Proofs are not verified here; see
+    /// [`crate::diagnose_input`] for that.
+    pub fn build(self) -> Result<TransactionInclusionInput, InputBuildError> {
+        let block_header = self
+            .block_header
+            .ok_or(InputBuildError::MissingField("block_header"))?;
+        let parent_block_header = self
+            .parent_block_header
+            .ok_or(InputBuildError::MissingField("parent_block_header"))?;
+        let committed_raw_transaction = self
+            .committed_raw_transaction
+            .ok_or(InputBuildError::MissingField("committed_raw_transaction"))?;
+        let sender_account = self
+            .sender_account
+            .ok_or(InputBuildError::MissingField("sender_account"))?;
+        let transaction_index = self
+            .transaction_index
+            .ok_or(InputBuildError::MissingField("transaction_index"))?;
+
+        // Index and root bounds come from whichever header the proofs are against
+        let proved_block_header = match self.proved_header {
+            ProvedHeader::Block => &block_header,
+            ProvedHeader::Parent => &parent_block_header,
+        };
+        if !self.prove_absence {
+            if self.raw_transaction.is_empty() {
+                return Err(InputBuildError::MissingField("raw_transaction"));
+            }
+            let max_transactions = proved_block_header.gas_limit / MIN_TRANSACTION_GAS;
+            if transaction_index >= max_transactions {
+                return Err(InputBuildError::IndexOutOfRange {
+                    index: transaction_index,
+                    max_transactions,
+                });
+            }
+        }
+
+        let root = self
+            .claimed_root
+            .unwrap_or(proved_block_header.transactions_root);
+        let settled_without_proof = self.prove_absence && root == alloy_trie::EMPTY_ROOT_HASH;
+        if self.merkle_proof.is_empty() && !settled_without_proof {
+            return Err(InputBuildError::EmptyProof);
+        }
+
+        Ok(TransactionInclusionInput {
+            block_header,
+            parent_block_header,
+            committed_raw_transaction,
+            sender_account,
+            sender_account_proof: self.sender_account_proof,
+            raw_transaction: self.raw_transaction,
+            transaction_index,
+            merkle_proof: self.merkle_proof,
+            prove_absence: self.prove_absence,
+            trie_backend: self.trie_backend,
+            expected_block_hash: self.expected_block_hash,
+            adjacent_transaction: self.adjacent_transaction,
+            sender_ordering: self.sender_ordering,
+            claimed_root: self.claimed_root,
+            gas_used: self.gas_used,
+            transaction_count: self.transaction_count,
+            cross_block: self.cross_block,
+            partial_header: None,
+            decode_mode: self.decode_mode,
+            slot: self.slot,
+            proved_header: self.proved_header,
+            access_list_entry: self.access_list_entry,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::mainnet_genesis_header;
+    use crate::{KeccakMptBackend, TrieBackend};
+    use alloy_primitives::U256;
+    use alloy_rlp::encode as rlp_encode;
+
+    fn account() -> AccountState {
+        AccountState {
+            nonce: 0,
+            balance: U256::ZERO,
+            storage_root: alloy_trie::EMPTY_ROOT_HASH,
+            code_hash: alloy_primitives::KECCAK256_EMPTY,
+        }
+    }
+
+    /// A builder for index 0 of a one-transaction block with a 30M gas limit.
+    fn builder() -> TransactionInclusionInputBuilder {
+        let raw_transaction = Bytes::from(vec![0x02; 40]);
+        let leaves = vec![(rlp_encode(U256::ZERO), raw_transaction.clone())];
+        let (root, proof) = KeccakMptBackend.generate_proof(&leaves, &leaves[0].0);
+
+        let parent_block_header = mainnet_genesis_header();
+        let mut block_header = parent_block_header.clone();
+        block_header.number = 1;
+        block_header.gas_limit = 30_000_000;
+        block_header.transactions_root = root;
+
+        TransactionInclusionInputBuilder::new()
+            .block_header(block_header)
+            .parent_block_header(parent_block_header)
+            .committed_raw_transaction(raw_transaction.clone())
+            .sender_account(account(), Vec::new())
+            .transaction(0, raw_transaction, proof)
+    }
+
+    #[test]
+    fn complete_builder_assembles_the_input() {
+        let input = builder()
+            .claimed_root(B256::repeat_byte(1))
+            .build()
+            .unwrap();
+        assert_eq!(input.transaction_index, 0);
+        assert!(!input.prove_absence);
+        assert_eq!(input.claimed_root, Some(B256::repeat_byte(1)));
+        assert!(input.adjacent_transaction.is_none());
+    }
+
+    #[test]
+    fn build_rejects_broken_inputs() {
+        let mut missing_header = builder();
+        missing_header.block_header = None;
+        assert_eq!(
+            missing_header.build().unwrap_err(),
+            InputBuildError::MissingField("block_header")
+        );
+
+        assert_eq!(
+            builder()
+                .transaction(0, Bytes::from(vec![0x02; 40]), Vec::new())
+                .build()
+                .unwrap_err(),
+            InputBuildError::EmptyProof
+        );
+
+        assert_eq!(
+            builder()
+                .transaction(1_428, Bytes::from(vec![0x02; 40]), vec![Bytes::new()])
+                .build()
+                .unwrap_err(),
+            InputBuildError::IndexOutOfRange {
+                index: 1_428,
+                max_transactions: 1_428
+            }
+        );
+    }
+
+    #[test]
+    fn absence_from_the_empty_trie_needs_no_proof() {
+        let mut empty_block = mainnet_genesis_header();
+        empty_block.number = 1;
+        assert_eq!(empty_block.transactions_root, alloy_trie::EMPTY_ROOT_HASH);
+
+        let input = builder()
+            .block_header(empty_block.clone())
+            .absence(5_000, Vec::new())
+            .build()
+            .unwrap();
+        assert!(input.prove_absence);
+
+        empty_block.transactions_root = B256::repeat_byte(3);
+        assert_eq!(
+            builder()
+                .block_header(empty_block)
+                .absence(0, Vec::new())
+                .build()
+                .unwrap_err(),
+            InputBuildError::EmptyProof
+        );
+    }
+}
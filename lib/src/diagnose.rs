@@ -0,0 +1,917 @@
+use alloy_consensus::{transaction::SignerRecoverable, Header, Transaction, TxEnvelope};
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{keccak256, Bytes, B256, U256};
+use alloy_rlp::encode as rlp_encode;
+use eyre::Result;
+
+use crate::{
+    receipt_gas_used, shared_sender, verification_root, verified_transaction_type,
+    verify_block_header_hash, verify_parent_linkage, walk_proof, DecodeMode, ProvedHeader,
+    ReceiptProofInput, TransactionInclusionInput, TransactionProofInput, TrieBackend,
+};
+
+/// A check the guest applies to a [`TransactionInclusionInput`], in the order it applies them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputCheck {
+    /// `partial_header` is not combined with anything that needs the full header.
+    PartialHeader,
+    /// The proved header hashes to `expected_block_hash`.
+    BlockHash,
+    /// `block_header` is the direct child of `parent_block_header`.
+    ParentLinkage,
+    /// The proved header was proposed at the start of the claimed `slot`.
+    Slot,
+    /// The committed transaction decodes and its signer recovers.
+    CommittedTransaction,
+    /// The sender account proof verifies against the parent state root.
+    SenderAccountProof,
+    /// The sender nonce equals the committed transaction's nonce.
+    SenderNonce,
+    /// The committed transaction pays at least the block base fee.
+    BaseFee,
+    /// The sender balance covers the committed transaction's upfront cost.
+    SenderBalance,
+    /// `claimed_root` equals the header transactions root.
+    ClaimedRoot,
+    /// `merkle_proof` proves the transaction at, or absence of, `transaction_index`.
+    TransactionProof,
+    /// The verified leaf decodes as a transaction.
+    TransactionType,
+    /// At most one optional section is requested.
+    AppendedSection,
+    AdjacentTransaction,
+    SenderOrdering,
+    GasUsed,
+    TransactionCount,
+    CrossBlock,
+}
+
+/// The outcome of one [`InputCheck`]; `error` explains a failure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckOutcome {
+    pub check: InputCheck,
+    pub error: Option<String>,
+}
+
+/// Which guest checks an input passes and why the others fail.
+///
+/// Checks that depend on a failed one, such as the sender nonce after the committed transaction
+/// fails to decode, are left out rather than reported as failures of their own. In
+/// [`DecodeMode::Lenient`] a decode failure is committed rather than rejected, so the decode
+/// checks are left out too.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputDiagnosis {
+    pub checks: Vec<CheckOutcome>,
+}
+
+impl InputDiagnosis {
+    /// True when every check ran and passed, so the guest would prove the claim without panicking.
+    pub fn is_valid(&self) -> bool {
+        self.checks.iter().all(|outcome| outcome.error.is_none())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &CheckOutcome> {
+        self.checks.iter().filter(|outcome| outcome.error.is_some())
+    }
+
+    fn record(&mut self, check: InputCheck, result: Result<()>) -> bool {
+        let error = result.err().map(|e| e.to_string());
+        let passed = error.is_none();
+        self.checks.push(CheckOutcome { check, error });
+        passed
+    }
+}
+
+/// Run the guest's checks on `input` on the host, without proving, and report each outcome.
+///
+/// Unlike the guest, this keeps going after a failure, so one run lists every independent problem.
+/// A proof that fails to verify is reported as a failure even where the guest would commit
+/// `isIncluded == false` instead of panicking, since the input then cannot prove its claim.
+pub fn diagnose_input(input: &TransactionInclusionInput) -> InputDiagnosis {
+    let mut diagnosis = InputDiagnosis::default();
+    let backend = input.trie_backend.backend();
+
+    // A partial header stands in for both headers, so nothing about them or the sender is checked
+    let partial_view;
+    let header = match &input.partial_header {
+        Some(partial) => {
+            diagnosis.record(InputCheck::PartialHeader, check_partial_header(input));
+            partial_view = Header {
+                number: partial.block_number,
+                transactions_root: partial.transactions_root,
+                ..Default::default()
+            };
+            &partial_view
+        }
+        None => {
+            diagnose_block_and_sender(&mut diagnosis, input, backend);
+            input.proved_block_header()
+        }
+    };
+
+    let root = match verification_root(header, input.claimed_root) {
+        Ok(root) => {
+            if input.claimed_root.is_some() {
+                diagnosis.record(InputCheck::ClaimedRoot, Ok(()));
+            }
+            root
+        }
+        Err(e) => {
+            diagnosis.record(InputCheck::ClaimedRoot, Err(e));
+            header.transactions_root
+        }
+    };
+
+    let key = rlp_encode(input.transaction_index);
+    let is_included = if input.prove_absence {
+        diagnosis.record(
+            InputCheck::TransactionProof,
+            backend
+                .verify_proof(root, &key, None, &input.merkle_proof)
+                .map_err(|e| {
+                    eyre::eyre!(
+                        "Absence proof for index {} does not verify: {}{}",
+                        input.transaction_index,
+                        e,
+                        where_proof_breaks(root, &key, None, &input.merkle_proof)
+                    )
+                }),
+        );
+        false
+    } else {
+        let included = diagnosis.record(
+            InputCheck::TransactionProof,
+            verify_transaction_proof(
+                backend,
+                root,
+                input.transaction_index,
+                &input.raw_transaction,
+                &input.merkle_proof,
+            ),
+        );
+        let leaf_type = verified_transaction_type(&input.raw_transaction);
+        if included && (leaf_type.is_ok() || input.decode_mode == DecodeMode::Strict) {
+            diagnosis.record(InputCheck::TransactionType, leaf_type.map(|_| ()));
+        }
+        included
+    };
+
+    let requested_sections = [
+        input.adjacent_transaction.is_some(),
+        input.sender_ordering.is_some(),
+        input.gas_used.is_some(),
+        input.transaction_count.is_some(),
+        input.cross_block.is_some(),
+    ];
+    if requested_sections
+        .iter()
+        .filter(|&&requested| requested)
+        .count()
+        > 1
+    {
+        diagnosis.record(
+            InputCheck::AppendedSection,
+            Err(eyre::eyre!(
+                "Adjacency, sender ordering, gas used, transaction count and cross-block ordering cannot be proved together"
+            )),
+        );
+    }
+
+    if let Some(adjacent) = &input.adjacent_transaction {
+        let result = require_inclusion(input, is_included, "Adjacency").and_then(|()| {
+            if Some(adjacent.transaction_index) != input.transaction_index.checked_add(1) {
+                return Err(eyre::eyre!(
+                    "Adjacent transaction index {} does not directly follow index {}",
+                    adjacent.transaction_index,
+                    input.transaction_index
+                ));
+            }
+            verify_proof_input(backend, root, adjacent)
+        });
+        diagnosis.record(InputCheck::AdjacentTransaction, result);
+    }
+
+    if let Some(ordering) = &input.sender_ordering {
+        let result = verify_proof_input(backend, root, &ordering.first)
+            .and_then(|()| verify_proof_input(backend, root, &ordering.second))
+            .and_then(|()| {
+                shared_sender(
+                    &ordering.first.raw_transaction,
+                    &ordering.second.raw_transaction,
+                )
+                .map(|_| ())
+            });
+        diagnosis.record(InputCheck::SenderOrdering, result);
+    }
+
+    if let Some(gas_used) = &input.gas_used {
+        let result = require_inclusion(input, is_included, "Gas used").and_then(|()| {
+            if gas_used.receipt.transaction_index != input.transaction_index {
+                return Err(eyre::eyre!(
+                    "Receipt index {} does not equal the transaction index {}",
+                    gas_used.receipt.transaction_index,
+                    input.transaction_index
+                ));
+            }
+            let previous_index = gas_used
+                .previous_receipt
+                .as_ref()
+                .map(|previous| previous.transaction_index);
+            if previous_index != input.transaction_index.checked_sub(1) {
+                return Err(eyre::eyre!(
+                    "Previous receipt index {:?} does not directly precede index {}",
+                    previous_index,
+                    input.transaction_index
+                ));
+            }
+            verify_receipt_proof(backend, header.receipts_root, &gas_used.receipt)?;
+            if let Some(previous) = &gas_used.previous_receipt {
+                verify_receipt_proof(backend, header.receipts_root, previous)?;
+            }
+            receipt_gas_used(
+                &gas_used.receipt.raw_receipt,
+                gas_used
+                    .previous_receipt
+                    .as_ref()
+                    .map(|previous| previous.raw_receipt.as_ref()),
+            )
+            .map(|_| ())
+        });
+        diagnosis.record(InputCheck::GasUsed, result);
+    }
+
+    if let Some(count) = &input.transaction_count {
+        let last = &count.last_transaction;
+        let result = require_inclusion(input, is_included, "Transaction count").and_then(|()| {
+            verify_proof_input(backend, root, last)?;
+            let transaction_count = last
+                .transaction_index
+                .checked_add(1)
+                .ok_or_else(|| eyre::eyre!("Transaction count overflowed"))?;
+            backend
+                .verify_proof(
+                    root,
+                    &rlp_encode(transaction_count),
+                    None,
+                    &count.absence_proof,
+                )
+                .map_err(|e| {
+                    eyre::eyre!(
+                        "Absence proof for index {} after the last transaction does not verify: {}",
+                        transaction_count,
+                        e
+                    )
+                })?;
+            if input.transaction_index > last.transaction_index {
+                return Err(eyre::eyre!(
+                    "Transaction index {} follows the last transaction index {}",
+                    input.transaction_index,
+                    last.transaction_index
+                ));
+            }
+            Ok(())
+        });
+        diagnosis.record(InputCheck::TransactionCount, result);
+    }
+
+    if let Some(cross_block) = &input.cross_block {
+        let result = require_inclusion(input, is_included, "Cross-block ordering")
+            .and_then(|()| verify_parent_linkage(header, &cross_block.next_block_header))
+            .and_then(|()| {
+                verify_proof_input(
+                    backend,
+                    cross_block.next_block_header.transactions_root,
+                    &cross_block.transaction,
+                )
+            });
+        diagnosis.record(InputCheck::CrossBlock, result);
+    }
+
+    diagnosis
+}
+
+fn check_partial_header(input: &TransactionInclusionInput) -> Result<()> {
+    if input.expected_block_hash.is_some() {
+        return Err(eyre::eyre!(
+            "A partial header cannot be bound to an expected block hash"
+        ));
+    }
+    if input.gas_used.is_some() || input.cross_block.is_some() || input.slot.is_some() {
+        return Err(eyre::eyre!(
+            "Gas used, cross-block ordering and a slot need the full block header"
+        ));
+    }
+    Ok(())
+}
+
+/// Checks on the full headers and the committed transaction's sender against the parent state.
+///
+/// The block hash and slot are checked on the proved header. Proving in the parent skips the
+/// sender checks, as the guest does.
+fn diagnose_block_and_sender(
+    diagnosis: &mut InputDiagnosis,
+    input: &TransactionInclusionInput,
+    backend: &dyn TrieBackend,
+) {
+    let header = &input.block_header;
+    let parent = &input.parent_block_header;
+    let proved = input.proved_block_header();
+
+    if let Some(expected_block_hash) = input.expected_block_hash {
+        diagnosis.record(
+            InputCheck::BlockHash,
+            verify_block_header_hash(proved, expected_block_hash),
+        );
+    }
+
+    diagnosis.record(
+        InputCheck::ParentLinkage,
+        verify_parent_linkage(parent, header),
+    );
+
+    if let Some(slot) = &input.slot {
+        diagnosis.record(InputCheck::Slot, slot.check_header(proved));
+    }
+
+    let committed = TxEnvelope::decode_2718_exact(&input.committed_raw_transaction)
+        .map_err(|e| eyre::eyre!("Committed transaction does not decode: {}", e))
+        .and_then(|tx| {
+            let sender = tx
+                .recover_signer()
+                .map_err(|e| eyre::eyre!("Committed transaction signer does not recover: {}", e))?;
+            Ok((tx, sender))
+        });
+    match committed {
+        Err(_) if input.decode_mode == DecodeMode::Lenient => {}
+        Err(e) => {
+            diagnosis.record(InputCheck::CommittedTransaction, Err(e));
+        }
+        Ok(_) if input.proved_header == ProvedHeader::Parent => {
+            diagnosis.record(InputCheck::CommittedTransaction, Ok(()));
+        }
+        Ok((tx, sender)) => {
+            diagnosis.record(InputCheck::CommittedTransaction, Ok(()));
+
+            let account = alloy_trie::TrieAccount {
+                nonce: input.sender_account.nonce,
+                balance: input.sender_account.balance,
+                storage_root: input.sender_account.storage_root,
+                code_hash: input.sender_account.code_hash,
+            };
+            diagnosis.record(
+                InputCheck::SenderAccountProof,
+                backend
+                    .verify_proof(
+                        parent.state_root,
+                        keccak256(sender).as_slice(),
+                        Some(&rlp_encode(account)),
+                        &input.sender_account_proof,
+                    )
+                    .map_err(|e| {
+                        eyre::eyre!(
+                            "Account proof for {:?} does not verify against parent state root {:?}: {}",
+                            sender,
+                            parent.state_root,
+                            e
+                        )
+                    }),
+            );
+
+            let nonce = if input.sender_account.nonce == tx.nonce() {
+                Ok(())
+            } else {
+                Err(eyre::eyre!(
+                    "Sender nonce is {}, transaction nonce is {}",
+                    input.sender_account.nonce,
+                    tx.nonce()
+                ))
+            };
+            diagnosis.record(InputCheck::SenderNonce, nonce);
+
+            if let Some(base_fee) = header.base_fee_per_gas {
+                let pays_base_fee = if tx.effective_tip_per_gas(base_fee).is_some() {
+                    Ok(())
+                } else {
+                    Err(eyre::eyre!(
+                        "Max fee per gas {} is below the block base fee {}",
+                        tx.max_fee_per_gas(),
+                        base_fee
+                    ))
+                };
+                diagnosis.record(InputCheck::BaseFee, pays_base_fee);
+            }
+
+            let gas_cost = U256::from(tx.gas_limit()) * U256::from(tx.max_fee_per_gas());
+            let blob_cost = match (tx.blob_gas_used(), tx.max_fee_per_blob_gas()) {
+                (Some(blob_gas_used), Some(max_fee_per_blob_gas)) => {
+                    U256::from(blob_gas_used) * U256::from(max_fee_per_blob_gas)
+                }
+                _ => U256::ZERO,
+            };
+            let balance = match gas_cost
+                .checked_add(blob_cost)
+                .and_then(|cost| cost.checked_add(tx.value()))
+            {
+                None => Err(eyre::eyre!("Transaction upfront cost overflowed")),
+                Some(upfront_cost) if input.sender_account.balance < upfront_cost => {
+                    Err(eyre::eyre!(
+                        "Sender balance {} is below upfront cost {}",
+                        input.sender_account.balance,
+                        upfront_cost
+                    ))
+                }
+                Some(_) => Ok(()),
+            };
+            diagnosis.record(InputCheck::SenderBalance, balance);
+        }
+    }
+}
+
+fn require_inclusion(
+    input: &TransactionInclusionInput,
+    is_included: bool,
+    section: &str,
+) -> Result<()> {
+    if input.prove_absence || !is_included {
+        return Err(eyre::eyre!(
+            "{} requires a verified inclusion proof for the transaction",
+            section
+        ));
+    }
+    Ok(())
+}
+
+fn verify_transaction_proof(
+    backend: &dyn TrieBackend,
+    root: B256,
+    transaction_index: u64,
+    raw_transaction: &[u8],
+    proof: &[Bytes],
+) -> Result<()> {
+    let key = rlp_encode(transaction_index);
+    backend
+        .verify_proof(root, &key, Some(raw_transaction), proof)
+        .map_err(|e| {
+            eyre::eyre!(
+                "Inclusion proof for index {} does not verify against {:?}: {}{}",
+                transaction_index,
+                root,
+                e,
+                where_proof_breaks(root, &key, Some(raw_transaction), proof)
+            )
+        })
+}
+
+/// `"; matched 3 of 4 nibbles then ..."` for a failed proof, locating where it breaks.
+fn where_proof_breaks(root: B256, key: &[u8], value: Option<&[u8]>, proof: &[Bytes]) -> String {
+    walk_proof(root, key, proof)
+        .failure(value)
+        .map(|failure| format!("; {}", failure))
+        .unwrap_or_default()
+}
+
+fn verify_proof_input(
+    backend: &dyn TrieBackend,
+    root: B256,
+    transaction: &TransactionProofInput,
+) -> Result<()> {
+    verify_transaction_proof(
+        backend,
+        root,
+        transaction.transaction_index,
+        &transaction.raw_transaction,
+        &transaction.merkle_proof,
+    )
+}
+
+fn verify_receipt_proof(
+    backend: &dyn TrieBackend,
+    receipts_root: B256,
+    receipt: &ReceiptProofInput,
+) -> Result<()> {
+    backend
+        .verify_proof(
+            receipts_root,
+            &rlp_encode(receipt.transaction_index),
+            Some(&receipt.raw_receipt),
+            &receipt.merkle_proof,
+        )
+        .map_err(|e| {
+            eyre::eyre!(
+                "Receipt proof for index {} does not verify against receipts root {:?}: {}",
+                receipt.transaction_index,
+                receipts_root,
+                e
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{mainnet_genesis_header, signed_rpc_transaction};
+    use crate::{
+        build_merkle_proof, encode_transaction_for_trie, AccountState, KeccakMptBackend,
+        TrieBackendKind,
+    };
+
+    /// An input the guest accepts: one signed transaction whose sender is the only account in the
+    /// parent state.
+    fn valid_input() -> TransactionInclusionInput {
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        let transaction = signed_rpc_transaction(&signer, 0);
+        let raw_transaction = encode_transaction_for_trie(&transaction).unwrap();
+
+        let sender_account = AccountState {
+            nonce: 0,
+            balance: U256::from(10).pow(U256::from(18)),
+            storage_root: alloy_trie::EMPTY_ROOT_HASH,
+            code_hash: alloy_primitives::KECCAK256_EMPTY,
+        };
+        let account_key = keccak256(signer.address()).to_vec();
+        let account_leaves = vec![(
+            account_key.clone(),
+            Bytes::from(rlp_encode(alloy_trie::TrieAccount {
+                nonce: sender_account.nonce,
+                balance: sender_account.balance,
+                storage_root: sender_account.storage_root,
+                code_hash: sender_account.code_hash,
+            })),
+        )];
+        let (state_root, sender_account_proof) =
+            KeccakMptBackend.generate_proof(&account_leaves, &account_key);
+
+        let mut parent_block_header = mainnet_genesis_header();
+        parent_block_header.state_root = state_root;
+        let mut block_header = parent_block_header.clone();
+        block_header.number = 1;
+        block_header.parent_hash = parent_block_header.hash_slow();
+        block_header.transactions_root =
+            KeccakMptBackend.build_root(&[(rlp_encode(U256::ZERO), raw_transaction.clone())]);
+
+        let bundle = build_merkle_proof(
+            &KeccakMptBackend,
+            &block_header,
+            &[transaction],
+            0,
+            &Default::default(),
+        )
+        .unwrap();
+
+        TransactionInclusionInput {
+            expected_block_hash: Some(block_header.hash_slow()),
+            block_header,
+            parent_block_header,
+            committed_raw_transaction: raw_transaction.clone(),
+            sender_account,
+            sender_account_proof,
+            raw_transaction,
+            transaction_index: 0,
+            merkle_proof: bundle.merkle_proof,
+            prove_absence: false,
+            trie_backend: TrieBackendKind::default(),
+            adjacent_transaction: None,
+            sender_ordering: None,
+            claimed_root: None,
+            gas_used: None,
+            transaction_count: None,
+            cross_block: None,
+            partial_header: None,
+            decode_mode: DecodeMode::default(),
+            slot: None,
+            proved_header: ProvedHeader::default(),
+            access_list_entry: None,
+        }
+    }
+
+    fn failed_checks(input: &TransactionInclusionInput) -> Vec<InputCheck> {
+        diagnose_input(input)
+            .failures()
+            .map(|outcome| outcome.check)
+            .collect()
+    }
+
+    #[test]
+    fn valid_input_passes_every_check() {
+        let diagnosis = diagnose_input(&valid_input());
+        assert!(diagnosis.is_valid(), "{:?}", diagnosis);
+        assert!(diagnosis
+            .checks
+            .iter()
+            .any(|outcome| outcome.check == InputCheck::SenderAccountProof));
+    }
+
+    #[test]
+    fn partial_header_inputs_skip_header_and_sender_checks() {
+        let mut full = valid_input();
+        assert!(full.clone().into_partial_header().is_err());
+        full.expected_block_hash = None;
+        let partial = full.clone().into_partial_header().unwrap();
+        assert!(
+            bincode::serialize(&partial).unwrap().len() < bincode::serialize(&full).unwrap().len()
+        );
+        assert_eq!(partial.block_number(), full.block_header.number);
+
+        let diagnosis = diagnose_input(&partial);
+        assert!(diagnosis.is_valid(), "{:?}", diagnosis);
+        assert!(!diagnosis.checks.iter().any(|outcome| matches!(
+            outcome.check,
+            InputCheck::ParentLinkage | InputCheck::SenderAccountProof
+        )));
+
+        // The transaction proof is still checked, against the partial root
+        let mut wrong_root = partial.clone();
+        wrong_root
+            .partial_header
+            .as_mut()
+            .unwrap()
+            .transactions_root = B256::repeat_byte(4);
+        assert_eq!(failed_checks(&wrong_root), [InputCheck::TransactionProof]);
+
+        let mut pinned = partial;
+        pinned.expected_block_hash = Some(B256::repeat_byte(5));
+        assert_eq!(failed_checks(&pinned), [InputCheck::PartialHeader]);
+    }
+
+    #[test]
+    fn relayed_root_inputs_prove_against_the_claimed_root_alone() {
+        let mut input = valid_input();
+        input.expected_block_hash = None;
+        let root = input.block_header.transactions_root;
+        assert!(input
+            .clone()
+            .into_relayed_root(B256::repeat_byte(6))
+            .is_err());
+
+        let relayed = input.into_relayed_root(root).unwrap();
+        assert_eq!(relayed.claimed_root, Some(root));
+        assert_eq!(relayed.block_header, Header::default());
+        let diagnosis = diagnose_input(&relayed);
+        assert!(diagnosis.is_valid(), "{:?}", diagnosis);
+        assert!(diagnosis
+            .checks
+            .iter()
+            .any(|outcome| outcome.check == InputCheck::ClaimedRoot));
+
+        // The claimed root is what the contract binds, so it cannot drift from the proven root
+        let mut drifted = relayed;
+        drifted.claimed_root = Some(B256::repeat_byte(6));
+        assert_eq!(failed_checks(&drifted), [InputCheck::ClaimedRoot]);
+    }
+
+    #[test]
+    fn lenient_inputs_commit_decode_failures_instead_of_failing() {
+        let mut input = valid_input();
+        input.committed_raw_transaction = Bytes::from_static(&[0x02, 0xc3, 0x01, 0x02]);
+        assert_eq!(failed_checks(&input), [InputCheck::CommittedTransaction]);
+
+        input.decode_mode = DecodeMode::Lenient;
+        let diagnosis = diagnose_input(&input);
+        assert!(diagnosis.is_valid(), "{:?}", diagnosis);
+        assert!(!diagnosis.checks.iter().any(|outcome| matches!(
+            outcome.check,
+            InputCheck::CommittedTransaction | InputCheck::SenderNonce
+        )));
+
+        // Other failures still fail in lenient mode
+        input.transaction_index = 1;
+        assert_eq!(failed_checks(&input), [InputCheck::TransactionProof]);
+    }
+
+    #[test]
+    fn committed_block_gas_is_bound_by_the_header_hash() {
+        let mut input = valid_input();
+        input.block_header.gas_used = 12_000_000;
+        input.expected_block_hash = Some(input.block_header.hash_slow());
+        assert_eq!(
+            input.committed_block_gas(),
+            (input.block_header.gas_limit, 12_000_000)
+        );
+        assert!(diagnose_input(&input).is_valid());
+
+        // A prover cannot claim a fuller block than the pinned header records
+        let mut tampered = input.clone();
+        tampered.block_header.gas_used += 1;
+        assert_eq!(failed_checks(&tampered), [InputCheck::BlockHash]);
+
+        input.expected_block_hash = None;
+        assert_eq!(
+            input.into_partial_header().unwrap().committed_block_gas(),
+            (0, 0)
+        );
+    }
+
+    #[test]
+    fn post_merge_is_read_from_the_header_difficulty() {
+        // Mainnet's last proof-of-work block and the first proof-of-stake block
+        let mut input = valid_input();
+        input.block_header.number = 15_537_393;
+        input.block_header.difficulty = U256::from(11_055_787_484_078_698u64);
+        assert!(!input.committed_post_merge());
+
+        input.block_header.number = 15_537_394;
+        input.block_header.difficulty = U256::ZERO;
+        assert!(input.committed_post_merge());
+        // Like every header field, the difficulty is bound to a pinned block hash
+        input.expected_block_hash = Some(input.block_header.hash_slow());
+        let mut tampered = input.clone();
+        tampered.block_header.difficulty = U256::from(1);
+        assert!(failed_checks(&tampered).contains(&InputCheck::BlockHash));
+
+        input.expected_block_hash = None;
+        assert!(!input.into_partial_header().unwrap().committed_post_merge());
+    }
+
+    #[test]
+    fn sidechain_block_fails_against_the_canonical_hash() {
+        let mut canonical = valid_input();
+        let canonical_hash = canonical.block_header.hash_slow();
+        let transactions_root = canonical.block_header.transactions_root;
+        canonical.expected_block_hash = Some(canonical_hash);
+        assert!(diagnose_input(&canonical).is_valid());
+
+        // An orphaned sibling at the same height holds the same transactions but hashes differently
+        let mut sidechain = canonical;
+        sidechain.block_header.timestamp += 12;
+        assert_eq!(sidechain.block_header.transactions_root, transactions_root);
+        assert_eq!(failed_checks(&sidechain), [InputCheck::BlockHash]);
+    }
+
+    #[test]
+    fn committed_transaction_size_is_the_verified_leaf_length() {
+        let input = valid_input();
+        assert!(diagnose_input(&input).is_valid());
+        let leaf_length = alloy_eips::eip2718::Encodable2718::encode_2718_len(
+            &TxEnvelope::decode_2718_exact(&input.raw_transaction).unwrap(),
+        );
+        assert_eq!(
+            input.committed_transaction_size().unwrap() as usize,
+            leaf_length
+        );
+
+        let mut absence = input;
+        absence.prove_absence = true;
+        absence.raw_transaction = Bytes::new();
+        assert_eq!(absence.committed_transaction_size().unwrap(), 0);
+    }
+
+    fn transaction_proof_error(input: &TransactionInclusionInput) -> String {
+        diagnose_input(input)
+            .failures()
+            .find(|outcome| outcome.check == InputCheck::TransactionProof)
+            .and_then(|outcome| outcome.error.clone())
+            .expect("transaction proof should fail")
+    }
+
+    #[test]
+    fn failed_proofs_report_where_they_break() {
+        let input = valid_input();
+
+        let mut truncated = input.clone();
+        truncated.merkle_proof.pop();
+        assert!(transaction_proof_error(&truncated)
+            .contains("matched 0 of 2 nibbles then the proof ended after 0 nodes"));
+
+        let mut wrong_leaf = input.clone();
+        let mut raw_transaction = wrong_leaf.raw_transaction.to_vec();
+        raw_transaction.push(0);
+        wrong_leaf.raw_transaction = raw_transaction.into();
+        assert!(transaction_proof_error(&wrong_leaf)
+            .contains("matched 2 of 2 nibbles then reached a leaf holding"));
+
+        let mut wrong_root = input;
+        wrong_root.block_header.transactions_root = B256::repeat_byte(7);
+        assert!(transaction_proof_error(&wrong_root).contains("then node 0 hash mismatch"));
+    }
+
+    #[test]
+    fn slot_must_match_the_block_timestamp() {
+        let mut input = valid_input();
+        let timestamp = input.block_header.timestamp;
+        input.slot = Some(crate::SlotInput {
+            slot: 0,
+            beacon_genesis_time: timestamp,
+        });
+        assert!(diagnose_input(&input).is_valid());
+
+        input.slot = Some(crate::SlotInput {
+            slot: 1,
+            beacon_genesis_time: timestamp,
+        });
+        assert_eq!(failed_checks(&input), [InputCheck::Slot]);
+
+        // A partial header carries no timestamp to check the slot against
+        assert!(input.clone().into_partial_header().is_err());
+        input.partial_header = Some(crate::PartialHeaderInput {
+            block_number: input.block_header.number,
+            transactions_root: input.block_header.transactions_root,
+        });
+        assert_eq!(failed_checks(&input), [InputCheck::PartialHeader]);
+    }
+
+    #[test]
+    fn broken_inputs_report_the_failing_check() {
+        let mut input = valid_input();
+        input.expected_block_hash = Some(B256::repeat_byte(1));
+        assert_eq!(failed_checks(&input), [InputCheck::BlockHash]);
+
+        let mut input = valid_input();
+        input.parent_block_header.gas_limit += 1;
+        assert_eq!(failed_checks(&input), [InputCheck::ParentLinkage]);
+
+        let mut input = valid_input();
+        input.sender_account.nonce = 1;
+        assert_eq!(
+            failed_checks(&input),
+            [InputCheck::SenderAccountProof, InputCheck::SenderNonce]
+        );
+
+        let mut input = valid_input();
+        input.committed_raw_transaction = Bytes::from_static(&[0x02, 0xc0]);
+        let diagnosis = diagnose_input(&input);
+        assert_eq!(
+            diagnosis
+                .failures()
+                .map(|outcome| outcome.check)
+                .collect::<Vec<_>>(),
+            [InputCheck::CommittedTransaction]
+        );
+        assert!(!diagnosis
+            .checks
+            .iter()
+            .any(|outcome| outcome.check == InputCheck::SenderNonce));
+
+        let mut input = valid_input();
+        input.claimed_root = Some(B256::repeat_byte(2));
+        assert_eq!(failed_checks(&input), [InputCheck::ClaimedRoot]);
+
+        let mut input = valid_input();
+        input.transaction_index = 1;
+        assert_eq!(failed_checks(&input), [InputCheck::TransactionProof]);
+
+        let mut input = valid_input();
+        input.prove_absence = true;
+        let diagnosis = diagnose_input(&input);
+        let failure = diagnosis.failures().next().unwrap();
+        assert_eq!(failure.check, InputCheck::TransactionProof);
+        assert!(failure.error.as_ref().unwrap().contains("Absence proof"));
+    }
+
+    #[test]
+    fn parent_inclusion_proves_against_the_linked_parent() {
+        use crate::test_utils::{
+            mainnet_block_1_header, MAINNET_BLOCK_1_HASH, MAINNET_GENESIS_HASH,
+        };
+        use crate::TransactionInclusionInputBuilder;
+
+        // Mainnet genesis and block 1 are a real linked pair, both with no transactions
+        let genesis = mainnet_genesis_header();
+        let block_1 = mainnet_block_1_header();
+        assert_eq!(block_1.hash_slow(), MAINNET_BLOCK_1_HASH);
+        crate::verify_parent_linkage(&genesis, &block_1).unwrap();
+
+        let committed = valid_input().committed_raw_transaction;
+        let input = TransactionInclusionInputBuilder::new()
+            .block_header(block_1.clone())
+            .parent_block_header(genesis.clone())
+            .committed_raw_transaction(committed)
+            .sender_account(valid_input().sender_account, Vec::new())
+            .absence(0, Vec::new())
+            .expected_block_hash(MAINNET_GENESIS_HASH)
+            .proved_header(ProvedHeader::Parent)
+            .build()
+            .unwrap();
+        let diagnosis = diagnose_input(&input);
+        assert!(diagnosis.is_valid(), "{:?}", diagnosis);
+        assert!(!diagnosis
+            .checks
+            .iter()
+            .any(|outcome| outcome.check == InputCheck::SenderAccountProof));
+        assert_eq!(input.block_number(), 0);
+        assert_eq!(
+            input.committed_block_gas(),
+            (genesis.gas_limit, genesis.gas_used)
+        );
+        assert!(input.clone().into_partial_header().is_err());
+
+        // The pair must still link, and the expected hash binds the parent, not the child
+        let mut unlinked = input.clone();
+        unlinked.block_header.parent_hash = B256::repeat_byte(1);
+        assert_eq!(failed_checks(&unlinked), [InputCheck::ParentLinkage]);
+        let mut pinned_to_child = input;
+        pinned_to_child.expected_block_hash = Some(MAINNET_BLOCK_1_HASH);
+        assert_eq!(failed_checks(&pinned_to_child), [InputCheck::BlockHash]);
+
+        // A block with a transaction proves it as the parent of its child
+        let included = valid_input();
+        let mut child_header = included.block_header.clone();
+        child_header.number = 2;
+        child_header.parent_hash = included.block_header.hash_slow();
+        child_header.transactions_root = alloy_trie::EMPTY_ROOT_HASH;
+        assert!(included.clone().into_parent_inclusion(genesis).is_err());
+        let in_parent = included.into_parent_inclusion(child_header).unwrap();
+        assert_eq!(in_parent.proved_header, ProvedHeader::Parent);
+        assert_eq!(in_parent.block_number(), 1);
+        assert!(diagnose_input(&in_parent).is_valid());
+    }
+}
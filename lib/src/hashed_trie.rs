@@ -0,0 +1,331 @@
+//! A Merkle Patricia Trie generic over the hash its nodes are referenced by, for cross-checking
+//! the keccak trie against an independent hash implementation.
+
+use alloy_primitives::{keccak256, Bytes, B256};
+use eyre::Result;
+
+use crate::trie::{walk_proof_with, TrieBackend};
+
+/// The hash a trie's nodes, and its root, are referenced by.
+pub trait TrieHasher {
+    fn hash(&self, data: &[u8]) -> B256;
+}
+
+/// `alloy_primitives::keccak256`, the hash [`crate::KeccakMptBackend`] uses.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Keccak256Hasher;
+
+impl TrieHasher for Keccak256Hasher {
+    fn hash(&self, data: &[u8]) -> B256 {
+        keccak256(data)
+    }
+}
+
+/// A Merkle Patricia Trie built from scratch under `H`, sharing no code with `alloy_trie`.
+///
+/// With [`Keccak256Hasher`] it computes the roots and proofs of [`crate::KeccakMptBackend`]. Swapping
+/// in another keccak implementation then tells a hashing divergence from a node encoding one. It is
+/// host-only: the guest always verifies with keccak.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HashedMptBackend<H>(pub H);
+
+/// A leaf's key as nibbles, with its value.
+type NibbleEntry<'a> = (Vec<u8>, &'a [u8]);
+
+impl<H: TrieHasher> HashedMptBackend<H> {
+    /// The root over `leaves`, and the nodes on the path to each of `targets` in root-to-leaf order.
+    fn build(&self, leaves: &[(Vec<u8>, Bytes)], targets: &[Vec<u8>]) -> (B256, Vec<Vec<Bytes>>) {
+        if leaves.is_empty() {
+            let root = self.0.hash(&[alloy_rlp::EMPTY_STRING_CODE]);
+            return (root, vec![Vec::new(); targets.len()]);
+        }
+
+        let mut entries: Vec<NibbleEntry> = leaves
+            .iter()
+            .map(|(key, value)| (unpack(key), value.as_ref()))
+            .collect();
+        entries.sort();
+        let targets: Vec<_> = targets.iter().map(|key| unpack(key)).collect();
+
+        let mut paths = vec![Vec::new(); targets.len()];
+        let root_node = self.node(&entries, 0, &targets, &mut paths);
+        let proofs = paths
+            .into_iter()
+            .map(|mut path| {
+                // Children are encoded before their parents, so restore root-to-leaf order by depth
+                path.sort_by_key(|(depth, _)| *depth);
+                path.into_iter().map(|(_, node)| node).collect()
+            })
+            .collect();
+        // The root is hashed whatever its length
+        (self.0.hash(&root_node), proofs)
+    }
+
+    /// Encode the node over the sorted `entries`, which share their first `depth` nibbles, and record
+    /// it in the path of every target that runs through it.
+    fn node(
+        &self,
+        entries: &[NibbleEntry],
+        depth: usize,
+        targets: &[Vec<u8>],
+        paths: &mut [Vec<(usize, Bytes)>],
+    ) -> Vec<u8> {
+        let (first, last) = (&entries[0].0, &entries[entries.len() - 1].0);
+        let encoded = if entries.len() == 1 {
+            rlp_list(&[
+                alloy_rlp::encode(&compact(&first[depth..], true)[..]),
+                alloy_rlp::encode(entries[0].1),
+            ])
+        } else {
+            // Sorted keys share a prefix exactly when the first and last do
+            let shared = first[depth..]
+                .iter()
+                .zip(&last[depth..])
+                .take_while(|(a, b)| a == b)
+                .count();
+            if shared > 0 {
+                let child = self.node(entries, depth + shared, targets, paths);
+                rlp_list(&[
+                    alloy_rlp::encode(&compact(&first[depth..depth + shared], false)[..]),
+                    self.reference(child),
+                ])
+            } else {
+                let mut items: Vec<Vec<u8>> = (0..16u8)
+                    .map(|nibble| {
+                        let child: Vec<_> = entries
+                            .iter()
+                            .filter(|(key, _)| key.get(depth) == Some(&nibble))
+                            .cloned()
+                            .collect();
+                        if child.is_empty() {
+                            vec![alloy_rlp::EMPTY_STRING_CODE]
+                        } else {
+                            let node = self.node(&child, depth + 1, targets, paths);
+                            self.reference(node)
+                        }
+                    })
+                    .collect();
+                let value = entries
+                    .iter()
+                    .find(|(key, _)| key.len() == depth)
+                    .map_or(&[][..], |(_, value)| *value);
+                items.push(alloy_rlp::encode(value));
+                rlp_list(&items)
+            }
+        };
+
+        for (target, path) in targets.iter().zip(paths.iter_mut()) {
+            if target.get(..depth) == Some(&first[..depth]) {
+                path.push((depth, Bytes::copy_from_slice(&encoded)));
+            }
+        }
+        encoded
+    }
+
+    /// Nodes of 32 bytes or more are referenced by hash, shorter ones inline.
+    fn reference(&self, node: Vec<u8>) -> Vec<u8> {
+        if node.len() < 32 {
+            node
+        } else {
+            alloy_rlp::encode(self.0.hash(&node))
+        }
+    }
+}
+
+impl<H: TrieHasher> TrieBackend for HashedMptBackend<H> {
+    fn build_root(&self, leaves: &[(Vec<u8>, Bytes)]) -> B256 {
+        self.build(leaves, &[]).0
+    }
+
+    fn generate_proof(&self, leaves: &[(Vec<u8>, Bytes)], target_key: &[u8]) -> (B256, Vec<Bytes>) {
+        let (root, mut proofs) = self.build(leaves, &[target_key.to_vec()]);
+        (root, proofs.remove(0))
+    }
+
+    fn generate_proofs(
+        &self,
+        leaves: &[(Vec<u8>, Bytes)],
+        target_keys: &[Vec<u8>],
+    ) -> (B256, Vec<Vec<Bytes>>) {
+        self.build(leaves, target_keys)
+    }
+
+    fn verify_proof(
+        &self,
+        root: B256,
+        key: &[u8],
+        value: Option<&[u8]>,
+        proof: &[Bytes],
+    ) -> Result<()> {
+        // The empty trie holds no leaves, whatever proof nodes came with the claim
+        if root == self.0.hash(&[alloy_rlp::EMPTY_STRING_CODE]) {
+            return match value {
+                Some(_) => Err(eyre::eyre!("The empty trie contains no values")),
+                None => Ok(()),
+            };
+        }
+
+        let walk = walk_proof_with(|node| self.0.hash(node), root, key, proof);
+        match walk.failure(value) {
+            None => Ok(()),
+            Some(reason) => Err(eyre::eyre!("Proof does not verify: {}", reason)),
+        }
+    }
+}
+
+fn unpack(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let payload = items.concat();
+    let mut encoded = Vec::new();
+    alloy_rlp::Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut encoded);
+    encoded.extend(payload);
+    encoded
+}
+
+/// Hex-prefix encoding of a node's key nibbles.
+fn compact(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 2 } else { 0 } + nibbles.len() as u8 % 2;
+    let (mut encoded, rest) = if nibbles.len() % 2 == 1 {
+        (vec![flag << 4 | nibbles[0]], &nibbles[1..])
+    } else {
+        (vec![flag << 4], nibbles)
+    };
+    encoded.extend(rest.chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeccakMptBackend;
+    use alloy_primitives::U256;
+    use alloy_rlp::encode as rlp_encode;
+
+    /// Keccak from `sha3`, independent of the `tiny-keccak` backing `alloy_primitives::keccak256`.
+    struct Sha3Keccak;
+
+    impl TrieHasher for Sha3Keccak {
+        fn hash(&self, data: &[u8]) -> B256 {
+            use sha3::{Digest, Keccak256};
+            B256::from_slice(&Keccak256::digest(data))
+        }
+    }
+
+    fn leaves(count: usize) -> Vec<(Vec<u8>, Bytes)> {
+        (0..count)
+            .map(|i| {
+                (
+                    rlp_encode(U256::from(i)),
+                    Bytes::from(vec![i as u8; 40 + i % 7]),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn roots_and_proofs_match_the_keccak_backend_under_an_independent_keccak() {
+        let backend = HashedMptBackend(Sha3Keccak);
+        assert_eq!(backend.build_root(&[]), alloy_trie::EMPTY_ROOT_HASH);
+
+        for count in [1, 2, 17, 200, 300] {
+            let leaves = leaves(count);
+            let targets: Vec<_> = [0, count / 2, count - 1, count]
+                .map(|index| rlp_encode(U256::from(index)))
+                .into();
+            let expected = KeccakMptBackend.generate_proofs(&leaves, &targets);
+            assert_eq!(
+                backend.generate_proofs(&leaves, &targets),
+                expected,
+                "{}",
+                count
+            );
+            assert_eq!(
+                HashedMptBackend(Keccak256Hasher).generate_proofs(&leaves, &targets),
+                expected,
+                "{}",
+                count
+            );
+
+            let (root, proofs) = expected;
+            for (index, (target, proof)) in targets.iter().zip(&proofs).enumerate() {
+                let value = leaves.get([0, count / 2, count - 1, count][index]);
+                let value = value.map(|(_, value)| value.as_ref());
+                backend
+                    .verify_proof(root, target, value, proof)
+                    .expect("keccak backend proof should verify");
+            }
+        }
+    }
+
+    #[test]
+    fn swapping_the_hash_changes_the_root_and_rejects_keccak_proofs() {
+        struct PrefixedKeccak;
+
+        impl TrieHasher for PrefixedKeccak {
+            fn hash(&self, data: &[u8]) -> B256 {
+                keccak256([b"prefix", data].concat())
+            }
+        }
+
+        let leaves = leaves(17);
+        let target = rlp_encode(U256::from(5));
+        let (root, proof) = KeccakMptBackend.generate_proof(&leaves, &target);
+        let backend = HashedMptBackend(PrefixedKeccak);
+        let (other_root, other_proof) = backend.generate_proof(&leaves, &target);
+
+        assert_ne!(other_root, root);
+        // The nodes only differ in how they reference their children
+        assert_eq!(other_proof.len(), proof.len());
+        assert!(backend
+            .verify_proof(other_root, &target, Some(&leaves[5].1), &other_proof)
+            .is_ok());
+        assert!(backend
+            .verify_proof(root, &target, Some(&leaves[5].1), &proof)
+            .is_err());
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+        use proptest::sample::Index;
+
+        /// Keys over a four-symbol byte alphabet so random sets share prefixes and exercise
+        /// extension and branch nodes. Values of at least 32 bytes keep every node hashed.
+        fn entries() -> impl Strategy<Value = Vec<(Vec<u8>, Bytes)>> {
+            prop::collection::btree_map(
+                prop::array::uniform4(0u8..4),
+                prop::collection::vec(any::<u8>(), 32..64),
+                1..64,
+            )
+            .prop_map(|entries| {
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.to_vec(), Bytes::from(value)))
+                    .collect()
+            })
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(128))]
+
+            #[test]
+            fn proofs_match_the_keccak_backend(leaves in entries(), target in any::<Index>()) {
+                let (key, value) = &leaves[target.index(leaves.len())];
+                let expected = KeccakMptBackend.generate_proof(&leaves, key);
+
+                let backend = HashedMptBackend(Sha3Keccak);
+                prop_assert_eq!(backend.generate_proof(&leaves, key), expected.clone());
+                prop_assert!(backend.verify_proof(expected.0, key, Some(value), &expected.1).is_ok());
+            }
+        }
+    }
+}
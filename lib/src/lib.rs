@@ -1,11 +1,32 @@
-use alloy::providers::Provider;
-use alloy_consensus::{transaction::SignerRecoverable, Header, Transaction, TxEnvelope};
-use alloy_eips::eip2718::Decodable2718;
+use alloy_consensus::Header;
 use alloy_primitives::{Address, Bytes, B256, U256};
-use alloy_rpc_types::{BlockId, BlockTransactions};
 use eyre::Result;
 use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "rpc")]
+mod beacon;
+mod builder;
+mod diagnose;
+#[cfg(feature = "trie-hasher")]
+mod hashed_trie;
+#[cfg(feature = "rpc")]
+mod rpc;
+mod trie;
+
+#[cfg(feature = "rpc")]
+pub use beacon::{execution_block_from_responses, BeaconClient, SlotBlock};
+pub use builder::{InputBuildError, TransactionInclusionInputBuilder};
+pub use diagnose::{diagnose_input, CheckOutcome, InputCheck, InputDiagnosis};
+#[cfg(feature = "trie-hasher")]
+pub use hashed_trie::{HashedMptBackend, Keccak256Hasher, TrieHasher};
+#[cfg(feature = "rpc")]
+pub use rpc::*;
+pub use trie::{
+    dedup_proof_nodes, prune_proof_nodes, single_leaf_proof, walk_proof, KeccakMptBackend,
+    ProofWalk, ProofWalkEnd, TrieBackend, TrieBackendKind,
+};
 
 // Test transaction hashes
 pub const INCLUDED_TX: &str = "0xd54acc3d86cf83ee241a6ad2cc5d394e91d142b85c96d7611b72bc267a9f9436";
@@ -30,370 +51,3053 @@ pub struct TransactionInclusionInput {
     pub merkle_proof: Vec<Bytes>,
     /// When true, prove that no transaction exists at the precise index.
     pub prove_absence: bool,
+    /// Trie backend the guest uses to verify `merkle_proof`.
+    pub trie_backend: TrieBackendKind,
+    /// Block hash the proof was requested for; the guest asserts the proved header hashes
+    /// to it.
+    pub expected_block_hash: Option<B256>,
+    /// Transaction at `transaction_index + 1`, when proving the two transactions are adjacent.
+    pub adjacent_transaction: Option<TransactionProofInput>,
+    /// Two transactions from one sender, when proving their relative order in the block.
+    pub sender_ordering: Option<SenderOrderingInput>,
+    /// Transactions root the caller trusts, e.g. from a header relay; must equal the proved header's transactions root.
+    pub claimed_root: Option<B256>,
+    /// Receipts at `transaction_index` and the index before it, when proving the transaction's gas used.
+    pub gas_used: Option<GasUsedInput>,
+    /// The block's last transaction and an absence proof after it, when proving the transaction count.
+    pub transaction_count: Option<TransactionCountInput>,
+    /// The child of `block_header` and a transaction in it, when proving ordering across the two blocks.
+    pub cross_block: Option<CrossBlockInput>,
+    /// Block number and transactions root standing in for both headers, which the guest then
+    /// ignores; see [`TransactionInclusionInput::into_partial_header`].
+    pub partial_header: Option<PartialHeaderInput>,
+    /// Whether a committed transaction or verified leaf that fails to decode aborts the proof.
+    pub decode_mode: DecodeMode,
+    /// Beacon slot `block_header` was proposed in, for commitments keyed by slot; the guest checks
+    /// it against the header timestamp.
+    pub slot: Option<SlotInput>,
+    /// Which of the two linked headers the transaction proofs are against; see [`ProvedHeader`].
+    pub proved_header: ProvedHeader,
+    /// Address and storage key the guest looks up in the included transaction's access list,
+    /// committing whether it is there.
+    pub access_list_entry: Option<AccessListEntryInput>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-/// Proof result showing whether a transaction is included at the expected precise index
-pub struct TransactionInclusionProof {
-    pub block_hash: B256,
-    pub block_number: u64,
-    pub committed_transaction_hash: B256,
-    pub transaction_hash: B256,
-    pub transaction_index: u64,
-    pub is_included: bool,
-    pub transaction_can_be_included: bool,
-    pub verified_against_root: B256,
+/// An EIP-2930 access-list address and one of its storage keys, for attesting that a transaction
+/// declared them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessListEntryInput {
+    pub address: Address,
+    pub storage_key: B256,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AccountState {
-    pub nonce: u64,
-    pub balance: U256,
-    pub storage_root: B256,
-    pub code_hash: B256,
+impl AccessListEntryInput {
+    /// Whether `raw_transaction` decodes and its access list holds `storage_key` under `address`.
+    /// Legacy transactions have no access list, so never hold the entry.
+    pub fn is_in(&self, raw_transaction: &[u8]) -> bool {
+        use alloy_consensus::Transaction;
+        use alloy_eips::eip2718::Decodable2718;
+
+        alloy_consensus::TxEnvelope::decode_2718_exact(raw_transaction).is_ok_and(|tx| {
+            tx.access_list().is_some_and(|access_list| {
+                access_list.iter().any(|item| {
+                    item.address == self.address && item.storage_keys.contains(&self.storage_key)
+                })
+            })
+        })
+    }
 }
 
-#[derive(Debug, Clone)]
-pub struct SenderAccountWitness {
-    pub parent_block_header: Header,
-    pub sender: Address,
-    pub account: AccountState,
-    pub proof: Vec<Bytes>,
+impl std::str::FromStr for AccessListEntryInput {
+    type Err = eyre::Report;
+
+    /// Parse `ADDRESS:STORAGE_KEY`.
+    fn from_str(s: &str) -> Result<Self> {
+        let (address, storage_key) = s.split_once(':').ok_or_else(|| {
+            eyre::eyre!("Access-list entry must be ADDRESS:STORAGE_KEY, got {}", s)
+        })?;
+        Ok(AccessListEntryInput {
+            address: address.parse()?,
+            storage_key: storage_key.parse()?,
+        })
+    }
 }
 
-/// Generate real Merkle proof for a transaction at a precise index in a block with exact Ethereum encoding
-pub async fn generate_merkle_proof(
-    provider: &impl Provider,
-    block_number: u64,
-    tx_index: u64,
-) -> Result<(Vec<Bytes>, Bytes)> {
-    use alloy_primitives::U256;
-    use alloy_rlp::encode as rlp_encode;
-    use alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles};
+/// How the guest treats a committed transaction or verified leaf that fails to decode, or whose
+/// signer does not recover.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecodeMode {
+    /// Abort, so no valid proof exists for the input.
+    #[default]
+    Strict,
+    /// Commit zero sentinels with `decodeFailed` set; the committed transaction is then not
+    /// includable.
+    Lenient,
+}
 
-    println!(
-        "Generating Merkle proof for transaction at precise index {} in block {} using alloy-trie",
-        tx_index, block_number
-    );
+impl std::str::FromStr for DecodeMode {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "strict" => Ok(DecodeMode::Strict),
+            "lenient" => Ok(DecodeMode::Lenient),
+            _ => Err(eyre::eyre!(
+                "Unknown decode mode: {} (expected: strict, lenient)",
+                s
+            )),
+        }
+    }
+}
+
+/// Which header a [`TransactionInclusionInput`] proves inclusion in.
+///
+/// Some commitments name a block by its parent ("the block whose parent is X"), so a proof built
+/// against the wrong one of the pair silently proves the wrong claim. With both headers supplied
+/// and linked, the caller picks one, and the guest commits which it was as `provedInParent`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProvedHeader {
+    /// Prove against `block_header`, with the committed transaction checked for inclusion there.
+    #[default]
+    Block,
+    /// Prove against `parent_block_header`. Every committed header field, including the block hash
+    /// and number, is the parent's. The sender witness is not checked, so the committed
+    /// transaction is never reported includable.
+    Parent,
+}
+
+impl std::str::FromStr for ProvedHeader {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "block" => Ok(ProvedHeader::Block),
+            "parent" => Ok(ProvedHeader::Parent),
+            _ => Err(eyre::eyre!(
+                "Unknown proved header: {} (expected: block, parent)",
+                s
+            )),
+        }
+    }
+}
+
+/// The only header fields the transaction proofs need.
+///
+/// Nothing binds these to a canonical block: the guest commits a zero block hash and
+/// `headerUnverified`, and cannot check that the committed transaction was includable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialHeaderInput {
+    pub block_number: u64,
+    pub transactions_root: B256,
+}
 
-    // Fetch the block with FULL transaction details
+/// Length of a beacon chain slot on every Ethereum network.
+pub const SECONDS_PER_SLOT: u64 = 12;
 
-    let block = provider
-        .get_block(BlockId::Number(block_number.into()))
-        .full()
-        .await?
-        .ok_or_else(|| eyre::eyre!("Block not found: {}", block_number))?;
+/// A beacon slot and the genesis time of its chain, which together fix the timestamp of the
+/// execution block proposed in the slot.
+///
+/// The guest commits both, so a verifier pins the genesis time of the network it expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlotInput {
+    pub slot: u64,
+    pub beacon_genesis_time: u64,
+}
+
+impl SlotInput {
+    /// Timestamp of an execution block proposed in this slot.
+    pub fn timestamp(&self) -> Option<u64> {
+        self.slot
+            .checked_mul(SECONDS_PER_SLOT)?
+            .checked_add(self.beacon_genesis_time)
+    }
+
+    /// Check `header` was proposed in this slot, as the guest does.
+    pub fn check_header(&self, header: &Header) -> Result<()> {
+        if self.timestamp() != Some(header.timestamp) {
+            return Err(eyre::eyre!(
+                "Block {} has timestamp {}, not the start of slot {} ({:?})",
+                header.number,
+                header.timestamp,
+                self.slot,
+                self.timestamp()
+            ));
+        }
+        Ok(())
+    }
+}
 
-    // Extract full transactions from block (we requested .full() so should always get Full variant)
-    let complete_transactions = match &block.transactions {
-        BlockTransactions::Full(txs) => {
-            println!("Got {} full transactions from block", txs.len());
-            txs.clone()
+impl TransactionInclusionInput {
+    /// Replace both headers and the sender witness with the block number and transactions root,
+    /// for monitoring that does not need the proof bound to a canonical block.
+    ///
+    /// Fails for inputs that need the full header: a pinned block hash, gas used (the receipts
+    /// root), cross-block ordering (the block hash) or a proof in the parent (the linkage).
+    pub fn into_partial_header(mut self) -> Result<Self> {
+        if self.proved_header == ProvedHeader::Parent {
+            return Err(eyre::eyre!(
+                "A partial header cannot prove inclusion in the parent block"
+            ));
         }
-        BlockTransactions::Hashes(_) => {
+        if self.expected_block_hash.is_some() {
             return Err(eyre::eyre!(
-                "Expected full transactions but got hashes - ensure .full() is used"
+                "A partial header cannot be bound to an expected block hash"
             ));
         }
-        _ => {
-            return Err(eyre::eyre!("Unexpected transaction format"));
+        if self.gas_used.is_some() || self.cross_block.is_some() || self.slot.is_some() {
+            return Err(eyre::eyre!(
+                "Gas used, cross-block ordering and a slot need the full block header"
+            ));
         }
-    };
 
-    if tx_index as usize >= complete_transactions.len() {
-        return Err(eyre::eyre!(
-            "Transaction index {} out of range (max: {})",
-            tx_index,
-            complete_transactions.len() - 1
-        ));
+        self.partial_header = Some(PartialHeaderInput {
+            block_number: self.block_header.number,
+            transactions_root: self.block_header.transactions_root,
+        });
+        self.block_header = Header::default();
+        self.parent_block_header = Header::default();
+        self.sender_account = AccountState {
+            nonce: 0,
+            balance: U256::ZERO,
+            storage_root: B256::ZERO,
+            code_hash: B256::ZERO,
+        };
+        self.sender_account_proof = Vec::new();
+        Ok(self)
     }
 
-    println!(
-        "Successfully got all {} transactions from block!",
-        complete_transactions.len()
-    );
-    println!("Building Ethereum transaction trie using alloy-trie with proof generation...");
+    /// Prove against `relayed_root`, a transactions root an L1 contract holds (e.g. an L2 block's
+    /// root read from the rollup's storage), with no header: a partial header whose root is also
+    /// committed as `claimed_root`.
+    ///
+    /// Trust is split. The proof shows the transaction sits at the index under `relayed_root`; the
+    /// contract must establish that the root is genuine with its own storage proof. Fails when
+    /// `relayed_root` is not the root the transaction proofs were built against.
+    pub fn into_relayed_root(mut self, relayed_root: B256) -> Result<Self> {
+        if relayed_root != self.block_header.transactions_root {
+            return Err(eyre::eyre!(
+                "Relayed root {:?} is not the transactions root {:?} of block {}",
+                relayed_root,
+                self.block_header.transactions_root,
+                self.block_header.number
+            ));
+        }
+        self.claimed_root = Some(relayed_root);
+        self.into_partial_header()
+    }
 
-    // Step 1: Create ProofRetainer and configure it with the target transaction key
-    let target_key = rlp_encode(U256::from(tx_index));
-    let target_nibbles = Nibbles::unpack(&target_key);
+    /// Prove against this input's block as the parent of `child_header`, for commitments that name
+    /// a block by its parent. The transaction proofs are kept; the sender witness is left in place
+    /// but the guest no longer checks it. Fails when `child_header` is not the block's direct child.
+    pub fn into_parent_inclusion(mut self, child_header: Header) -> Result<Self> {
+        verify_parent_linkage(&self.block_header, &child_header)?;
+        self.parent_block_header = std::mem::replace(&mut self.block_header, child_header);
+        self.proved_header = ProvedHeader::Parent;
+        Ok(self)
+    }
 
-    // ProofRetainer needs to know the target keys BEFORE trie construction
-    let proof_retainer = ProofRetainer::from_iter([target_nibbles.clone()]);
-    let mut trie_builder = HashBuilder::default().with_proof_retainer(proof_retainer);
+    /// The header the transaction proofs are against and every committed header field is read
+    /// from: `block_header`, or `parent_block_header` under [`ProvedHeader::Parent`].
+    pub fn proved_block_header(&self) -> &Header {
+        match self.proved_header {
+            ProvedHeader::Block => &self.block_header,
+            ProvedHeader::Parent => &self.parent_block_header,
+        }
+    }
 
-    // Step 2: Prepare transaction data
-    let mut encoded_transactions = Vec::with_capacity(complete_transactions.len());
-    let mut key_value_pairs = Vec::new();
+    /// The `transactionSizeBytes` the guest commits once the inclusion proof verifies: the length of
+    /// `raw_transaction`, as logged when the proof was built, or zero for absence. A length past
+    /// `u32::MAX` is an error, as it makes the guest panic.
+    pub fn committed_transaction_size(&self) -> Result<u32> {
+        if self.prove_absence {
+            return Ok(0);
+        }
+        u32::try_from(self.raw_transaction.len()).map_err(|_| {
+            eyre::eyre!(
+                "Encoded transaction length {} does not fit in u32",
+                self.raw_transaction.len()
+            )
+        })
+    }
 
-    for (i, tx) in complete_transactions.iter().enumerate() {
-        // Generate transaction key using proper RLP encoding of index
-        let key = rlp_encode(U256::from(i));
-        let nibbles = Nibbles::unpack(&key);
+    /// The `hasAccessListEntry` the guest commits: whether the included transaction's access list
+    /// holds `access_list_entry`; false when none was given and for absence.
+    pub fn committed_has_access_list_entry(&self) -> bool {
+        !self.prove_absence
+            && self
+                .access_list_entry
+                .is_some_and(|entry| entry.is_in(&self.raw_transaction))
+    }
 
-        // Encode the transaction using EXACT EIP-2718 encoding
-        let encoded_tx = encode_transaction_for_trie(tx)?;
-        encoded_transactions.push(encoded_tx.clone());
+    /// The `calldataHash` the guest commits once the inclusion proof verifies: keccak256 of the input
+    /// data of `raw_transaction`, the empty-input hash for a plain transfer, or zero for absence and
+    /// leaves that do not decode.
+    pub fn committed_calldata_hash(&self) -> B256 {
+        use alloy_consensus::Transaction;
+        use alloy_eips::eip2718::Decodable2718;
 
-        key_value_pairs.push((nibbles, encoded_tx));
+        if self.prove_absence {
+            return B256::ZERO;
+        }
+        alloy_consensus::TxEnvelope::decode_2718_exact(&self.raw_transaction)
+            .map_or(B256::ZERO, |tx| alloy_primitives::keccak256(tx.input()))
     }
 
-    // Step 3: Sort by key to ensure proper trie construction
-    key_value_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+    /// The block gas limit and gas used the guest commits, echoed from the proved header and bound
+    /// to the committed block hash; zero for a partial header.
+    pub fn committed_block_gas(&self) -> (u64, u64) {
+        if self.partial_header.is_some() {
+            (0, 0)
+        } else {
+            let header = self.proved_block_header();
+            (header.gas_limit, header.gas_used)
+        }
+    }
 
-    // Step 4: Add all transactions to trie (this populates the ProofRetainer)
-    for (nibbles, encoded_tx) in key_value_pairs.iter() {
-        trie_builder.add_leaf(nibbles.clone(), encoded_tx);
+    /// The `proofDepth` the guest commits: the number of nodes in `merkle_proof`. More than 255 is
+    /// an error, as it makes the guest panic.
+    pub fn committed_proof_depth(&self) -> Result<u8> {
+        u8::try_from(self.merkle_proof.len()).map_err(|_| {
+            eyre::eyre!(
+                "Transactions trie proof has {} nodes; at most 255 fit in proofDepth",
+                self.merkle_proof.len()
+            )
+        })
     }
 
-    // Step 5: Get the trie root and validate
-    let computed_root = trie_builder.root();
-    let block_root = block.header.transactions_root;
+    /// Number of the proved block, read from the partial header when the full one was dropped.
+    pub fn block_number(&self) -> u64 {
+        self.partial_header
+            .as_ref()
+            .map_or(self.proved_block_header().number, |partial| {
+                partial.block_number
+            })
+    }
 
-    println!("\n=== TRIE ROOT COMPARISON ===");
-    println!("Computed trie root: {:?}", computed_root);
-    println!("Block transactions root: {:?}", block_root);
+    /// Transactions root of the proved block, read from the partial header when the full one was
+    /// dropped.
+    pub fn transactions_root(&self) -> B256 {
+        self.partial_header
+            .as_ref()
+            .map_or(self.proved_block_header().transactions_root, |partial| {
+                partial.transactions_root
+            })
+    }
 
-    if computed_root != block_root {
-        println!("❌ WARNING: Transaction roots do not match!");
-        println!("   Computed: {:?}", computed_root);
-        println!("   Expected: {:?}", block_root);
-        println!("   Continuing with computed root for proof generation...");
-    } else {
-        println!("🎉 SUCCESS: Trie root MATCHES block transactions root!");
+    /// Check the input's block number and transactions root belong to the same block, given the
+    /// transactions root of the canonical block at that number. The header hash binding already
+    /// catches a header mixing the two, but only in the guest, after proving has started.
+    pub fn check_canonical_transactions_root(&self, canonical_root: B256) -> Result<()> {
+        if self.transactions_root() != canonical_root {
+            return Err(eyre::eyre!(
+                "Input pairs block {} with transactions root {:?}, but the canonical block {} has transactions root {:?}",
+                self.block_number(),
+                self.transactions_root(),
+                self.block_number(),
+                canonical_root
+            ));
+        }
+        Ok(())
     }
 
-    // Step 6: Extract the proper MPT proof from ProofRetainer
-    let target_tx_encoded = &encoded_transactions[tx_index as usize];
+    /// The `postMerge` the guest commits: whether the proved header has zero difficulty, which
+    /// EIP-3675 fixes for proof-of-stake blocks. False for a partial header, which carries no
+    /// difficulty.
+    ///
+    /// There is no per-chain table of Merge blocks, and the guest does not know the chain id. So this
+    /// is true from genesis on chains that never ran proof-of-work, such as post-Merge devnets, and
+    /// false on proof-of-work or clique test chains that never merged, whatever their block number.
+    /// A contract that needs a specific chain's Merge block must compare `blockNumber` itself.
+    pub fn committed_post_merge(&self) -> bool {
+        self.partial_header.is_none() && self.proved_block_header().difficulty.is_zero()
+    }
 
-    println!(
-        "\nExtracting Merkle proof for transaction at index {}...",
-        tx_index
-    );
+    /// keccak256 of the bincode encoding the guest reads, a stable key for caching and
+    /// deduplicating proofs: inputs with the same hash prove the same claim. Compression and the
+    /// input file format do not affect it.
+    pub fn content_hash(&self) -> Result<B256> {
+        Ok(alloy_primitives::keccak256(bincode::serialize(self)?))
+    }
+}
 
-    // Extract the proof nodes that ProofRetainer captured during trie construction
-    let proof_nodes = trie_builder.take_proof_nodes();
+/// Encoding of a [`TransactionInclusionInput`] written to or read from an input file.
+///
+/// The guest always reads bincode; the other formats are for files kept across struct changes
+/// or handed to other tools. MessagePack is not supported: use JSON for a self-describing file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    /// The compact, non-self-describing encoding the guest reads.
+    Bincode,
+    /// Self-describing and interoperable.
+    #[default]
+    Json,
+}
 
-    // Convert ProofNodes to Vec<Bytes> using built-in sorting
-    let proof_bytes: Vec<Bytes> = proof_nodes
-        .into_nodes_sorted()
-        .into_iter()
-        .map(|(_, bytes)| bytes)
-        .collect();
+impl InputFormat {
+    pub fn encode(self, input: &TransactionInclusionInput) -> Result<Vec<u8>> {
+        match self {
+            InputFormat::Bincode => Ok(bincode::serialize(input)?),
+            InputFormat::Json => Ok(serde_json::to_vec_pretty(input)?),
+        }
+    }
 
-    println!(
-        "Extracted {} proof nodes from ProofRetainer (using built-in sorting)",
-        proof_bytes.len()
-    );
+    /// Encode as [`InputFormat::encode`] does, then zstd-compress behind [`COMPRESSED_INPUT_MAGIC`].
+    #[cfg(feature = "compression")]
+    pub fn encode_compressed(self, input: &TransactionInclusionInput) -> Result<Vec<u8>> {
+        let mut bytes = COMPRESSED_INPUT_MAGIC.to_vec();
+        bytes.extend(zstd::encode_all(
+            &self.encode(input)?[..],
+            zstd::DEFAULT_COMPRESSION_LEVEL,
+        )?);
+        Ok(bytes)
+    }
 
-    // Step 7: Validate the merkle proof before sending to client
-    println!("\nValidating generated merkle proof...");
-    use alloy_trie::proof::verify_proof;
-
-    match verify_proof(
-        computed_root,
-        target_nibbles,
-        Some(target_tx_encoded.to_vec()),
-        &proof_bytes,
-    ) {
-        Ok(()) => {
-            println!("✅ Host validation successful - merkle proof is valid!");
-        }
-        Err(e) => {
-            println!("❌ Host validation failed: {:?}", e);
-            return Err(eyre::eyre!(
-                "Generated merkle proof failed validation: {:?}",
-                e
-            ));
+    /// Decode `bytes`, first decompressing them if they start with [`COMPRESSED_INPUT_MAGIC`].
+    pub fn decode(self, bytes: &[u8]) -> Result<TransactionInclusionInput> {
+        let bytes = decompress_input(bytes)?;
+        match self {
+            InputFormat::Bincode => Ok(bincode::deserialize(&bytes)?),
+            InputFormat::Json => Ok(serde_json::from_slice(&bytes)?),
         }
     }
+}
 
-    println!("\n=== MERKLE PROOF GENERATED ===");
-    println!(
-        "Generated Merkle proof with {} nodes using alloy-trie",
-        proof_bytes.len()
-    );
-    println!("Target transaction index: {}", tx_index);
-    println!(
-        "Encoded transaction size: {} bytes",
-        target_tx_encoded.len()
-    );
-    println!("Trie root: {:?}", computed_root);
-
-    Ok((proof_bytes, target_tx_encoded.clone()))
-}
-
-/// Generate the parent-block account witness needed to prove a signed transaction was still
-/// includable at the start of `block_number`.
-pub async fn generate_sender_account_witness(
-    provider: &impl Provider,
-    block_number: u64,
-    raw_transaction: &Bytes,
-) -> Result<SenderAccountWitness> {
-    if block_number == 0 {
-        return Err(eyre::eyre!("Block 0 has no parent state to prove against"));
-    }
-
-    let tx = TxEnvelope::decode_2718_exact(raw_transaction.as_ref())
-        .map_err(|e| eyre::eyre!("Failed to decode committed transaction: {:?}", e))?;
-    let sender = tx
-        .recover_signer()
-        .map_err(|e| eyre::eyre!("Failed to recover committed transaction signer: {:?}", e))?;
-
-    let parent_block_number = block_number - 1;
-    let parent_block = provider
-        .get_block(BlockId::Number(parent_block_number.into()))
-        .await?
-        .ok_or_else(|| eyre::eyre!("Parent block not found: {}", parent_block_number))?;
-
-    let proof_response = provider
-        .get_proof(sender, Vec::new())
-        .block_id(BlockId::Number(parent_block_number.into()))
-        .await?;
-
-    let account = AccountState {
-        nonce: proof_response.nonce,
-        balance: proof_response.balance,
-        storage_root: proof_response.storage_hash,
-        code_hash: proof_response.code_hash,
+/// Leads an input file written by [`InputFormat::encode_compressed`]. The guest never sees it:
+/// inputs are decompressed on the host before being written to the guest's stdin.
+pub const COMPRESSED_INPUT_MAGIC: [u8; 4] = *b"TXZ\x01";
+
+fn decompress_input(bytes: &[u8]) -> Result<std::borrow::Cow<'_, [u8]>> {
+    let Some(compressed) = bytes.strip_prefix(&COMPRESSED_INPUT_MAGIC) else {
+        return Ok(std::borrow::Cow::Borrowed(bytes));
     };
+    #[cfg(feature = "compression")]
+    {
+        Ok(std::borrow::Cow::Owned(zstd::decode_all(compressed)?))
+    }
+    #[cfg(not(feature = "compression"))]
+    {
+        let _ = compressed;
+        Err(eyre::eyre!(
+            "Input is zstd-compressed; build with the `compression` feature to read it"
+        ))
+    }
+}
 
-    if account.nonce != tx.nonce() {
-        return Err(eyre::eyre!(
-            "Committed transaction is not includable at block {}: sender nonce is {}, transaction nonce is {}",
-            block_number,
-            account.nonce,
-            tx.nonce()
-        ));
+impl std::str::FromStr for InputFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "bincode" => Ok(InputFormat::Bincode),
+            "json" => Ok(InputFormat::Json),
+            "messagepack" | "msgpack" => Err(eyre::eyre!(
+                "MessagePack input files are not supported; use json for a self-describing format"
+            )),
+            _ => Err(eyre::eyre!(
+                "Unknown input format: {} (expected: bincode, json)",
+                s
+            )),
+        }
+    }
+}
+
+/// A transaction at a given index with its own proof against `block_header.transactions_root`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionProofInput {
+    pub raw_transaction: Bytes,
+    pub transaction_index: u64,
+    pub merkle_proof: Vec<Bytes>,
+}
+
+/// Two transactions the guest checks share a recovered sender, committing which index comes first.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderOrderingInput {
+    pub first: TransactionProofInput,
+    pub second: TransactionProofInput,
+}
+
+/// An EIP-2718 encoded receipt at a given index with its proof against `block_header.receipts_root`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptProofInput {
+    pub raw_receipt: Bytes,
+    pub transaction_index: u64,
+    pub merkle_proof: Vec<Bytes>,
+}
+
+/// Receipts whose cumulative gas difference is the gas used by the transaction at
+/// `receipt.transaction_index`; `previous_receipt` is `None` for the first transaction.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasUsedInput {
+    pub receipt: ReceiptProofInput,
+    pub previous_receipt: Option<ReceiptProofInput>,
+}
+
+/// The last transaction of a block and an absence proof for the index after it, which together
+/// prove the block holds `last_transaction.transaction_index + 1` transactions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionCountInput {
+    pub last_transaction: TransactionProofInput,
+    pub absence_proof: Vec<Bytes>,
+}
+
+/// A transaction in the direct child of `block_header`, proved against that child's transactions root.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossBlockInput {
+    #[serde_as(as = "alloy_consensus::serde_bincode_compat::Header")]
+    pub next_block_header: Header,
+    pub transaction: TransactionProofInput,
+}
+
+impl From<MerkleProofBundle> for TransactionProofInput {
+    fn from(bundle: MerkleProofBundle) -> Self {
+        Self {
+            raw_transaction: bundle.raw_transaction,
+            transaction_index: bundle.transaction_index,
+            merkle_proof: bundle.merkle_proof,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Proof result showing whether a transaction is included at the expected precise index
+pub struct TransactionInclusionProof {
+    pub block_hash: B256,
+    pub block_number: u64,
+    pub committed_transaction_hash: B256,
+    pub transaction_hash: B256,
+    pub transaction_index: u64,
+    pub is_included: bool,
+    pub transaction_can_be_included: bool,
+    pub verified_against_root: B256,
+}
+
+impl TransactionInclusionProof {
+    pub fn outcome(&self) -> InclusionOutcome {
+        InclusionOutcome::from_committed(
+            self.transaction_hash,
+            self.transaction_index,
+            self.is_included,
+        )
     }
+}
+
+/// What a proof's committed `isIncluded` flag means once the proof mode is taken into account.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InclusionOutcome {
+    /// The committed transaction was proved to be at this index.
+    IncludedAt(u64),
+    /// No transaction exists at the committed index.
+    ExcludedProven,
+    /// The supplied Merkle proof did not verify, so nothing was proved about the index.
+    Indeterminate(String),
+}
 
-    let gas_cost = U256::from(tx.gas_limit()) * U256::from(tx.max_fee_per_gas());
-    let blob_cost = match (tx.blob_gas_used(), tx.max_fee_per_blob_gas()) {
-        (Some(blob_gas_used), Some(max_fee_per_blob_gas)) => {
-            U256::from(blob_gas_used) * U256::from(max_fee_per_blob_gas)
+impl InclusionOutcome {
+    /// Interpret committed public values. Absence proofs commit a zero `transaction_hash`, and their
+    /// `is_included` is true only when the absence proof failed to verify.
+    pub fn from_committed(
+        transaction_hash: B256,
+        transaction_index: u64,
+        is_included: bool,
+    ) -> Self {
+        match (transaction_hash == B256::ZERO, is_included) {
+            (false, true) => InclusionOutcome::IncludedAt(transaction_index),
+            (false, false) => InclusionOutcome::Indeterminate(format!(
+                "inclusion proof for index {} did not verify",
+                transaction_index
+            )),
+            (true, false) => InclusionOutcome::ExcludedProven,
+            (true, true) => InclusionOutcome::Indeterminate(format!(
+                "absence proof for index {} did not verify",
+                transaction_index
+            )),
         }
-        _ => U256::ZERO,
-    };
-    let upfront_cost = gas_cost
-        .checked_add(blob_cost)
-        .and_then(|cost| cost.checked_add(tx.value()))
-        .ok_or_else(|| eyre::eyre!("Committed transaction upfront cost overflowed"))?;
-    if account.balance < upfront_cost {
-        return Err(eyre::eyre!(
-            "Committed transaction is not includable at block {}: sender balance {} is below upfront cost {}",
-            block_number,
-            account.balance,
-            upfront_cost
-        ));
     }
+}
 
-    Ok(SenderAccountWitness {
-        parent_block_header: parent_block.header.into(),
-        sender,
-        account,
-        proof: proof_response.account_proof,
-    })
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountState {
+    pub nonce: u64,
+    pub balance: U256,
+    pub storage_root: B256,
+    pub code_hash: B256,
 }
 
-/// Generate a Merkle Patricia Trie exclusion proof for a transaction index in a block.
-pub async fn generate_merkle_absence_proof(
-    provider: &impl Provider,
-    block_number: u64,
-    tx_index: u64,
-) -> Result<Vec<Bytes>> {
-    use alloy_primitives::U256;
-    use alloy_rlp::encode as rlp_encode;
-    use alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles};
+/// A transaction's Merkle proof together with the block it was generated against.
+#[derive(Debug, Clone)]
+pub struct MerkleProofBundle {
+    pub block_header: Header,
+    /// Root computed while building the trie.
+    pub transactions_root: B256,
+    pub merkle_proof: Vec<Bytes>,
+    /// EIP-2718 encoded transaction stored at `transaction_index`.
+    pub raw_transaction: Bytes,
+    pub transaction_index: u64,
+    /// Trie key the proof was built for, the RLP encoding of the index.
+    pub proof_key: Bytes,
+    /// EIP-2718 type of `raw_transaction`, zero for legacy.
+    pub tx_type: u8,
+}
 
-    println!(
-        "Generating Merkle absence proof for transaction index {} in block {} using alloy-trie",
-        tx_index, block_number
-    );
+impl MerkleProofBundle {
+    /// Number of nodes in `merkle_proof`, which the guest commits as `proofDepth`.
+    pub fn depth(&self) -> usize {
+        self.merkle_proof.len()
+    }
 
-    let block = provider
-        .get_block(BlockId::Number(block_number.into()))
-        .full()
-        .await?
-        .ok_or_else(|| eyre::eyre!("Block not found: {}", block_number))?;
+    /// Check that the proof was built for `transaction_index`, so an input pairing the proof with a
+    /// different index is rejected before proving rather than failing inside the guest.
+    pub fn check_index(&self, transaction_index: u64) -> Result<()> {
+        use alloy_rlp::Decodable;
 
-    let complete_transactions = match &block.transactions {
-        BlockTransactions::Full(txs) => txs.clone(),
-        BlockTransactions::Hashes(_) => {
+        let mut key = self.proof_key.as_ref();
+        let key_index = u64::decode(&mut key)
+            .ok()
+            .filter(|_| key.is_empty())
+            .ok_or_else(|| {
+                eyre::eyre!("Proof key {} is not an RLP-encoded index", self.proof_key)
+            })?;
+        if key_index != transaction_index || self.transaction_index != transaction_index {
             return Err(eyre::eyre!(
-                "Expected full transactions but got hashes - ensure .full() is used"
+                "Transaction index {} does not match the proof, built for key index {} (bundle index {})",
+                transaction_index,
+                key_index,
+                self.transaction_index
             ));
         }
-        _ => {
-            return Err(eyre::eyre!("Unexpected transaction format"));
+        Ok(())
+    }
+
+    /// Check the root computed while building the trie against `expected_root`, e.g. from a trusted
+    /// source, before any proving.
+    pub fn check_root(&self, expected_root: B256) -> Result<()> {
+        if self.transactions_root != expected_root {
+            return Err(eyre::eyre!(
+                "Computed transactions root {:?} does not match the asserted root {:?}",
+                self.transactions_root,
+                expected_root
+            ));
         }
-    };
+        Ok(())
+    }
+}
 
-    if (tx_index as usize) < complete_transactions.len() {
+#[derive(Debug, Clone)]
+pub struct SenderAccountWitness {
+    pub parent_block_header: Header,
+    pub sender: Address,
+    pub account: AccountState,
+    pub proof: Vec<Bytes>,
+}
+/// Check that `header` hashes to `expected_block_hash`.
+pub fn verify_block_header_hash(header: &Header, expected_block_hash: B256) -> Result<()> {
+    let computed_block_hash = header.hash_slow();
+    if computed_block_hash != expected_block_hash {
         return Err(eyre::eyre!(
-            "Transaction index {} exists in block {}; cannot generate absence proof",
-            tx_index,
-            block_number
+            "Block header for block {} hashes to {:?}, expected {:?}",
+            header.number,
+            computed_block_hash,
+            expected_block_hash
         ));
     }
+    Ok(())
+}
 
-    let target_key = rlp_encode(U256::from(tx_index));
-    let target_nibbles = Nibbles::unpack(&target_key);
-    let proof_retainer = ProofRetainer::from_iter([target_nibbles.clone()]);
-    let mut trie_builder = HashBuilder::default().with_proof_retainer(proof_retainer);
+/// Check that `child` is the direct child of `parent`, as the guest does for both the parent header
+/// and a cross-block header.
+pub fn verify_parent_linkage(parent: &Header, child: &Header) -> Result<()> {
+    if parent.number.checked_add(1) != Some(child.number) {
+        return Err(eyre::eyre!(
+            "Block {} is not the direct child of block {}",
+            child.number,
+            parent.number
+        ));
+    }
+    let parent_hash = parent.hash_slow();
+    if child.parent_hash != parent_hash {
+        return Err(eyre::eyre!(
+            "Block {} parent hash {:?} does not match block {} hash {:?}",
+            child.number,
+            child.parent_hash,
+            parent.number,
+            parent_hash
+        ));
+    }
+    Ok(())
+}
 
-    let mut key_value_pairs = Vec::new();
-    for (i, tx) in complete_transactions.iter().enumerate() {
-        let key = rlp_encode(U256::from(i));
-        let nibbles = Nibbles::unpack(&key);
-        let encoded_tx = encode_transaction_for_trie(tx)?;
-        key_value_pairs.push((nibbles, encoded_tx));
+/// Root the guest verifies transaction proofs against: `claimed_root` when given, which must equal
+/// the header's transactions root, otherwise the header's own root.
+pub fn verification_root(header: &Header, claimed_root: Option<B256>) -> Result<B256> {
+    match claimed_root {
+        Some(claimed_root) if claimed_root != header.transactions_root => Err(eyre::eyre!(
+            "Claimed transactions root {:?} does not match block {} transactions root {:?}",
+            claimed_root,
+            header.number,
+            header.transactions_root
+        )),
+        Some(claimed_root) => Ok(claimed_root),
+        None => Ok(header.transactions_root),
     }
-    key_value_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+}
+
+/// Length of the header's RLP encoding, i.e. the bytes `hash_slow` hashes, as committed by the guest.
+pub fn header_rlp_length(header: &Header) -> Result<u32> {
+    use alloy_rlp::Encodable;
 
-    for (nibbles, encoded_tx) in key_value_pairs.iter() {
-        trie_builder.add_leaf(nibbles.clone(), encoded_tx);
+    u32::try_from(header.length())
+        .map_err(|_| eyre::eyre!("Block header RLP length {} exceeds u32", header.length()))
+}
+
+/// EIP-2718 type of a verified transactions-trie leaf, as committed by the guest.
+///
+/// Fails when the leaf does not decode as a transaction.
+pub fn verified_transaction_type(raw_transaction: &[u8]) -> Result<u8> {
+    use alloy_consensus::TxEnvelope;
+    use alloy_eips::eip2718::Decodable2718;
+
+    let tx = TxEnvelope::decode_2718_exact(raw_transaction)
+        .map_err(|e| eyre::eyre!("Verified leaf is not a transaction: {}", e))?;
+    Ok(tx.tx_type() as u8)
+}
+
+/// EIP-2718 type of an encoded transaction from its first byte alone, without decoding it.
+///
+/// Typed transactions start with their type, at most 0x7f; legacy ones are a bare RLP list, which
+/// starts at 0xc0 or above. Anything else is not an encoded transaction.
+pub fn encoded_transaction_type(raw_transaction: &[u8]) -> Result<u8> {
+    match raw_transaction.first() {
+        Some(&tx_type) if tx_type <= 0x7f => Ok(tx_type),
+        Some(&first_byte) if first_byte >= 0xc0 => Ok(0),
+        first_byte => Err(eyre::eyre!(
+            "Not an EIP-2718 encoded transaction: first byte {:?}",
+            first_byte
+        )),
     }
+}
 
-    let computed_root = trie_builder.root();
-    let block_root = block.header.transactions_root;
-    if computed_root != block_root {
-        return Err(eyre::eyre!(
-            "Computed trie root {:?} does not match block transactions root {:?}",
-            computed_root,
-            block_root
-        ));
+/// EIP-2718 type of a verified leaf as the guest commits it under `mode`.
+///
+/// A leaf that is not a transaction fails in strict mode and is `None` in lenient mode, where the
+/// guest commits type zero and `decodeFailed`.
+pub fn committed_transaction_type(raw_transaction: &[u8], mode: DecodeMode) -> Result<Option<u8>> {
+    match (verified_transaction_type(raw_transaction), mode) {
+        (Ok(tx_type), _) => Ok(Some(tx_type)),
+        (Err(_), DecodeMode::Lenient) => Ok(None),
+        (Err(e), DecodeMode::Strict) => Err(e),
     }
+}
 
-    let proof_bytes: Vec<Bytes> = trie_builder
-        .take_proof_nodes()
-        .into_nodes_sorted()
-        .into_iter()
-        .map(|(_, bytes)| bytes)
-        .collect();
+/// Per-gas price a verified leaf paid in a block with `base_fee_per_gas`, as committed by the guest.
+///
+/// Legacy and EIP-2930 transactions pay their gas price. EIP-1559, blob and set-code transactions
+/// pay the base fee plus their priority fee, capped at their max fee; blob gas is priced separately.
+pub fn effective_gas_price(raw_transaction: &[u8], base_fee_per_gas: Option<u64>) -> Result<u128> {
+    use alloy_consensus::{Transaction, TxEnvelope};
+    use alloy_eips::eip2718::Decodable2718;
 
-    use alloy_trie::proof::verify_proof;
-    verify_proof(computed_root, target_nibbles, None, &proof_bytes)
-        .map_err(|e| eyre::eyre!("Generated absence proof failed validation: {:?}", e))?;
+    let tx = TxEnvelope::decode_2718_exact(raw_transaction)
+        .map_err(|e| eyre::eyre!("Verified leaf is not a transaction: {}", e))?;
+    Ok(tx.effective_gas_price(base_fee_per_gas))
+}
 
-    println!(
-        "Generated absence proof with {} nodes for missing transaction index {}",
-        proof_bytes.len(),
-        tx_index
-    );
+/// Cumulative gas used by the block up to and including the receipt's transaction.
+pub fn receipt_cumulative_gas_used(raw_receipt: &[u8]) -> Result<u64> {
+    use alloy_consensus::ReceiptEnvelope;
+    use alloy_eips::eip2718::Decodable2718;
 
-    Ok(proof_bytes)
+    let receipt = ReceiptEnvelope::decode_2718_exact(raw_receipt)
+        .map_err(|e| eyre::eyre!("Failed to decode receipt: {}", e))?;
+    Ok(receipt.cumulative_gas_used())
 }
 
-/// Encode transaction for trie using the exact Ethereum format
-pub fn encode_transaction_for_trie(tx: &alloy_rpc_types::Transaction) -> Result<Bytes> {
-    use alloy_eips::eip2718::Encodable2718;
+/// Whether the receipt's transaction succeeded, as the guest reads it from a legacy or typed receipt.
+///
+/// Pre-Byzantium receipts carry a post-state root instead of a status, so they read as `false`;
+/// see [`receipt_post_state_root`].
+pub fn receipt_status(raw_receipt: &[u8]) -> Result<bool> {
+    use alloy_consensus::{Eip658Value, ReceiptEnvelope, TxReceipt};
+    use alloy_eips::eip2718::Decodable2718;
 
-    // println!("Encoding transaction hash: {}", tx.inner.hash());
+    let receipt = ReceiptEnvelope::decode_2718_exact(raw_receipt)
+        .map_err(|e| eyre::eyre!("Failed to decode receipt: {}", e))?;
+    Ok(matches!(
+        receipt.status_or_post_state(),
+        Eip658Value::Eip658(true)
+    ))
+}
 
-    // Use Alloy's built-in EIP-2718 encoding which should match Ethereum exactly
-    // This handles all transaction types with proper prefixes and RLP encoding
-    let encoded_bytes = tx.inner.encoded_2718();
+/// The post-state root a pre-Byzantium receipt carries, or `None` for a receipt with a status.
+pub fn receipt_post_state_root(raw_receipt: &[u8]) -> Result<Option<B256>> {
+    use alloy_consensus::{ReceiptEnvelope, TxReceipt};
+    use alloy_eips::eip2718::Decodable2718;
 
-    // println!("Used EIP-2718 encoded transaction: {} bytes", encoded_bytes.len());
-    Ok(Bytes::from(encoded_bytes))
+    let receipt = ReceiptEnvelope::decode_2718_exact(raw_receipt)
+        .map_err(|e| eyre::eyre!("Failed to decode receipt: {}", e))?;
+    Ok(receipt.status_or_post_state().as_post_state())
+}
+
+/// Gas used by one transaction, as the guest derives it from its receipt and the previous one.
+pub fn receipt_gas_used(raw_receipt: &[u8], previous_raw_receipt: Option<&[u8]>) -> Result<u64> {
+    let cumulative_gas_used = receipt_cumulative_gas_used(raw_receipt)?;
+    let previous_cumulative_gas_used = previous_raw_receipt
+        .map(receipt_cumulative_gas_used)
+        .transpose()?
+        .unwrap_or(0);
+    cumulative_gas_used
+        .checked_sub(previous_cumulative_gas_used)
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "Cumulative gas used decreased from {} to {}",
+                previous_cumulative_gas_used,
+                cumulative_gas_used
+            )
+        })
+}
+
+/// Put `transactions` in block order by the index each reports, so a provider that returns them
+/// out of order cannot make the trie, or a position, select the wrong leaf.
+///
+/// Transactions that report no index are left in the order given.
+pub fn order_transactions_by_index(
+    transactions: &mut [alloy_rpc_types::Transaction],
+) -> Result<()> {
+    if transactions.iter().all(|tx| tx.transaction_index.is_some()) {
+        transactions.sort_by_key(|tx| tx.transaction_index);
+    }
+    check_transaction_positions(transactions)
+}
+
+/// Check that every transaction reporting an index sits at that position.
+pub fn check_transaction_positions(transactions: &[alloy_rpc_types::Transaction]) -> Result<()> {
+    for (position, tx) in transactions.iter().enumerate() {
+        if let Some(reported_index) = tx.transaction_index {
+            if reported_index != position as u64 {
+                return Err(eyre::eyre!(
+                    "Transaction {:?} reports index {} but is at position {}",
+                    tx.inner.hash(),
+                    reported_index,
+                    position
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Find the index of the transaction with `transaction_hash` in a block fetched with full transactions.
+pub fn transaction_index_in_block(
+    block: &alloy_rpc_types::Block,
+    transaction_hash: B256,
+) -> Result<u64> {
+    let transactions = block
+        .transactions
+        .as_transactions()
+        .ok_or_else(|| eyre::eyre!("Expected full transactions but got hashes"))?;
+    transactions
+        .iter()
+        .position(|tx| *tx.inner.hash() == transaction_hash)
+        .map(|index| index as u64)
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "Transaction {:?} not found in block {}",
+                transaction_hash,
+                block.header.number
+            )
+        })
+}
+
+/// Index of `from`'s transaction with `nonce` in `block`, for callers that know the sender and nonce
+/// but not the hash, e.g. relayers and account-abstraction bundlers.
+pub fn transaction_index_by_sender_nonce(
+    block: &alloy_rpc_types::Block,
+    from: Address,
+    nonce: u64,
+) -> Result<u64> {
+    let transactions = block
+        .transactions
+        .as_transactions()
+        .ok_or_else(|| eyre::eyre!("Expected full transactions but got hashes"))?;
+    transactions
+        .iter()
+        .position(|tx| {
+            tx.inner.signer() == from && alloy_consensus::Transaction::nonce(tx) == nonce
+        })
+        .map(|index| index as u64)
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "No transaction from {} with nonce {} in block {}",
+                from,
+                nonce,
+                block.header.number
+            )
+        })
+}
+
+/// The index `block` actually holds `transaction_hash` at: `reported_index` when it matches,
+/// otherwise the transaction's position in the block, with a warning about the discrepancy.
+///
+/// Some RPCs report an `eth_getTransactionByHash` index off by one from their own full block, and
+/// a proof built at the reported index would be for the wrong transaction.
+pub fn reconcile_transaction_index(
+    block: &alloy_rpc_types::Block,
+    transaction_hash: B256,
+    reported_index: u64,
+) -> Result<u64> {
+    let transactions = block
+        .transactions
+        .as_transactions()
+        .ok_or_else(|| eyre::eyre!("Expected full transactions but got hashes"))?;
+    if transactions
+        .get(reported_index as usize)
+        .is_some_and(|tx| *tx.inner.hash() == transaction_hash)
+    {
+        return Ok(reported_index);
+    }
+
+    let index = transaction_index_in_block(block, transaction_hash)?;
+    println!(
+        "⚠️  WARNING: the RPC reported transaction {:?} at index {}, but block {} holds it at index {}; proving index {}",
+        transaction_hash, reported_index, block.header.number, index, index
+    );
+    Ok(index)
+}
+
+/// Block number and index of a transaction returned by `eth_getTransactionByHash`.
+///
+/// A missing block number means the transaction is still pending; a mined transaction without an
+/// index means the provider omitted the field. The two get distinct errors.
+pub fn mined_transaction_position(tx: &alloy_rpc_types::Transaction) -> Result<(u64, u64)> {
+    let transaction_hash = tx.inner.hash();
+    let block_number = tx.block_number.ok_or_else(|| {
+        eyre::eyre!(
+            "Transaction {:?} is not mined yet; wait for it to be included and confirmed, then retry",
+            transaction_hash
+        )
+    })?;
+    let tx_index = tx.transaction_index.ok_or_else(|| {
+        eyre::eyre!(
+            "Transaction {:?} is mined in block {} but the provider returned no transaction index; retry against a provider that reports it",
+            transaction_hash,
+            block_number
+        )
+    })?;
+    Ok((block_number, tx_index))
+}
+
+/// Maximum number of transactions written to a root-mismatch debug dump.
+pub const MAX_DEBUG_DUMP_TRANSACTIONS: usize = 10_000;
+
+/// Number of leading key nibbles written per transaction to a root-mismatch debug dump.
+pub const DEBUG_DUMP_KEY_NIBBLES: usize = 4;
+
+/// Decides whether a block transaction is inserted into the transactions trie.
+///
+/// For chains whose header root leaves system transactions out, the filter drops them before the
+/// trie is built; indices then refer to positions among the remaining transactions. Ethereum L1
+/// needs no filter.
+pub type TransactionFilter = fn(&alloy_rpc_types::Transaction) -> bool;
+
+/// A block tag a proof can target. The tag is resolved to one concrete block before any proof is
+/// generated, so the proof stays bound to that block while the tag moves on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    Latest,
+    Safe,
+    Finalized,
+}
+
+impl BlockTag {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BlockTag::Latest => "latest",
+            BlockTag::Safe => "safe",
+            BlockTag::Finalized => "finalized",
+        }
+    }
+
+    /// Only finalized blocks cannot be reorged away, so only their proofs are safe to slash with.
+    pub fn is_finalized(self) -> bool {
+        self == BlockTag::Finalized
+    }
+}
+
+impl From<BlockTag> for alloy_eips::BlockNumberOrTag {
+    fn from(tag: BlockTag) -> Self {
+        match tag {
+            BlockTag::Latest => alloy_eips::BlockNumberOrTag::Latest,
+            BlockTag::Safe => alloy_eips::BlockNumberOrTag::Safe,
+            BlockTag::Finalized => alloy_eips::BlockNumberOrTag::Finalized,
+        }
+    }
+}
+
+impl std::str::FromStr for BlockTag {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "latest" => Ok(BlockTag::Latest),
+            "safe" => Ok(BlockTag::Safe),
+            "finalized" => Ok(BlockTag::Finalized),
+            _ => Err(eyre::eyre!(
+                "Unknown block tag: {} (expected: latest, safe, finalized)",
+                s
+            )),
+        }
+    }
+}
+
+/// An event that names the target transaction: logs from `address` with `topic0` in an inclusive
+/// block range. Parsed from `ADDRESS:TOPIC0:FROM-TO`, or `ADDRESS:TOPIC0:BLOCK` for one block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogSelector {
+    pub address: Address,
+    pub topic0: B256,
+    pub from_block: u64,
+    pub to_block: u64,
+}
+
+impl std::str::FromStr for LogSelector {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let [address, topic0, range] = s.split(':').collect::<Vec<_>>()[..] else {
+            return Err(eyre::eyre!(
+                "Log selector must be ADDRESS:TOPIC0:FROM-TO, got {}",
+                s
+            ));
+        };
+        let (from_block, to_block) = match range.split_once('-') {
+            Some((from_block, to_block)) => (from_block.parse()?, to_block.parse()?),
+            None => (range.parse()?, range.parse()?),
+        };
+        if to_block < from_block {
+            return Err(eyre::eyre!(
+                "Log selector block range {}-{} is empty",
+                from_block,
+                to_block
+            ));
+        }
+        Ok(Self {
+            address: address.parse()?,
+            topic0: topic0.parse()?,
+            from_block,
+            to_block,
+        })
+    }
+}
+
+/// Optional behaviour for Merkle proof generation.
+#[derive(Debug, Clone, Default)]
+pub struct ProofOptions {
+    /// When set and the computed root does not match the header root, write a per-transaction dump here.
+    pub debug_dump: Option<PathBuf>,
+    /// When set, only transactions it accepts are inserted into the trie, in block order.
+    pub transaction_filter: Option<TransactionFilter>,
+    /// Log every leaf as it is added, with the root over the leaves so far; the proof is unchanged.
+    pub verbose_trie: bool,
+    /// Reject a fetched block whose JSON serialization is larger than this many bytes, before
+    /// building its trie.
+    pub max_block_bytes: Option<usize>,
+    /// Fetch each block's header again with `eth_getHeaderByNumber` and require it to match the
+    /// full block's header.
+    pub cross_check_header: bool,
+    /// Fetch each transaction's raw bytes with `eth_getRawTransactionByHash` and require its
+    /// re-encoding to match them.
+    pub check_raw_transactions: bool,
+}
+
+/// Build the transactions trie for a block and extract the proof for the transaction at `tx_index`.
+pub fn build_merkle_proof(
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    transactions: &[alloy_rpc_types::Transaction],
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<MerkleProofBundle> {
+    let mut bundles =
+        build_merkle_proofs(backend, block_header, transactions, &[tx_index], options)?;
+    Ok(bundles.remove(0))
+}
+
+/// Position of `transaction_index` counted from the end of a block of `transaction_count`
+/// transactions, as the guest commits it: zero for the last transaction.
+pub fn index_from_end(transaction_count: u64, transaction_index: u64) -> Result<u64> {
+    transaction_count
+        .checked_sub(1)
+        .and_then(|last_index| last_index.checked_sub(transaction_index))
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "Transaction index {} out of range for a block of {} transactions",
+                transaction_index,
+                transaction_count
+            )
+        })
+}
+
+/// Build the transactions trie for a block once and extract proofs for the transactions at
+/// `tx_index` and `tx_index + 1`.
+pub fn build_adjacent_merkle_proofs(
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    transactions: &[alloy_rpc_types::Transaction],
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<(MerkleProofBundle, MerkleProofBundle)> {
+    let next_index = tx_index
+        .checked_add(1)
+        .ok_or_else(|| eyre::eyre!("Transaction index {} has no successor", tx_index))?;
+    let mut bundles = build_merkle_proofs(
+        backend,
+        block_header,
+        transactions,
+        &[tx_index, next_index],
+        options,
+    )?;
+    let next = bundles.remove(1);
+    Ok((bundles.remove(0), next))
+}
+
+/// Build the transactions trie for a block once and extract proofs for two transactions that must
+/// come from the same sender.
+pub fn build_sender_ordering_proofs(
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    transactions: &[alloy_rpc_types::Transaction],
+    first_index: u64,
+    second_index: u64,
+    options: &ProofOptions,
+) -> Result<(MerkleProofBundle, MerkleProofBundle)> {
+    if first_index == second_index {
+        return Err(eyre::eyre!(
+            "Sender ordering needs two distinct transactions, got index {} twice",
+            first_index
+        ));
+    }
+    let mut bundles = build_merkle_proofs(
+        backend,
+        block_header,
+        transactions,
+        &[first_index, second_index],
+        options,
+    )?;
+    let second = bundles.remove(1);
+    let first = bundles.remove(0);
+    shared_sender(&first.raw_transaction, &second.raw_transaction)?;
+    Ok((first, second))
+}
+
+/// Recover the signers of two EIP-2718 encoded transactions, as the guest does, and require they match.
+pub fn shared_sender(
+    first_raw_transaction: &[u8],
+    second_raw_transaction: &[u8],
+) -> Result<Address> {
+    use alloy_consensus::{transaction::SignerRecoverable, TxEnvelope};
+    use alloy_eips::eip2718::Decodable2718;
+
+    let recover = |raw_transaction: &[u8]| -> Result<Address> {
+        let tx = TxEnvelope::decode_2718_exact(raw_transaction)
+            .map_err(|e| eyre::eyre!("Failed to decode transaction: {}", e))?;
+        tx.recover_signer()
+            .map_err(|e| eyre::eyre!("Failed to recover transaction signer: {}", e))
+    };
+    let first_sender = recover(first_raw_transaction)?;
+    let second_sender = recover(second_raw_transaction)?;
+    if first_sender != second_sender {
+        return Err(eyre::eyre!(
+            "Transactions have different senders: {:?} and {:?}",
+            first_sender,
+            second_sender
+        ));
+    }
+    Ok(first_sender)
+}
+
+/// Build the transactions trie for a block once and extract one proof per entry of `tx_indices`.
+pub fn build_merkle_proofs(
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    transactions: &[alloy_rpc_types::Transaction],
+    tx_indices: &[u64],
+    options: &ProofOptions,
+) -> Result<Vec<MerkleProofBundle>> {
+    let encoded_transactions = encode_trie_transactions(transactions, options)?;
+    build_merkle_proofs_for_encoded(
+        backend,
+        block_header,
+        &encoded_transactions,
+        tx_indices,
+        options,
+    )
+}
+
+/// EIP-2718 encodings of the transactions that go into the trie, in block order: those
+/// `options.transaction_filter` accepts, or all of them.
+pub fn encode_trie_transactions(
+    transactions: &[alloy_rpc_types::Transaction],
+    options: &ProofOptions,
+) -> Result<Vec<Bytes>> {
+    check_transaction_positions(transactions)?;
+
+    let transactions: Vec<_> = match options.transaction_filter {
+        Some(filter) => transactions.iter().filter(|tx| filter(tx)).collect(),
+        None => transactions.iter().collect(),
+    };
+    if options.transaction_filter.is_some() {
+        println!("Transaction filter applied; indices refer to the filtered transactions");
+    }
+
+    // Encode the transactions using EXACT EIP-2718 encoding
+    transactions
+        .into_iter()
+        .map(encode_transaction_for_trie)
+        .collect()
+}
+
+/// Transactions trie leaves for EIP-2718 encoded transactions in block order, each keyed by the RLP
+/// encoding of its index.
+pub fn transaction_trie_leaves(encoded_transactions: &[Bytes]) -> Vec<(Vec<u8>, Bytes)> {
+    encoded_transactions
+        .iter()
+        .enumerate()
+        .map(|(i, encoded_tx)| (alloy_rlp::encode(U256::from(i)), encoded_tx.clone()))
+        .collect()
+}
+
+/// Recompute a block's transactions root from its full transactions with the same leaves the
+/// proofs are built from, so a consumer can check `transactions_root` without building a proof.
+pub fn compute_transactions_root(
+    backend: &dyn TrieBackend,
+    block: &alloy_rpc_types::Block,
+) -> Result<B256> {
+    let transactions = block
+        .transactions
+        .as_transactions()
+        .ok_or_else(|| eyre::eyre!("Expected full transactions but got hashes"))?;
+    check_transaction_positions(transactions)?;
+    let encoded_transactions = transactions
+        .iter()
+        .map(encode_transaction_for_trie)
+        .collect::<Result<Vec<_>>>()?;
+    Ok(backend.build_root(&transaction_trie_leaves(&encoded_transactions)))
+}
+
+/// Build the transactions trie from EIP-2718 encoded transactions, in block order, and extract one
+/// proof per entry of `tx_indices`.
+///
+/// As for absence and count proofs, a computed root that differs from `transactions_root` is an
+/// error, after any `options.debug_dump` is written.
+pub fn build_merkle_proofs_for_encoded(
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    encoded_transactions: &[Bytes],
+    tx_indices: &[u64],
+    options: &ProofOptions,
+) -> Result<Vec<MerkleProofBundle>> {
+    use alloy_rlp::encode as rlp_encode;
+
+    if encoded_transactions.is_empty() && !tx_indices.is_empty() {
+        return Err(eyre::eyre!(
+            "Block {} has no transactions to prove",
+            block_header.number
+        ));
+    }
+    for &tx_index in tx_indices {
+        if tx_index as usize >= encoded_transactions.len() {
+            return Err(eyre::eyre!(
+                "Transaction index {} out of range (max: {})",
+                tx_index,
+                encoded_transactions.len() - 1
+            ));
+        }
+    }
+
+    println!(
+        "Successfully got all {} transactions from block!",
+        encoded_transactions.len()
+    );
+    println!("Building transaction trie with proof generation...");
+
+    // Step 1: Prepare transaction leaves keyed by the RLP-encoded index
+    let target_keys: Vec<_> = tx_indices
+        .iter()
+        .map(|&tx_index| rlp_encode(U256::from(tx_index)))
+        .collect();
+    let leaves = transaction_trie_leaves(encoded_transactions);
+
+    if options.verbose_trie {
+        for line in verbose_trie_log(backend, &leaves) {
+            println!("{}", line);
+        }
+    }
+
+    // Step 2: Build the trie once, retaining the proof nodes for every target key
+    let (computed_root, proofs) = backend.generate_proofs(&leaves, &target_keys);
+    let block_root = block_header.transactions_root;
+
+    println!("\n=== TRIE ROOT COMPARISON ===");
+    println!("Computed trie root: {:?}", computed_root);
+    println!("Block transactions root: {:?}", block_root);
+
+    if computed_root != block_root {
+        if let Some(path) = &options.debug_dump {
+            write_root_mismatch_dump(path, computed_root, block_root, &leaves)?;
+            println!("Wrote root mismatch debug dump to {:?}", path);
+        }
+        return Err(eyre::eyre!(
+            "Computed trie root {:?} does not match block {} transactions root {:?}",
+            computed_root,
+            block_header.number,
+            block_root
+        ));
+    }
+    println!("🎉 SUCCESS: Trie root MATCHES block transactions root!");
+
+    let mut bundles = Vec::with_capacity(tx_indices.len());
+    for ((&tx_index, target_key), proof_bytes) in tx_indices.iter().zip(&target_keys).zip(proofs) {
+        // The guest rejects an empty proof; a single-leaf trie's proof is just its root node
+        let proof_bytes = if proof_bytes.is_empty() {
+            println!(
+                "⚠️  No proof nodes were retained for index {}; rebuilding the single-leaf root node",
+                tx_index
+            );
+            single_leaf_proof(computed_root, &leaves)?
+        } else {
+            proof_bytes
+        };
+        let proof_node_count = proof_bytes.len();
+        let proof_bytes = dedup_proof_nodes(proof_bytes);
+        if proof_bytes.len() != proof_node_count {
+            println!(
+                "Removed {} duplicate proof nodes",
+                proof_node_count - proof_bytes.len()
+            );
+        }
+        let unpruned_node_count = proof_bytes.len();
+        let proof_bytes = prune_proof_nodes(computed_root, target_key, &proof_bytes);
+        println!(
+            "Pruned proof nodes off the path to index {}: {} -> {}",
+            tx_index,
+            unpruned_node_count,
+            proof_bytes.len()
+        );
+
+        // Step 3: Extract the proof for the target transaction
+        let target_tx_encoded = &leaves[tx_index as usize].1;
+
+        println!(
+            "\nExtracted {} proof nodes for transaction at index {}",
+            proof_bytes.len(),
+            tx_index
+        );
+
+        // Step 4: Validate the merkle proof before sending to client
+        println!("\nValidating generated merkle proof...");
+
+        match backend.verify_proof(
+            computed_root,
+            target_key,
+            Some(target_tx_encoded),
+            &proof_bytes,
+        ) {
+            Ok(()) => {
+                println!("✅ Host validation successful - merkle proof is valid!");
+            }
+            Err(e) => {
+                println!("❌ Host validation failed: {:?}", e);
+                return Err(eyre::eyre!(
+                    "Generated merkle proof failed validation: {:?}",
+                    e
+                ));
+            }
+        }
+
+        let tx_type = encoded_transaction_type(target_tx_encoded)?;
+
+        println!("\n=== MERKLE PROOF GENERATED ===");
+        println!("Generated Merkle proof with {} nodes", proof_bytes.len());
+        println!("Target transaction index: {}", tx_index);
+        println!("Transaction type: {}", tx_type);
+        println!(
+            "Encoded transaction size: {} bytes",
+            target_tx_encoded.len()
+        );
+        println!("Trie root: {:?}", computed_root);
+
+        bundles.push(MerkleProofBundle {
+            block_header: block_header.clone(),
+            transactions_root: computed_root,
+            merkle_proof: proof_bytes,
+            raw_transaction: target_tx_encoded.clone(),
+            transaction_index: tx_index,
+            proof_key: Bytes::from(target_key.clone()),
+            tx_type,
+        });
+    }
+
+    Ok(bundles)
+}
+
+/// Build the transactions trie from EIP-2718 encoded transactions, in block order, and extract the
+/// proofs that fix the block's transaction count: inclusion of the last transaction and absence of
+/// the index after it.
+///
+/// As for absence proofs, a computed root that differs from `transactions_root` is an error.
+pub fn build_transaction_count_proofs(
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    encoded_transactions: &[Bytes],
+) -> Result<TransactionCountInput> {
+    use alloy_rlp::encode as rlp_encode;
+
+    let transaction_count = encoded_transactions.len() as u64;
+    let last_index = transaction_count
+        .checked_sub(1)
+        .ok_or_else(|| eyre::eyre!("Block {} has no transactions to count", block_header.number))?;
+
+    let target_keys = vec![
+        rlp_encode(U256::from(last_index)),
+        rlp_encode(U256::from(transaction_count)),
+    ];
+    let leaves = transaction_trie_leaves(encoded_transactions);
+
+    let (computed_root, mut proofs) = backend.generate_proofs(&leaves, &target_keys);
+    if computed_root != block_header.transactions_root {
+        return Err(eyre::eyre!(
+            "Computed trie root {:?} does not match block {} transactions root {:?}",
+            computed_root,
+            block_header.number,
+            block_header.transactions_root
+        ));
+    }
+
+    let absence_proof = dedup_proof_nodes(proofs.remove(1));
+    let last_proof = dedup_proof_nodes(proofs.remove(0));
+    let last_raw_transaction = leaves[last_index as usize].1.clone();
+    backend
+        .verify_proof(
+            computed_root,
+            &target_keys[0],
+            Some(&last_raw_transaction),
+            &last_proof,
+        )
+        .map_err(|e| {
+            eyre::eyre!(
+                "Generated last transaction proof failed validation: {:?}",
+                e
+            )
+        })?;
+    backend
+        .verify_proof(computed_root, &target_keys[1], None, &absence_proof)
+        .map_err(|e| eyre::eyre!("Generated absence proof failed validation: {:?}", e))?;
+
+    println!(
+        "Proved block {} holds {} transactions",
+        block_header.number, transaction_count
+    );
+    Ok(TransactionCountInput {
+        last_transaction: TransactionProofInput {
+            raw_transaction: last_raw_transaction,
+            transaction_index: last_index,
+            merkle_proof: last_proof,
+        },
+        absence_proof,
+    })
+}
+
+/// Build the receipts trie from EIP-2718 encoded receipts, in block order, and extract proofs for the
+/// receipt at `tx_index` and the one before it.
+///
+/// As for the transactions trie, a computed root that differs from `receipts_root` is an error: the
+/// guest could never verify the resulting proofs.
+pub fn build_gas_used_proofs(
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    encoded_receipts: &[Bytes],
+    tx_index: u64,
+) -> Result<GasUsedInput> {
+    use alloy_rlp::encode as rlp_encode;
+
+    if tx_index as usize >= encoded_receipts.len() {
+        return Err(eyre::eyre!(
+            "Receipt index {} out of range for block {} with {} receipts",
+            tx_index,
+            block_header.number,
+            encoded_receipts.len()
+        ));
+    }
+
+    let indices: Vec<u64> = tx_index
+        .checked_sub(1)
+        .into_iter()
+        .chain([tx_index])
+        .collect();
+    let target_keys: Vec<_> = indices
+        .iter()
+        .map(|&index| rlp_encode(U256::from(index)))
+        .collect();
+    let leaves: Vec<_> = encoded_receipts
+        .iter()
+        .enumerate()
+        .map(|(i, encoded_receipt)| (rlp_encode(U256::from(i)), encoded_receipt.clone()))
+        .collect();
+
+    let (computed_root, proofs) = backend.generate_proofs(&leaves, &target_keys);
+    if computed_root != block_header.receipts_root {
+        return Err(eyre::eyre!(
+            "Computed receipts root {:?} does not match block {} receipts root {:?}",
+            computed_root,
+            block_header.number,
+            block_header.receipts_root
+        ));
+    }
+
+    let mut receipts = Vec::with_capacity(indices.len());
+    for ((&index, target_key), proof) in indices.iter().zip(&target_keys).zip(proofs) {
+        let proof = dedup_proof_nodes(proof);
+        let raw_receipt = leaves[index as usize].1.clone();
+        backend
+            .verify_proof(computed_root, target_key, Some(&raw_receipt), &proof)
+            .map_err(|e| eyre::eyre!("Generated receipt proof failed validation: {:?}", e))?;
+        receipts.push(ReceiptProofInput {
+            raw_receipt,
+            transaction_index: index,
+            merkle_proof: proof,
+        });
+    }
+
+    let receipt = receipts.pop().expect("the target receipt is always proved");
+    let gas_used = GasUsedInput {
+        receipt,
+        previous_receipt: receipts.pop(),
+    };
+    println!(
+        "Proved receipts for transaction {}: {} gas used",
+        tx_index,
+        receipt_gas_used(
+            &gas_used.receipt.raw_receipt,
+            gas_used
+                .previous_receipt
+                .as_ref()
+                .map(|previous| previous.raw_receipt.as_ref())
+        )?
+    );
+    Ok(gas_used)
+}
+
+/// Build the proof for the transaction at `tx_index` from a block RLP exported from a node, without a provider.
+pub fn generate_merkle_proof_from_rlp(
+    block_rlp: &[u8],
+    tx_index: u64,
+) -> Result<MerkleProofBundle> {
+    use alloy_consensus::{Block, TxEnvelope};
+    use alloy_eips::eip2718::Encodable2718;
+    use alloy_rlp::Decodable;
+
+    let mut buf = block_rlp;
+    let block = Block::<TxEnvelope>::decode(&mut buf)
+        .map_err(|e| eyre::eyre!("Failed to decode block RLP: {}", e))?;
+    if !buf.is_empty() {
+        return Err(eyre::eyre!("Block RLP has {} trailing bytes", buf.len()));
+    }
+
+    let encoded_transactions: Vec<_> = block
+        .body
+        .transactions
+        .iter()
+        .map(|tx| Bytes::from(tx.encoded_2718()))
+        .collect();
+    let mut bundles = build_merkle_proofs_for_encoded(
+        &KeccakMptBackend,
+        &block.header,
+        &encoded_transactions,
+        &[tx_index],
+        &ProofOptions::default(),
+    )?;
+    Ok(bundles.remove(0))
+}
+
+/// One line per leaf in block order: index, key nibbles, encoded length, the leaf value's keccak256
+/// hash, and the root of the trie over that leaf and all before it.
+///
+/// Rebuilds the trie for every prefix, so it is quadratic in the block size and meant for debugging.
+pub fn verbose_trie_log(backend: &dyn TrieBackend, leaves: &[(Vec<u8>, Bytes)]) -> Vec<String> {
+    (0..leaves.len())
+        .map(|i| {
+            let (key, value) = &leaves[i];
+            let key_nibbles: String = alloy_trie::Nibbles::unpack(key)
+                .to_vec()
+                .iter()
+                .map(|nibble| format!("{:x}", nibble))
+                .collect();
+            format!(
+                "trie leaf index={} key_nibbles={} encoded_len={} leaf_hash={:?} root={:?}",
+                i,
+                key_nibbles,
+                value.len(),
+                alloy_primitives::keccak256(value),
+                backend.build_root(&leaves[..=i])
+            )
+        })
+        .collect()
+}
+
+/// Write the encoded length and leading key nibbles of every leaf, so a mismatching root can be
+/// diffed against a reference. At most [`MAX_DEBUG_DUMP_TRANSACTIONS`] leaves are written.
+pub fn write_root_mismatch_dump(
+    path: &Path,
+    computed_root: B256,
+    expected_root: B256,
+    leaves: &[(Vec<u8>, Bytes)],
+) -> Result<()> {
+    std::fs::write(
+        path,
+        format_root_mismatch_dump(computed_root, expected_root, leaves),
+    )?;
+    Ok(())
+}
+
+fn format_root_mismatch_dump(
+    computed_root: B256,
+    expected_root: B256,
+    leaves: &[(Vec<u8>, Bytes)],
+) -> String {
+    use std::fmt::Write;
+
+    let mut dump = String::new();
+    writeln!(dump, "computed_root {:?}", computed_root).unwrap();
+    writeln!(dump, "expected_root {:?}", expected_root).unwrap();
+    writeln!(dump, "transactions {}", leaves.len()).unwrap();
+    writeln!(dump, "index\tencoded_len\ttype\tkey_nibbles").unwrap();
+
+    for (i, (key, value)) in leaves.iter().take(MAX_DEBUG_DUMP_TRANSACTIONS).enumerate() {
+        let nibbles = alloy_trie::Nibbles::unpack(key);
+        let key_prefix: String = nibbles
+            .to_vec()
+            .iter()
+            .take(DEBUG_DUMP_KEY_NIBBLES)
+            .map(|nibble| format!("{:x}", nibble))
+            .collect();
+        // A leaf that is not a transaction is dumped as type zero, like a legacy one
+        let tx_type = encoded_transaction_type(value).unwrap_or(0);
+        writeln!(dump, "{}\t{}\t{}\t{}", i, value.len(), tx_type, key_prefix).unwrap();
+    }
+
+    if leaves.len() > MAX_DEBUG_DUMP_TRANSACTIONS {
+        writeln!(
+            dump,
+            "truncated {} transactions",
+            leaves.len() - MAX_DEBUG_DUMP_TRANSACTIONS
+        )
+        .unwrap();
+    }
+
+    dump
+}
+
+/// Encode transaction for trie using the exact Ethereum format
+pub fn encode_transaction_for_trie(tx: &alloy_rpc_types::Transaction) -> Result<Bytes> {
+    use alloy_eips::eip2718::Encodable2718;
+
+    // println!("Encoding transaction hash: {}", tx.inner.hash());
+
+    // Use Alloy's built-in EIP-2718 encoding which should match Ethereum exactly
+    // This handles all transaction types with proper prefixes and RLP encoding
+    let encoded_bytes = tx.inner.encoded_2718();
+
+    // println!("Used EIP-2718 encoded transaction: {} bytes", encoded_bytes.len());
+    Ok(Bytes::from(encoded_bytes))
+}
+/// Check that `reencoded`, the EIP-2718 encoding a trie leaf is built from, is byte-for-byte the
+/// node's `raw` transaction. A divergence means the leaf, and so the computed transactions root,
+/// will not match the block's.
+pub fn check_reencoded_transaction(tx_hash: B256, reencoded: &[u8], raw: &[u8]) -> Result<()> {
+    if reencoded == raw {
+        return Ok(());
+    }
+    let offset = reencoded
+        .iter()
+        .zip(raw)
+        .position(|(reencoded, raw)| reencoded != raw)
+        .unwrap_or(reencoded.len().min(raw.len()));
+    Err(eyre::eyre!(
+        "Transaction {:?} re-encodes to {} bytes that diverge from the node's {} raw bytes at byte {}; its trie leaf will not match the block",
+        tx_hash,
+        reencoded.len(),
+        raw.len(),
+        offset
+    ))
+}
+
+/// Mainnet block at which Byzantium (EIP-658) replaced the receipt's post-state root with a
+/// status code.
+pub const MAINNET_BYZANTIUM_BLOCK: u64 = 4_370_000;
+
+/// What a block's receipts record about their transaction's outcome, which decides their trie
+/// encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReceiptFormat {
+    /// Pre-Byzantium: the intermediate state root after the transaction.
+    PostState,
+    /// The EIP-658 status code.
+    Status,
+}
+
+impl ReceiptFormat {
+    /// Format of the receipts in block `block_number` of chain `chain_id`. Only mainnet has
+    /// pre-Byzantium blocks among live chains; the others activated it at genesis.
+    pub fn at_block(chain_id: u64, block_number: u64) -> Self {
+        if chain_id == 1 && block_number < MAINNET_BYZANTIUM_BLOCK {
+            ReceiptFormat::PostState
+        } else {
+            ReceiptFormat::Status
+        }
+    }
+}
+
+/// Encode an RPC receipt the way it is stored in the receipts trie of a block whose receipts are
+/// in `format`.
+///
+/// Fails when the receipt records the other outcome field. Some nodes report a status for
+/// pre-Byzantium receipts in place of their `root`, which cannot reproduce the receipts root.
+pub fn encode_receipt_for_trie(
+    receipt: &alloy_rpc_types::TransactionReceipt,
+    format: ReceiptFormat,
+) -> Result<Bytes> {
+    use alloy_consensus::TxReceipt;
+    use alloy_eips::eip2718::Encodable2718;
+
+    let receipt = receipt.inner.clone().into_primitives_receipt();
+    match (format, receipt.status_or_post_state().is_post_state()) {
+        (ReceiptFormat::PostState, false) => Err(eyre::eyre!(
+            "Pre-Byzantium receipt has a status instead of its post-state root; use a node that returns `root`"
+        )),
+        (ReceiptFormat::Status, true) => Err(eyre::eyre!(
+            "Receipt has a post-state root, but its block is after Byzantium"
+        )),
+        _ => Ok(Bytes::from(receipt.encoded_2718())),
+    }
+}
+#[cfg(test)]
+mod test_utils;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        header_with_transactions, mainnet_genesis_header, rpc_transactions, MAINNET_GENESIS_HASH,
+    };
+
+    #[test]
+    fn header_rlp_length_matches_independent_encoding() {
+        let mut header = mainnet_genesis_header();
+        let encoded = alloy_rlp::encode(&header);
+
+        assert_eq!(header_rlp_length(&header).unwrap() as usize, encoded.len());
+        assert_eq!(alloy_primitives::keccak256(&encoded), MAINNET_GENESIS_HASH);
+
+        // The committed blockHash is the guest's hash_slow, which must be keccak256 over the same RLP
+        header.number = 20_000_000;
+        header.base_fee_per_gas = Some(7);
+        let encoded = alloy_rlp::encode(&header);
+        assert_eq!(header.hash_slow(), alloy_primitives::keccak256(&encoded));
+        assert_eq!(header_rlp_length(&header).unwrap() as usize, encoded.len());
+    }
+
+    #[test]
+    fn shuffled_transactions_are_put_back_in_index_order() {
+        let transactions = rpc_transactions(5);
+        let mut shuffled = transactions.clone();
+        shuffled.swap(0, 3);
+        shuffled.swap(1, 4);
+
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        let err = build_merkle_proof(
+            &KeccakMptBackend,
+            &header,
+            &shuffled,
+            1,
+            &Default::default(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("reports index 3 but is at position 0"));
+
+        order_transactions_by_index(&mut shuffled).unwrap();
+        assert_eq!(shuffled, transactions);
+
+        let mut duplicated = transactions.clone();
+        duplicated[2].transaction_index = Some(1);
+        assert!(order_transactions_by_index(&mut duplicated).is_err());
+    }
+
+    #[test]
+    fn adjacent_merkle_proofs_share_the_block_root() {
+        use alloy_rlp::encode as rlp_encode;
+
+        let transactions = rpc_transactions(130);
+        let (header, _, _) = header_with_transactions(mainnet_genesis_header(), &transactions);
+
+        // 127 -> 128 crosses from a one-byte to a two-byte RLP key
+        let (first, second) = build_adjacent_merkle_proofs(
+            &KeccakMptBackend,
+            &header,
+            &transactions,
+            127,
+            &ProofOptions::default(),
+        )
+        .expect("adjacent proofs should build");
+
+        assert_eq!(first.transaction_index, 127);
+        assert_eq!(second.transaction_index, 128);
+        for bundle in [&first, &second] {
+            assert_eq!(bundle.transactions_root, header.transactions_root);
+            KeccakMptBackend
+                .verify_proof(
+                    header.transactions_root,
+                    &rlp_encode(U256::from(bundle.transaction_index)),
+                    Some(&bundle.raw_transaction),
+                    &bundle.merkle_proof,
+                )
+                .expect("proof should verify against the header root");
+        }
+    }
+
+    #[test]
+    fn transaction_filter_excludes_transactions_from_the_trie() {
+        use alloy_consensus::Transaction as _;
+
+        let transactions = rpc_transactions(4);
+        // Keep everything except nonce 1, so the transaction with nonce 2 moves to index 1
+        let kept: Vec<_> = [0, 2, 3].map(|nonce| transactions[nonce].clone()).into();
+        let (header, _, _) = header_with_transactions(mainnet_genesis_header(), &kept);
+
+        let options = ProofOptions {
+            transaction_filter: Some(|tx| tx.inner.nonce() != 1),
+            ..Default::default()
+        };
+        let bundle = build_merkle_proof(&KeccakMptBackend, &header, &transactions, 1, &options)
+            .expect("filtered proof should build");
+
+        assert_eq!(bundle.transactions_root, header.transactions_root);
+        assert_eq!(
+            bundle.raw_transaction,
+            encode_transaction_for_trie(&transactions[2]).unwrap()
+        );
+        assert!(
+            build_merkle_proof(&KeccakMptBackend, &header, &transactions, 3, &options).is_err()
+        );
+    }
+
+    #[test]
+    fn verbose_trie_logs_each_leaf_without_changing_the_proof() {
+        let transactions = rpc_transactions(3);
+        let (header, encoded_transactions, _) =
+            header_with_transactions(mainnet_genesis_header(), &transactions);
+        let leaves = transaction_trie_leaves(&encoded_transactions);
+
+        let log = verbose_trie_log(&KeccakMptBackend, &leaves);
+        assert_eq!(log.len(), 3);
+        assert!(log[0].starts_with("trie leaf index=0 key_nibbles=80 "));
+        assert!(log[2].ends_with(&format!("root={:?}", header.transactions_root)));
+
+        let verbose = ProofOptions {
+            verbose_trie: true,
+            ..Default::default()
+        };
+        let quiet = build_merkle_proof(
+            &KeccakMptBackend,
+            &header,
+            &transactions,
+            1,
+            &Default::default(),
+        )
+        .unwrap();
+        let logged =
+            build_merkle_proof(&KeccakMptBackend, &header, &transactions, 1, &verbose).unwrap();
+        assert_eq!(logged.merkle_proof, quiet.merkle_proof);
+        assert_eq!(logged.transactions_root, quiet.transactions_root);
+    }
+
+    const BLOCK_RLP_FIXTURE: &[u8] = include_bytes!("fixtures/block-3-transactions.rlp");
+
+    #[test]
+    fn generate_merkle_proof_from_rlp_matches_header_root() {
+        use alloy_rlp::encode as rlp_encode;
+
+        let bundle = generate_merkle_proof_from_rlp(BLOCK_RLP_FIXTURE, 1)
+            .expect("proof should build from block RLP");
+
+        assert_eq!(
+            bundle.block_header.hash_slow(),
+            "0x5c51674f3f0c83c89f2458e7e542758f0bd1dd839454341b8a343603e128e9eb"
+                .parse::<B256>()
+                .unwrap()
+        );
+        assert_eq!(
+            bundle.transactions_root,
+            bundle.block_header.transactions_root
+        );
+        assert_eq!(bundle.raw_transaction[0], 0x02);
+        KeccakMptBackend
+            .verify_proof(
+                bundle.block_header.transactions_root,
+                &rlp_encode(U256::from(1)),
+                Some(&bundle.raw_transaction),
+                &bundle.merkle_proof,
+            )
+            .expect("proof should verify against the header root");
+    }
+
+    #[test]
+    fn computed_transactions_roots_match_across_transaction_types() {
+        use crate::test_utils::{signed_rpc_transaction, signed_set_code_rpc_transaction};
+        use alloy_consensus::{Block, TxEnvelope};
+        use alloy_rlp::Decodable;
+        use alloy_rpc_types::BlockTransactions;
+
+        let rpc_block = |header: Header, transactions| {
+            alloy_rpc_types::Block::new(
+                alloy_rpc_types::Header::new(header),
+                BlockTransactions::Full(transactions),
+            )
+        };
+
+        // A real EIP-1559-era block, served as an RPC block would be
+        let fixture = Block::<TxEnvelope>::decode(&mut &BLOCK_RLP_FIXTURE[..]).unwrap();
+        let transactions = fixture
+            .body
+            .transactions
+            .iter()
+            .enumerate()
+            .map(|(index, tx)| alloy_rpc_types::Transaction {
+                inner: alloy_consensus::transaction::Recovered::new_unchecked(
+                    tx.clone(),
+                    Address::ZERO,
+                ),
+                block_hash: None,
+                block_number: None,
+                transaction_index: Some(index as u64),
+                effective_gas_price: None,
+                block_timestamp: None,
+            })
+            .collect();
+        let block = rpc_block(fixture.header.clone(), transactions);
+        assert_eq!(
+            compute_transactions_root(&KeccakMptBackend, &block).unwrap(),
+            fixture.header.transactions_root
+        );
+
+        // Legacy, EIP-1559 and set-code transactions in one block, against alloy's own trie
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        let mut transactions = rpc_transactions(2);
+        transactions.push(signed_rpc_transaction(&signer, 0));
+        transactions.push(signed_set_code_rpc_transaction(&signer, 1));
+        for (index, tx) in transactions.iter_mut().enumerate() {
+            tx.transaction_index = Some(index as u64);
+        }
+        let envelopes: Vec<TxEnvelope> = transactions
+            .iter()
+            .map(|tx| tx.inner.inner().clone())
+            .collect();
+        let mut header = mainnet_genesis_header();
+        header.transactions_root = alloy_consensus::proofs::calculate_transaction_root(&envelopes);
+        let block = rpc_block(header.clone(), transactions);
+        assert_eq!(
+            compute_transactions_root(&KeccakMptBackend, &block).unwrap(),
+            header.transactions_root
+        );
+
+        assert_eq!(
+            compute_transactions_root(
+                &KeccakMptBackend,
+                &rpc_block(mainnet_genesis_header(), Vec::new())
+            )
+            .unwrap(),
+            alloy_trie::EMPTY_ROOT_HASH
+        );
+    }
+
+    #[test]
+    fn committed_calldata_hash_is_the_keccak_of_the_input_data() {
+        use alloy_consensus::{Block, TxEnvelope};
+        use alloy_eips::eip2718::Encodable2718;
+        use alloy_primitives::{keccak256, KECCAK256_EMPTY};
+        use alloy_rlp::Decodable;
+
+        let mut input = sample_input();
+        let fixture = Block::<TxEnvelope>::decode(&mut &BLOCK_RLP_FIXTURE[..]).unwrap();
+        let expected = [KECCAK256_EMPTY, keccak256([0xab; 4]), KECCAK256_EMPTY];
+        for (tx, expected) in fixture.body.transactions.iter().zip(expected) {
+            input.raw_transaction = tx.encoded_2718().into();
+            assert_eq!(input.committed_calldata_hash(), expected);
+        }
+
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        let set_code = crate::test_utils::signed_set_code_rpc_transaction(&signer, 0);
+        input.raw_transaction = encode_transaction_for_trie(&set_code).unwrap();
+        assert_eq!(input.committed_calldata_hash(), KECCAK256_EMPTY);
+
+        input.raw_transaction = Bytes::from(vec![0x7f, 0x00]);
+        assert_eq!(input.committed_calldata_hash(), B256::ZERO);
+        input.prove_absence = true;
+        input.raw_transaction = Bytes::new();
+        assert_eq!(input.committed_calldata_hash(), B256::ZERO);
+    }
+
+    #[test]
+    fn committed_access_list_entry_is_read_from_the_included_transaction() {
+        use alloy_consensus::{Signed, TxEip2930, TxEnvelope};
+        use alloy_eips::eip2718::Encodable2718;
+        use alloy_eips::eip2930::{AccessList, AccessListItem};
+        use alloy_primitives::{Signature, TxKind};
+
+        let entry = AccessListEntryInput {
+            address: Address::repeat_byte(0x33),
+            storage_key: B256::with_last_byte(7),
+        };
+        let tx = TxEip2930 {
+            chain_id: 1,
+            nonce: 0,
+            gas_price: 1_000_000_000,
+            gas_limit: 30_000,
+            to: TxKind::Call(entry.address),
+            value: U256::ZERO,
+            access_list: AccessList(vec![AccessListItem {
+                address: entry.address,
+                storage_keys: vec![B256::ZERO, entry.storage_key],
+            }]),
+            input: Default::default(),
+        };
+        let signature = Signature::new(U256::from(1), U256::from(1), false);
+        let mut input = sample_input();
+        input.raw_transaction = TxEnvelope::Eip2930(Signed::new_unhashed(tx, signature))
+            .encoded_2718()
+            .into();
+        assert!(!input.committed_has_access_list_entry());
+
+        input.access_list_entry = Some(entry);
+        assert!(input.committed_has_access_list_entry());
+        input.access_list_entry = Some(AccessListEntryInput {
+            storage_key: B256::with_last_byte(8),
+            ..entry
+        });
+        assert!(!input.committed_has_access_list_entry());
+
+        // The sample's legacy transaction carries no access list
+        let mut legacy = sample_input();
+        legacy.access_list_entry = Some(entry);
+        assert!(!legacy.committed_has_access_list_entry());
+
+        assert_eq!(
+            format!("{:?}:{:?}", entry.address, entry.storage_key)
+                .parse::<AccessListEntryInput>()
+                .unwrap(),
+            entry
+        );
+        assert!(format!("{:?}", entry.address)
+            .parse::<AccessListEntryInput>()
+            .is_err());
+    }
+
+    #[test]
+    fn committed_proof_depth_counts_the_proof_nodes() {
+        let mut input = sample_input();
+        for tx_index in 0..3 {
+            let bundle = generate_merkle_proof_from_rlp(BLOCK_RLP_FIXTURE, tx_index).unwrap();
+            input.merkle_proof = bundle.merkle_proof.clone();
+            assert_eq!(bundle.depth(), bundle.merkle_proof.len());
+            assert_eq!(
+                input.committed_proof_depth().unwrap() as usize,
+                bundle.depth()
+            );
+        }
+
+        input.merkle_proof = Vec::new();
+        assert_eq!(input.committed_proof_depth().unwrap(), 0);
+
+        // Too deep for u8, which the guest refuses rather than wrapping
+        input.merkle_proof = vec![Bytes::new(); 256];
+        assert!(input.committed_proof_depth().is_err());
+    }
+
+    #[test]
+    fn generate_merkle_proof_from_rlp_rejects_malformed_input() {
+        assert!(generate_merkle_proof_from_rlp(&BLOCK_RLP_FIXTURE[..100], 0).is_err());
+
+        let mut trailing = BLOCK_RLP_FIXTURE.to_vec();
+        trailing.push(0);
+        assert!(generate_merkle_proof_from_rlp(&trailing, 0).is_err());
+        assert!(generate_merkle_proof_from_rlp(BLOCK_RLP_FIXTURE, 3).is_err());
+    }
+
+    #[test]
+    fn verified_transaction_type_decodes_leaf() {
+        let eip1559 = generate_merkle_proof_from_rlp(BLOCK_RLP_FIXTURE, 1).unwrap();
+        assert_eq!(
+            verified_transaction_type(&eip1559.raw_transaction).unwrap(),
+            2
+        );
+
+        let legacy = generate_merkle_proof_from_rlp(BLOCK_RLP_FIXTURE, 0).unwrap();
+        assert_eq!(
+            verified_transaction_type(&legacy.raw_transaction).unwrap(),
+            0
+        );
+
+        assert!(verified_transaction_type(&[0x02, 0xc0]).is_err());
+    }
+
+    #[test]
+    fn decode_mode_decides_whether_a_corrupt_leaf_aborts() {
+        let corrupt = [0x02, 0xc3, 0x01, 0x02];
+        assert!(committed_transaction_type(&corrupt, DecodeMode::Strict).is_err());
+        assert_eq!(
+            committed_transaction_type(&corrupt, DecodeMode::Lenient).unwrap(),
+            None
+        );
+
+        let valid = encode_transaction_for_trie(&rpc_transactions(1)[0]).unwrap();
+        for mode in [DecodeMode::Strict, DecodeMode::Lenient] {
+            assert_eq!(committed_transaction_type(&valid, mode).unwrap(), Some(0));
+        }
+        assert_eq!(
+            "lenient".parse::<DecodeMode>().unwrap(),
+            DecodeMode::Lenient
+        );
+    }
+
+    #[test]
+    fn effective_gas_price_follows_each_fee_model() {
+        use alloy_consensus::{Signed, TxEip1559, TxEip4844, TxEnvelope, TxLegacy};
+        use alloy_eips::eip2718::Encodable2718;
+        use alloy_primitives::Signature;
+
+        let signature = Signature::test_signature();
+        let legacy = TxEnvelope::Legacy(Signed::new_unhashed(
+            TxLegacy {
+                gas_price: 30,
+                ..Default::default()
+            },
+            signature,
+        ));
+        let dynamic_fee = TxEnvelope::Eip1559(Signed::new_unhashed(
+            TxEip1559 {
+                max_fee_per_gas: 30,
+                max_priority_fee_per_gas: 5,
+                ..Default::default()
+            },
+            signature,
+        ));
+        let blob = TxEnvelope::Eip4844(Signed::new_unhashed(
+            TxEip4844 {
+                max_fee_per_gas: 40,
+                max_priority_fee_per_gas: 3,
+                max_fee_per_blob_gas: 1_000,
+                blob_versioned_hashes: vec![B256::repeat_byte(1)],
+                ..Default::default()
+            }
+            .into(),
+            signature,
+        ));
+        let legacy = legacy.encoded_2718();
+        let dynamic_fee = dynamic_fee.encoded_2718();
+        let blob = blob.encoded_2718();
+
+        // The legacy price ignores the base fee
+        assert_eq!(effective_gas_price(&legacy, Some(10)).unwrap(), 30);
+        assert_eq!(effective_gas_price(&legacy, None).unwrap(), 30);
+        // Base fee plus priority fee, until the max fee caps it
+        assert_eq!(effective_gas_price(&dynamic_fee, Some(10)).unwrap(), 15);
+        assert_eq!(effective_gas_price(&dynamic_fee, Some(28)).unwrap(), 30);
+        // Blob transactions price execution gas like EIP-1559, whatever the blob fee
+        assert_eq!(effective_gas_price(&blob, Some(10)).unwrap(), 13);
+        assert_eq!(effective_gas_price(&blob, Some(39)).unwrap(), 40);
+
+        assert!(effective_gas_price(&[0x02, 0xc0], Some(10)).is_err());
+    }
+
+    #[test]
+    fn set_code_transactions_encode_as_type_4_leaves() {
+        use crate::test_utils::{signed_rpc_transaction, signed_set_code_rpc_transaction};
+        use alloy_consensus::{Transaction, TxEnvelope};
+        use alloy_eips::eip2718::Decodable2718;
+
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        let set_code = signed_set_code_rpc_transaction(&signer, 1);
+
+        // Round trip through the JSON a node returns, which is what the host encodes for the trie
+        let from_rpc: alloy_rpc_types::Transaction =
+            serde_json::from_str(&serde_json::to_string(&set_code).unwrap()).unwrap();
+        let encoded = encode_transaction_for_trie(&from_rpc).unwrap();
+        assert_eq!(encoded[0], 0x04);
+        assert_eq!(
+            alloy_primitives::keccak256(&encoded),
+            *set_code.inner.hash()
+        );
+        assert_eq!(verified_transaction_type(&encoded).unwrap(), 4);
+        let decoded = TxEnvelope::decode_2718_exact(&encoded).unwrap();
+        assert_eq!(
+            decoded.authorization_list(),
+            set_code.inner.authorization_list()
+        );
+
+        // A block mixing legacy, EIP-1559 and set-code transactions proves the set-code leaf
+        let transactions = vec![
+            rpc_transactions(1).remove(0),
+            signed_rpc_transaction(&signer, 0),
+            from_rpc,
+        ];
+        let (header, _, _) = header_with_transactions(mainnet_genesis_header(), &transactions);
+        let bundle = build_merkle_proof(
+            &KeccakMptBackend,
+            &header,
+            &transactions,
+            2,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(bundle.transactions_root, header.transactions_root);
+        assert_eq!(bundle.raw_transaction, encoded);
+    }
+
+    #[test]
+    fn sender_ordering_proofs_require_one_sender() {
+        use crate::test_utils::signed_rpc_transaction;
+        use alloy_signer_local::PrivateKeySigner;
+
+        let sender = PrivateKeySigner::from_bytes(&B256::repeat_byte(1)).unwrap();
+        let other = PrivateKeySigner::from_bytes(&B256::repeat_byte(2)).unwrap();
+        let transactions = vec![
+            signed_rpc_transaction(&sender, 0),
+            signed_rpc_transaction(&other, 0),
+            signed_rpc_transaction(&sender, 1),
+        ];
+        let (header, _, _) = header_with_transactions(mainnet_genesis_header(), &transactions);
+        let options = ProofOptions::default();
+
+        let (first, second) =
+            build_sender_ordering_proofs(&KeccakMptBackend, &header, &transactions, 2, 0, &options)
+                .expect("same-sender proofs should build");
+        assert_eq!((first.transaction_index, second.transaction_index), (2, 0));
+        assert_eq!(
+            shared_sender(&first.raw_transaction, &second.raw_transaction).unwrap(),
+            sender.address()
+        );
+
+        assert!(build_sender_ordering_proofs(
+            &KeccakMptBackend,
+            &header,
+            &transactions,
+            0,
+            1,
+            &options
+        )
+        .is_err());
+        assert!(build_sender_ordering_proofs(
+            &KeccakMptBackend,
+            &header,
+            &transactions,
+            0,
+            0,
+            &options
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn inclusion_outcome_accounts_for_proof_mode() {
+        let tx_hash = B256::repeat_byte(7);
+        assert_eq!(
+            InclusionOutcome::from_committed(tx_hash, 4, true),
+            InclusionOutcome::IncludedAt(4)
+        );
+        assert!(matches!(
+            InclusionOutcome::from_committed(tx_hash, 4, false),
+            InclusionOutcome::Indeterminate(_)
+        ));
+        assert_eq!(
+            InclusionOutcome::from_committed(B256::ZERO, 4, false),
+            InclusionOutcome::ExcludedProven
+        );
+        assert!(matches!(
+            InclusionOutcome::from_committed(B256::ZERO, 4, true),
+            InclusionOutcome::Indeterminate(_)
+        ));
+    }
+
+    #[test]
+    fn verification_root_rejects_divergent_claimed_root() {
+        let header = mainnet_genesis_header();
+
+        assert_eq!(
+            verification_root(&header, None).unwrap(),
+            header.transactions_root
+        );
+        assert_eq!(
+            verification_root(&header, Some(header.transactions_root)).unwrap(),
+            header.transactions_root
+        );
+        assert!(verification_root(&header, Some(B256::repeat_byte(0xaa))).is_err());
+    }
+
+    #[test]
+    fn gas_used_proofs_require_the_header_receipts_root() {
+        use crate::test_utils::rpc_receipts;
+        use alloy_rlp::encode as rlp_encode;
+
+        let encoded_receipts: Vec<_> = rpc_receipts(&[21_000, 50_000])
+            .iter()
+            .map(|receipt| encode_receipt_for_trie(receipt, ReceiptFormat::Status).unwrap())
+            .collect();
+        let leaves: Vec<_> = encoded_receipts
+            .iter()
+            .enumerate()
+            .map(|(i, receipt)| (rlp_encode(U256::from(i)), receipt.clone()))
+            .collect();
+        let mut header = mainnet_genesis_header();
+        header.receipts_root = KeccakMptBackend.build_root(&leaves);
+
+        let gas_used_input =
+            build_gas_used_proofs(&KeccakMptBackend, &header, &encoded_receipts, 1).unwrap();
+        let previous_receipt = gas_used_input.previous_receipt.as_ref().unwrap();
+        assert_eq!(
+            receipt_gas_used(
+                &gas_used_input.receipt.raw_receipt,
+                Some(&previous_receipt.raw_receipt)
+            )
+            .unwrap(),
+            29_000
+        );
+        assert!(receipt_gas_used(
+            &previous_receipt.raw_receipt,
+            Some(&gas_used_input.receipt.raw_receipt)
+        )
+        .is_err());
+
+        header.receipts_root = B256::ZERO;
+        assert!(build_gas_used_proofs(&KeccakMptBackend, &header, &encoded_receipts, 1).is_err());
+    }
+
+    #[test]
+    fn pre_byzantium_receipts_reproduce_the_receipts_root() {
+        use crate::test_utils::{pre_byzantium_rpc_receipts, rpc_receipts};
+        use alloy_rlp::{encode as rlp_encode, Encodable, Header as RlpHeader};
+
+        // Block 46147 holds mainnet's first transaction, well before Byzantium
+        let block_number = 46_147;
+        let format = ReceiptFormat::at_block(1, block_number);
+        assert_eq!(format, ReceiptFormat::PostState);
+        assert_eq!(
+            ReceiptFormat::at_block(1, MAINNET_BYZANTIUM_BLOCK),
+            ReceiptFormat::Status
+        );
+        assert_eq!(
+            ReceiptFormat::at_block(11_155_111, block_number),
+            ReceiptFormat::Status
+        );
+
+        // Round trip through JSON so the post-state root arrives as a node's `root` field
+        let receipts: Vec<alloy_rpc_types::TransactionReceipt> = serde_json::from_value(
+            serde_json::to_value(pre_byzantium_rpc_receipts(&[21_000, 50_000])).unwrap(),
+        )
+        .unwrap();
+        let encoded_receipts = receipts
+            .iter()
+            .map(|receipt| encode_receipt_for_trie(receipt, format))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+
+        // The pre-Byzantium receipt is rlp([postStateRoot, cumulativeGasUsed, logsBloom, logs])
+        let leaves: Vec<_> = [21_000u64, 50_000]
+            .iter()
+            .enumerate()
+            .map(|(i, cumulative_gas_used)| {
+                let mut payload = Vec::new();
+                B256::repeat_byte(0x40 + i as u8).encode(&mut payload);
+                cumulative_gas_used.encode(&mut payload);
+                alloy_primitives::Bloom::ZERO.encode(&mut payload);
+                payload.push(alloy_rlp::EMPTY_LIST_CODE);
+                let mut receipt = Vec::new();
+                RlpHeader {
+                    list: true,
+                    payload_length: payload.len(),
+                }
+                .encode(&mut receipt);
+                receipt.extend(payload);
+                (rlp_encode(U256::from(i)), Bytes::from(receipt))
+            })
+            .collect();
+        for ((_, expected), encoded) in leaves.iter().zip(&encoded_receipts) {
+            assert_eq!(expected, encoded);
+        }
+
+        let mut header = mainnet_genesis_header();
+        header.number = block_number;
+        header.receipts_root = KeccakMptBackend.build_root(&leaves);
+        let gas_used_input =
+            build_gas_used_proofs(&KeccakMptBackend, &header, &encoded_receipts, 1).unwrap();
+        let raw_receipt = &gas_used_input.receipt.raw_receipt;
+        assert!(!receipt_status(raw_receipt).unwrap());
+        assert_eq!(
+            receipt_post_state_root(raw_receipt).unwrap(),
+            Some(B256::repeat_byte(0x41))
+        );
+
+        // A node reporting a status for these receipts has dropped the root the trie needs
+        let status_receipt = &rpc_receipts(&[21_000])[0];
+        assert!(encode_receipt_for_trie(status_receipt, format).is_err());
+        assert!(encode_receipt_for_trie(&receipts[0], ReceiptFormat::Status).is_err());
+    }
+
+    #[test]
+    fn transaction_count_proofs_fix_the_last_index() {
+        use alloy_rlp::encode as rlp_encode;
+
+        let (mut header, encoded_transactions, _) =
+            header_with_transactions(mainnet_genesis_header(), &rpc_transactions(3));
+
+        let count_input =
+            build_transaction_count_proofs(&KeccakMptBackend, &header, &encoded_transactions)
+                .unwrap();
+        let transaction_count = count_input.last_transaction.transaction_index + 1;
+        assert_eq!(transaction_count, 3);
+        KeccakMptBackend
+            .verify_proof(
+                header.transactions_root,
+                &rlp_encode(U256::from(transaction_count)),
+                None,
+                &count_input.absence_proof,
+            )
+            .expect("absence proof should verify");
+
+        assert_eq!(index_from_end(transaction_count, 2).unwrap(), 0);
+        assert_eq!(index_from_end(transaction_count, 0).unwrap(), 2);
+        assert!(index_from_end(transaction_count, 3).is_err());
+        assert!(index_from_end(0, 0).is_err());
+
+        header.transactions_root = B256::ZERO;
+        assert!(
+            build_transaction_count_proofs(&KeccakMptBackend, &header, &encoded_transactions)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn transaction_count_proofs_establish_the_count_of_a_known_block() {
+        use alloy_consensus::{Block, TxEnvelope};
+        use alloy_eips::eip2718::Encodable2718;
+        use alloy_rlp::{encode as rlp_encode, Decodable};
+
+        let block = Block::<TxEnvelope>::decode(&mut &BLOCK_RLP_FIXTURE[..]).unwrap();
+        let encoded_transactions: Vec<_> = block
+            .body
+            .transactions
+            .iter()
+            .map(|tx| Bytes::from(tx.encoded_2718()))
+            .collect();
+        let root = block.header.transactions_root;
+
+        let count_input =
+            build_transaction_count_proofs(&KeccakMptBackend, &block.header, &encoded_transactions)
+                .unwrap();
+        let last = &count_input.last_transaction;
+        assert_eq!(last.transaction_index + 1, 3);
+        assert_eq!(last.raw_transaction, encoded_transactions[2]);
+        KeccakMptBackend
+            .verify_proof(
+                root,
+                &rlp_encode(U256::from(2)),
+                Some(&last.raw_transaction),
+                &last.merkle_proof,
+            )
+            .expect("the last transaction should be proved at index 2");
+        KeccakMptBackend
+            .verify_proof(
+                root,
+                &rlp_encode(U256::from(3)),
+                None,
+                &count_input.absence_proof,
+            )
+            .expect("no transaction should be proved at index 3");
+
+        // Neither proof can be reused for a different count
+        assert!(KeccakMptBackend
+            .verify_proof(
+                root,
+                &rlp_encode(U256::from(2)),
+                None,
+                &count_input.absence_proof
+            )
+            .is_err());
+        assert!(KeccakMptBackend
+            .verify_proof(
+                root,
+                &rlp_encode(U256::from(3)),
+                Some(&last.raw_transaction),
+                &last.merkle_proof,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn mined_transaction_position_distinguishes_pending_from_missing_index() {
+        let mut tx = rpc_transactions(1).remove(0);
+        tx.block_number = Some(7);
+        assert_eq!(mined_transaction_position(&tx).unwrap(), (7, 0));
+
+        tx.transaction_index = None;
+        let missing_index = mined_transaction_position(&tx).unwrap_err().to_string();
+        assert!(missing_index.contains("mined in block 7"));
+
+        tx.block_number = None;
+        let pending = mined_transaction_position(&tx).unwrap_err().to_string();
+        assert!(pending.contains("not mined yet"));
+        assert!(pending.contains("wait for it to be included"));
+    }
+
+    #[test]
+    fn parent_linkage_requires_number_and_hash() {
+        let parent = mainnet_genesis_header();
+        let mut child = parent.clone();
+        child.number = 1;
+        child.parent_hash = MAINNET_GENESIS_HASH;
+        verify_parent_linkage(&parent, &child).expect("child should link to genesis");
+
+        let mut skipped = child.clone();
+        skipped.number = 2;
+        assert!(verify_parent_linkage(&parent, &skipped).is_err());
+
+        let mut unlinked = child;
+        unlinked.parent_hash = B256::repeat_byte(1);
+        assert!(verify_parent_linkage(&parent, &unlinked).is_err());
+    }
+
+    fn sample_input() -> TransactionInclusionInput {
+        let transactions = rpc_transactions(2);
+        let raw_transaction = encode_transaction_for_trie(&transactions[1]).unwrap();
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        header.parent_hash = MAINNET_GENESIS_HASH;
+        header.base_fee_per_gas = Some(7);
+        TransactionInclusionInput {
+            block_header: header,
+            parent_block_header: mainnet_genesis_header(),
+            committed_raw_transaction: raw_transaction.clone(),
+            sender_account: AccountState {
+                nonce: 1,
+                balance: U256::from(10).pow(U256::from(18)),
+                storage_root: alloy_trie::EMPTY_ROOT_HASH,
+                code_hash: alloy_primitives::KECCAK256_EMPTY,
+            },
+            sender_account_proof: vec![Bytes::from_static(&[0xc0])],
+            raw_transaction,
+            transaction_index: 1,
+            merkle_proof: vec![Bytes::from_static(&[1, 2, 3])],
+            prove_absence: false,
+            trie_backend: TrieBackendKind::KeccakMpt,
+            expected_block_hash: Some(B256::repeat_byte(4)),
+            adjacent_transaction: None,
+            sender_ordering: None,
+            claimed_root: Some(B256::repeat_byte(5)),
+            gas_used: None,
+            transaction_count: None,
+            cross_block: None,
+            partial_header: None,
+            decode_mode: DecodeMode::default(),
+            slot: None,
+            proved_header: ProvedHeader::default(),
+            access_list_entry: None,
+        }
+    }
+
+    #[test]
+    fn slot_inputs_match_only_the_slot_start_timestamp() {
+        // Mainnet beacon genesis and the first post-merge block, proposed in slot 4700013
+        let slot = SlotInput {
+            slot: 4_700_013,
+            beacon_genesis_time: 1_606_824_023,
+        };
+        let mut header = mainnet_genesis_header();
+        header.number = 15_537_394;
+        header.timestamp = 1_663_224_179;
+        slot.check_header(&header).unwrap();
+
+        header.timestamp += SECONDS_PER_SLOT;
+        let err = slot.check_header(&header).unwrap_err();
+        assert!(err.to_string().contains("not the start of slot 4700013"));
+
+        let overflowing = SlotInput {
+            slot: u64::MAX,
+            beacon_genesis_time: 1,
+        };
+        assert_eq!(overflowing.timestamp(), None);
+        assert!(overflowing.check_header(&header).is_err());
+    }
+
+    #[test]
+    fn input_formats_round_trip() {
+        let input = sample_input();
+        for format in [InputFormat::Bincode, InputFormat::Json] {
+            let encoded = format.encode(&input).unwrap();
+            let decoded = format.decode(&encoded).unwrap();
+            assert_eq!(decoded.block_header, input.block_header, "{:?}", format);
+            assert_eq!(
+                decoded.block_header.hash_slow(),
+                input.block_header.hash_slow()
+            );
+            assert_eq!(decoded.parent_block_header, input.parent_block_header);
+            // Re-encoding is stable, so every other field survived the round trip
+            assert_eq!(format.encode(&decoded).unwrap(), encoded, "{:?}", format);
+        }
+        assert_eq!(
+            "json".parse::<InputFormat>().unwrap(),
+            InputFormat::default()
+        );
+        assert!("messagepack"
+            .parse::<InputFormat>()
+            .unwrap_err()
+            .to_string()
+            .contains("not supported"));
+        assert!("yaml".parse::<InputFormat>().is_err());
+    }
+
+    #[test]
+    fn block_numbers_paired_with_another_blocks_root_are_rejected() {
+        let bundle = generate_merkle_proof_from_rlp(BLOCK_RLP_FIXTURE, 0).unwrap();
+        let mut input = sample_input();
+        input.block_header = bundle.block_header.clone();
+        input
+            .check_canonical_transactions_root(bundle.block_header.transactions_root)
+            .unwrap();
+
+        // Genesis' number with the fixture block's root
+        input.block_header.number = 0;
+        let err = input
+            .check_canonical_transactions_root(mainnet_genesis_header().transactions_root)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("but the canonical block 0 has transactions root"));
+
+        // A partial header's number and root are checked the same way
+        input.partial_header = Some(PartialHeaderInput {
+            block_number: 0,
+            transactions_root: alloy_trie::EMPTY_ROOT_HASH,
+        });
+        input
+            .check_canonical_transactions_root(mainnet_genesis_header().transactions_root)
+            .unwrap();
+    }
+
+    #[test]
+    fn content_hash_is_stable_for_the_same_transaction_and_block() {
+        // Each call rebuilds the trie and proof from the block, as separate invocations would
+        let input_at = |tx_index| {
+            let bundle = generate_merkle_proof_from_rlp(BLOCK_RLP_FIXTURE, tx_index).unwrap();
+            TransactionInclusionInput {
+                block_header: bundle.block_header,
+                committed_raw_transaction: bundle.raw_transaction.clone(),
+                raw_transaction: bundle.raw_transaction,
+                transaction_index: tx_index,
+                merkle_proof: bundle.merkle_proof,
+                ..sample_input()
+            }
+        };
+
+        let hash = input_at(1).content_hash().unwrap();
+        assert_eq!(input_at(1).content_hash().unwrap(), hash);
+        assert_ne!(input_at(2).content_hash().unwrap(), hash);
+
+        // The file format an input was stored in does not change it
+        let json = InputFormat::Json.encode(&input_at(1)).unwrap();
+        assert_eq!(
+            InputFormat::Json
+                .decode(&json)
+                .unwrap()
+                .content_hash()
+                .unwrap(),
+            hash
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_inputs_are_detected_and_round_trip() {
+        let mut input = sample_input();
+        input.merkle_proof = vec![Bytes::from(vec![0xab; 532]); 8];
+
+        for format in [InputFormat::Bincode, InputFormat::Json] {
+            let plain = format.encode(&input).unwrap();
+            let compressed = format.encode_compressed(&input).unwrap();
+            assert!(compressed.starts_with(&COMPRESSED_INPUT_MAGIC));
+            assert!(compressed.len() < plain.len(), "{:?}", format);
+
+            // The same decode reads both, so loaders need no flag to tell them apart
+            for bytes in [&plain, &compressed] {
+                let decoded = format.decode(bytes).unwrap();
+                assert_eq!(format.encode(&decoded).unwrap(), plain, "{:?}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_retained_proofs_fall_back_to_the_single_leaf_node() {
+        /// The keccak trie, but retaining no proof nodes, as a degenerate hash builder would.
+        struct RetainsNothing;
+
+        impl TrieBackend for RetainsNothing {
+            fn build_root(&self, leaves: &[(Vec<u8>, Bytes)]) -> B256 {
+                KeccakMptBackend.build_root(leaves)
+            }
+
+            fn generate_proof(&self, leaves: &[(Vec<u8>, Bytes)], _: &[u8]) -> (B256, Vec<Bytes>) {
+                (self.build_root(leaves), Vec::new())
+            }
+
+            fn verify_proof(
+                &self,
+                root: B256,
+                key: &[u8],
+                value: Option<&[u8]>,
+                proof: &[Bytes],
+            ) -> Result<()> {
+                KeccakMptBackend.verify_proof(root, key, value, proof)
+            }
+        }
+
+        let transactions = rpc_transactions(2);
+        let single = &transactions[..1];
+        let raw_transaction = encode_transaction_for_trie(&single[0]).unwrap();
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        header.transactions_root =
+            KeccakMptBackend.build_root(&[(alloy_rlp::encode(U256::ZERO), raw_transaction)]);
+
+        let expected =
+            build_merkle_proof(&KeccakMptBackend, &header, single, 0, &Default::default()).unwrap();
+        let bundle =
+            build_merkle_proof(&RetainsNothing, &header, single, 0, &Default::default()).unwrap();
+        assert_eq!(bundle.merkle_proof.len(), 1);
+        assert_eq!(bundle.merkle_proof, expected.merkle_proof);
+
+        // Nothing can be rebuilt once the trie has more than one leaf
+        let (header, _, _) = header_with_transactions(header, &transactions);
+        let error = build_merkle_proof(
+            &RetainsNothing,
+            &header,
+            &transactions,
+            0,
+            &Default::default(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("only a single-leaf trie"));
+    }
+
+    #[test]
+    fn check_index_rejects_index_the_proof_was_not_built_for() {
+        let transactions = rpc_transactions(3);
+        let (header, _, _) = header_with_transactions(mainnet_genesis_header(), &transactions);
+
+        let bundle = build_merkle_proof(
+            &KeccakMptBackend,
+            &header,
+            &transactions,
+            1,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(bundle.proof_key, Bytes::from(alloy_rlp::encode(1u64)));
+        bundle.check_index(1).expect("proof was built for index 1");
+        assert!(bundle.check_index(2).is_err());
+
+        let mut mismatched = bundle.clone();
+        mismatched.proof_key = Bytes::from(alloy_rlp::encode(2u64));
+        assert!(mismatched.check_index(1).is_err());
+        mismatched.proof_key = Bytes::from_static(&[0x81]);
+        assert!(mismatched.check_index(1).is_err());
+    }
+
+    #[test]
+    fn check_root_compares_the_computed_root() {
+        let transactions = rpc_transactions(1);
+        let raw_transaction = encode_transaction_for_trie(&transactions[0]).unwrap();
+        let mut header = mainnet_genesis_header();
+        header.transactions_root =
+            KeccakMptBackend.build_root(&[(alloy_rlp::encode(U256::ZERO), raw_transaction)]);
+
+        let bundle = build_merkle_proof(
+            &KeccakMptBackend,
+            &header,
+            &transactions,
+            0,
+            &Default::default(),
+        )
+        .unwrap();
+        bundle
+            .check_root(header.transactions_root)
+            .expect("the computed root is the header's");
+        let mismatch = bundle
+            .check_root(B256::repeat_byte(7))
+            .unwrap_err()
+            .to_string();
+        assert!(mismatch.contains("does not match the asserted root"));
+    }
+
+    #[test]
+    fn receipt_status_reads_legacy_and_typed_receipts() {
+        use alloy_consensus::{Eip658Value, Receipt, ReceiptEnvelope, ReceiptWithBloom};
+        use alloy_eips::eip2718::Encodable2718;
+
+        let encode = |succeeded: bool, typed: bool| {
+            let receipt = ReceiptWithBloom {
+                receipt: Receipt::<alloy_primitives::Log> {
+                    status: Eip658Value::Eip658(succeeded),
+                    cumulative_gas_used: 21_000,
+                    logs: Vec::new(),
+                },
+                logs_bloom: alloy_primitives::Bloom::ZERO,
+            };
+            let envelope = if typed {
+                ReceiptEnvelope::Eip1559(receipt)
+            } else {
+                ReceiptEnvelope::Legacy(receipt)
+            };
+            envelope.encoded_2718()
+        };
+
+        for typed in [false, true] {
+            assert!(
+                receipt_status(&encode(true, typed)).unwrap(),
+                "typed: {}",
+                typed
+            );
+            assert!(
+                !receipt_status(&encode(false, typed)).unwrap(),
+                "typed: {}",
+                typed
+            );
+        }
+        assert!(receipt_status(&[0x02]).is_err());
+    }
+
+    #[test]
+    fn bundles_report_the_encoded_transaction_type() {
+        use alloy_consensus::{
+            SignableTransaction, TxEip1559, TxEip2930, TxEip4844, TxEnvelope, TxLegacy,
+        };
+        use alloy_primitives::{Signature, TxKind};
+
+        let signature = Signature::new(U256::from(1), U256::from(1), false);
+        let to = Address::repeat_byte(0x11);
+        let envelopes: Vec<TxEnvelope> = vec![
+            TxLegacy {
+                chain_id: Some(1),
+                gas_limit: 21_000,
+                to: TxKind::Call(to),
+                ..Default::default()
+            }
+            .into_signed(signature)
+            .into(),
+            TxEip2930 {
+                chain_id: 1,
+                nonce: 1,
+                gas_limit: 21_000,
+                to: TxKind::Call(to),
+                ..Default::default()
+            }
+            .into_signed(signature)
+            .into(),
+            TxEip1559 {
+                chain_id: 1,
+                nonce: 2,
+                gas_limit: 21_000,
+                to: TxKind::Call(to),
+                ..Default::default()
+            }
+            .into_signed(signature)
+            .into(),
+            TxEip4844 {
+                chain_id: 1,
+                nonce: 3,
+                gas_limit: 21_000,
+                to,
+                blob_versioned_hashes: vec![B256::repeat_byte(1)],
+                ..Default::default()
+            }
+            .into_signed(signature)
+            .into(),
+        ];
+        let transactions: Vec<_> = envelopes
+            .into_iter()
+            .enumerate()
+            .map(|(index, envelope)| alloy_rpc_types::Transaction {
+                inner: alloy_consensus::transaction::Recovered::new_unchecked(
+                    envelope,
+                    Address::repeat_byte(0x22),
+                ),
+                block_hash: None,
+                block_number: None,
+                transaction_index: Some(index as u64),
+                effective_gas_price: None,
+                block_timestamp: None,
+            })
+            .collect();
+
+        let mut block_1 = mainnet_genesis_header();
+        block_1.number = 1;
+        let (header, _, _) = header_with_transactions(block_1, &transactions);
+        for (index, expected_type) in [0u8, 1, 2, 3].into_iter().enumerate() {
+            let bundle = build_merkle_proof(
+                &KeccakMptBackend,
+                &header,
+                &transactions,
+                index as u64,
+                &ProofOptions::default(),
+            )
+            .unwrap();
+            assert_eq!(bundle.tx_type, expected_type);
+            assert_eq!(
+                verified_transaction_type(&bundle.raw_transaction).unwrap(),
+                expected_type
+            );
+        }
+        assert!(encoded_transaction_type(&[]).is_err());
+        assert!(encoded_transaction_type(&[0x80]).is_err());
+    }
+
+    #[test]
+    fn build_merkle_proof_rejects_empty_block() {
+        assert!(build_merkle_proof(
+            &KeccakMptBackend,
+            &mainnet_genesis_header(),
+            &[],
+            0,
+            &ProofOptions::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn block_tag_parses_known_tags() {
+        for tag in [BlockTag::Latest, BlockTag::Safe, BlockTag::Finalized] {
+            assert_eq!(tag.as_str().parse::<BlockTag>().unwrap(), tag);
+        }
+        assert!(BlockTag::Finalized.is_finalized());
+        assert!(!BlockTag::Safe.is_finalized());
+        assert!("pending".parse::<BlockTag>().is_err());
+    }
+
+    #[test]
+    fn log_selector_parses_address_topic_and_range() {
+        let address = Address::repeat_byte(0xaa);
+        let topic0 = B256::repeat_byte(0xbb);
+        let selector: LogSelector = format!("{}:{}:100-120", address, topic0).parse().unwrap();
+        assert_eq!(
+            selector,
+            LogSelector {
+                address,
+                topic0,
+                from_block: 100,
+                to_block: 120,
+            }
+        );
+
+        let single: LogSelector = format!("{}:{}:7", address, topic0).parse().unwrap();
+        assert_eq!((single.from_block, single.to_block), (7, 7));
+
+        assert!(format!("{}:{}:120-100", address, topic0)
+            .parse::<LogSelector>()
+            .is_err());
+        assert!(format!("{}:100-120", address)
+            .parse::<LogSelector>()
+            .is_err());
+    }
+
+    #[test]
+    fn adjacent_merkle_proofs_reject_last_transaction() {
+        let transactions = rpc_transactions(3);
+        assert!(build_adjacent_merkle_proofs(
+            &KeccakMptBackend,
+            &mainnet_genesis_header(),
+            &transactions,
+            2,
+            &ProofOptions::default(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn inclusion_proofs_reject_a_root_mismatch_after_writing_the_dump() {
+        let (mut header, encoded_transactions, _) =
+            header_with_transactions(mainnet_genesis_header(), &rpc_transactions(2));
+        header.transactions_root = B256::repeat_byte(0x11);
+        let dump_path = std::env::temp_dir().join(format!(
+            "inclusion-root-mismatch-{}.tsv",
+            std::process::id()
+        ));
+        let options = ProofOptions {
+            debug_dump: Some(dump_path.clone()),
+            ..Default::default()
+        };
+
+        let error = build_merkle_proofs_for_encoded(
+            &KeccakMptBackend,
+            &header,
+            &encoded_transactions,
+            &[1],
+            &options,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("does not match block 0"));
+        let dump = std::fs::read_to_string(&dump_path).unwrap();
+        std::fs::remove_file(&dump_path).unwrap();
+        assert!(dump.contains("transactions 2"));
+    }
+
+    #[test]
+    fn root_mismatch_dump_lists_lengths_and_key_nibbles() {
+        use alloy_rlp::encode as rlp_encode;
+
+        let leaves = vec![
+            (rlp_encode(U256::from(0)), Bytes::from(vec![0xf8; 10])),
+            (rlp_encode(U256::from(200)), Bytes::from(vec![0x02; 30])),
+            (rlp_encode(U256::from(1)), Bytes::from(vec![0x7f; 5])),
+        ];
+        let dump = format_root_mismatch_dump(B256::ZERO, B256::repeat_byte(1), &leaves);
+        let lines: Vec<_> = dump.lines().collect();
+
+        assert_eq!(lines[2], "transactions 3");
+        assert_eq!(lines[4], "0\t10\t0\t80");
+        assert_eq!(lines[5], "1\t30\t2\t81c8");
+        // The highest typed transaction byte is its type, as encoded_transaction_type reads it
+        assert_eq!(lines[6], "2\t5\t127\t01");
+    }
+
+    #[test]
+    fn root_mismatch_dump_is_bounded() {
+        let leaves =
+            vec![(vec![0x80], Bytes::from_static(&[0x01])); MAX_DEBUG_DUMP_TRANSACTIONS + 5];
+        let dump = format_root_mismatch_dump(B256::ZERO, B256::ZERO, &leaves);
+
+        assert_eq!(dump.lines().count(), 4 + MAX_DEBUG_DUMP_TRANSACTIONS + 1);
+        assert!(dump.ends_with("truncated 5 transactions\n"));
+    }
+
+    #[test]
+    fn verify_block_header_hash_accepts_known_block_hash() {
+        verify_block_header_hash(&mainnet_genesis_header(), MAINNET_GENESIS_HASH)
+            .expect("mainnet genesis header should hash to the genesis block hash");
+    }
+
+    #[test]
+    fn verify_block_header_hash_rejects_other_block_hash() {
+        let mut header = mainnet_genesis_header();
+        header.gas_limit += 1;
+        assert!(verify_block_header_hash(&header, MAINNET_GENESIS_HASH).is_err());
+    }
 }
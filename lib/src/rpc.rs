@@ -0,0 +1,2200 @@
+use alloy::providers::Provider;
+use alloy_consensus::{transaction::SignerRecoverable, Header, Transaction, TxEnvelope};
+use alloy_eips::eip2718::Decodable2718;
+use alloy_primitives::{Address, Bytes, B256, U256};
+use alloy_rpc_types::{Block, BlockId, BlockTransactions};
+use eyre::Result;
+
+use crate::{
+    build_adjacent_merkle_proofs, build_gas_used_proofs, build_merkle_proof,
+    build_sender_ordering_proofs, build_transaction_count_proofs, check_reencoded_transaction,
+    dedup_proof_nodes, encode_receipt_for_trie, encode_transaction_for_trie,
+    encode_trie_transactions, order_transactions_by_index, receipt_gas_used,
+    reconcile_transaction_index, transaction_trie_leaves, verbose_trie_log,
+    verify_block_header_hash, verify_parent_linkage, write_root_mismatch_dump, AccountState,
+    BlockTag, CrossBlockInput, DecodeMode, GasUsedInput, KeccakMptBackend, LogSelector,
+    MerkleProofBundle, ProofOptions, ProvedHeader, ReceiptFormat, SenderAccountWitness,
+    TransactionCountInput, TransactionFilter, TransactionInclusionInput, TrieBackend,
+    TrieBackendKind,
+};
+
+/// Fetch the header of the block with `block_hash`, checking that it hashes back to `block_hash`.
+pub async fn fetch_block_header_by_hash(
+    provider: &impl Provider,
+    block_hash: B256,
+) -> Result<Header> {
+    let block = provider
+        .get_block_by_hash(block_hash)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Block not found: {:?}", block_hash))?;
+    let header: Header = block.header.into();
+    verify_block_header_hash(&header, block_hash)?;
+    Ok(header)
+}
+/// Generate real Merkle proof for a transaction at a precise index in a block with exact Ethereum encoding
+pub async fn generate_merkle_proof(
+    provider: &impl Provider,
+    block_number: u64,
+    tx_index: u64,
+) -> Result<(Vec<Bytes>, Bytes)> {
+    generate_merkle_proof_with_backend(provider, &KeccakMptBackend, block_number, tx_index).await
+}
+
+/// Generate a Merkle proof for a transaction at a precise index using the given trie backend
+pub async fn generate_merkle_proof_with_backend(
+    provider: &impl Provider,
+    backend: &dyn TrieBackend,
+    block_number: u64,
+    tx_index: u64,
+) -> Result<(Vec<Bytes>, Bytes)> {
+    let bundle = generate_merkle_proof_at(
+        provider,
+        backend,
+        BlockId::Number(block_number.into()),
+        tx_index,
+        &ProofOptions::default(),
+    )
+    .await?;
+    Ok((bundle.merkle_proof, bundle.raw_transaction))
+}
+
+/// Generate a Merkle proof for a transaction at a precise index in the block identified by `block_id`.
+///
+/// `BlockId::pending()` is accepted for monitoring, but pending blocks are not canonical and a proof
+/// against one can never be used on-chain.
+pub async fn generate_merkle_proof_at(
+    provider: &impl Provider,
+    backend: &dyn TrieBackend,
+    block_id: BlockId,
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<MerkleProofBundle> {
+    println!(
+        "Generating Merkle proof for transaction at precise index {} in block {}",
+        tx_index, block_id
+    );
+    if block_id.is_pending() {
+        warn_pending_block();
+    }
+
+    // Fetch the block with FULL transaction details
+    let block = fetch_full_block(provider, block_id).await?;
+    check_fetched_block(provider, block_id, &block, options).await?;
+    let BlockTransactions::Full(complete_transactions) = &block.transactions else {
+        unreachable!("fetch_full_block only returns full transactions");
+    };
+
+    build_merkle_proof(
+        backend,
+        &block.header.clone().into(),
+        complete_transactions,
+        tx_index,
+        options,
+    )
+}
+
+/// Generate proofs for the transactions at `tx_index` and `tx_index + 1` of the block at
+/// `block_id`, building its transactions trie once.
+pub async fn generate_adjacent_merkle_proofs_at(
+    provider: &impl Provider,
+    backend: &dyn TrieBackend,
+    block_id: BlockId,
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<(MerkleProofBundle, MerkleProofBundle)> {
+    println!(
+        "Generating Merkle proofs for adjacent transactions at indices {} and {} in block {}",
+        tx_index,
+        tx_index + 1,
+        block_id
+    );
+    if block_id.is_pending() {
+        warn_pending_block();
+    }
+
+    let block = fetch_full_block(provider, block_id).await?;
+    check_fetched_block(provider, block_id, &block, options).await?;
+    let BlockTransactions::Full(complete_transactions) = &block.transactions else {
+        unreachable!("fetch_full_block only returns full transactions");
+    };
+
+    build_adjacent_merkle_proofs(
+        backend,
+        &block.header.clone().into(),
+        complete_transactions,
+        tx_index,
+        options,
+    )
+}
+
+/// Generate proofs for two transactions from one sender in the block at `block_id`, building its
+/// transactions trie once.
+pub async fn generate_sender_ordering_proofs_at(
+    provider: &impl Provider,
+    backend: &dyn TrieBackend,
+    block_id: BlockId,
+    first_index: u64,
+    second_index: u64,
+    options: &ProofOptions,
+) -> Result<(MerkleProofBundle, MerkleProofBundle)> {
+    println!(
+        "Generating Merkle proofs for same-sender transactions at indices {} and {} in block {}",
+        first_index, second_index, block_id
+    );
+    if block_id.is_pending() {
+        warn_pending_block();
+    }
+
+    let block = fetch_full_block(provider, block_id).await?;
+    check_fetched_block(provider, block_id, &block, options).await?;
+    let BlockTransactions::Full(complete_transactions) = &block.transactions else {
+        unreachable!("fetch_full_block only returns full transactions");
+    };
+
+    build_sender_ordering_proofs(
+        backend,
+        &block.header.clone().into(),
+        complete_transactions,
+        first_index,
+        second_index,
+        options,
+    )
+}
+
+/// Generate receipt proofs for the transaction at `tx_index` of the block with `block_header`,
+/// checking the derived gas used against the value the RPC reports for that receipt.
+pub async fn generate_gas_used_proofs(
+    provider: &impl Provider,
+    chain_id: &CachedChainId,
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    tx_index: u64,
+) -> Result<GasUsedInput> {
+    // Fetch by hash so the receipts belong to exactly this header
+    let block_hash = block_header.hash_slow();
+    let receipts = provider
+        .get_block_receipts(BlockId::Hash(block_hash.into()))
+        .await?
+        .ok_or_else(|| eyre::eyre!("Receipts not found for block {:?}", block_hash))?;
+    let format = ReceiptFormat::at_block(chain_id.get(provider).await?, block_header.number);
+    let encoded_receipts = receipts
+        .iter()
+        .map(|receipt| encode_receipt_for_trie(receipt, format))
+        .collect::<Result<Vec<_>>>()?;
+
+    let gas_used_input = build_gas_used_proofs(backend, block_header, &encoded_receipts, tx_index)?;
+    let gas_used = receipt_gas_used(
+        &gas_used_input.receipt.raw_receipt,
+        gas_used_input
+            .previous_receipt
+            .as_ref()
+            .map(|previous| previous.raw_receipt.as_ref()),
+    )?;
+    let reported_gas_used = receipts[tx_index as usize].gas_used;
+    if gas_used != reported_gas_used {
+        return Err(eyre::eyre!(
+            "Gas used {} derived from cumulative receipt gas differs from the RPC-reported {}",
+            gas_used,
+            reported_gas_used
+        ));
+    }
+
+    Ok(gas_used_input)
+}
+
+/// Resolve `tag` to the block it names now and return that block's header, so proofs are
+/// generated against one concrete block rather than a moving tag.
+pub async fn resolve_block_tag(provider: &impl Provider, tag: BlockTag) -> Result<Header> {
+    if !tag.is_finalized() {
+        println!(
+            "⚠️  WARNING: a {} block can still be reorged; only finalized proofs are safe for on-chain slashing",
+            tag.as_str()
+        );
+    }
+
+    let block = provider
+        .get_block(BlockId::Number(tag.into()))
+        .await?
+        .ok_or_else(|| eyre::eyre!("No {} block returned", tag.as_str()))?;
+    let header: Header = block.header.into();
+    println!(
+        "Resolved {} to block {} ({:?})",
+        tag.as_str(),
+        header.number,
+        header.hash_slow()
+    );
+    Ok(header)
+}
+
+/// Generate the proof for the transaction at `tx_index` of the direct child of the block with
+/// `block_header`, checking that the child links to it.
+pub async fn generate_cross_block_proof(
+    provider: &impl Provider,
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<CrossBlockInput> {
+    let next_block_number = block_header
+        .number
+        .checked_add(1)
+        .ok_or_else(|| eyre::eyre!("Block {} has no successor", block_header.number))?;
+    let bundle = generate_merkle_proof_at(
+        provider,
+        backend,
+        BlockId::Number(next_block_number.into()),
+        tx_index,
+        options,
+    )
+    .await?;
+    bundle.check_index(tx_index)?;
+    verify_parent_linkage(block_header, &bundle.block_header)?;
+
+    Ok(CrossBlockInput {
+        next_block_header: bundle.block_header.clone(),
+        transaction: bundle.into(),
+    })
+}
+
+/// Fetch the header of the direct child of the block with `block_header`, checking that it links
+/// back, for proving inclusion in a block named by its child.
+pub async fn fetch_child_header(provider: &impl Provider, block_header: &Header) -> Result<Header> {
+    let child_number = block_header
+        .number
+        .checked_add(1)
+        .ok_or_else(|| eyre::eyre!("Block {} has no successor", block_header.number))?;
+    let block = provider
+        .get_block(BlockId::Number(child_number.into()))
+        .await?
+        .ok_or_else(|| eyre::eyre!("Block {} has no child yet", block_header.number))?;
+    let child_header: Header = block.header.into();
+    verify_parent_linkage(block_header, &child_header)?;
+    Ok(child_header)
+}
+
+/// Fetch the canonical block at the input's committed number and require its transactions root to
+/// match the input's, so an input pairing one block's number with another's root fails before
+/// proving rather than in the guest.
+pub async fn check_input_transactions_root(
+    provider: &impl Provider,
+    input: &TransactionInclusionInput,
+) -> Result<()> {
+    let block_number = input.block_number();
+    let block = provider
+        .get_block(BlockId::Number(block_number.into()))
+        .await?
+        .ok_or_else(|| eyre::eyre!("Block not found: {}", block_number))?;
+    input.check_canonical_transactions_root(block.header.transactions_root)?;
+    println!(
+        "Transactions root of the input matches canonical block {}",
+        block_number
+    );
+    Ok(())
+}
+
+/// Generate the proofs fixing the transaction count of the block with `block_header`. With
+/// `options.transaction_filter`, the count is of the transactions the filter keeps, as in the trie.
+pub async fn generate_transaction_count_proofs(
+    provider: &impl Provider,
+    backend: &dyn TrieBackend,
+    block_header: &Header,
+    options: &ProofOptions,
+) -> Result<TransactionCountInput> {
+    // Fetch by hash so the transactions belong to exactly this header
+    let block = fetch_full_block(provider, BlockId::Hash(block_header.hash_slow().into())).await?;
+    let BlockTransactions::Full(complete_transactions) = &block.transactions else {
+        unreachable!("fetch_full_block only returns full transactions");
+    };
+    let encoded_transactions = encode_trie_transactions(complete_transactions, options)?;
+
+    build_transaction_count_proofs(backend, block_header, &encoded_transactions)
+}
+
+/// Assemble the guest input proving that the transaction at `tx_index` of block `block_number` is
+/// included there, with the sender witness against the parent state. The block is read through
+/// `blocks`, so targets in the same block fetch its full transactions once.
+pub async fn inclusion_input_at(
+    provider: &impl Provider,
+    blocks: &BlockCache,
+    block_number: u64,
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<TransactionInclusionInput> {
+    println!(
+        "Generating Merkle proof for transaction at precise index {} in block {}",
+        tx_index, block_number
+    );
+    let block = blocks.get(provider, block_number).await?;
+    check_fetched_block(
+        provider,
+        BlockId::Number(block_number.into()),
+        &block,
+        options,
+    )
+    .await?;
+    let BlockTransactions::Full(complete_transactions) = &block.transactions else {
+        unreachable!("fetch_full_block only returns full transactions");
+    };
+    let bundle = build_merkle_proof(
+        &KeccakMptBackend,
+        &block.header.clone().into(),
+        complete_transactions,
+        tx_index,
+        options,
+    )?;
+    bundle.check_index(tx_index)?;
+    let sender_witness =
+        generate_sender_account_witness(provider, block_number, &bundle.raw_transaction).await?;
+
+    Ok(TransactionInclusionInput {
+        block_header: bundle.block_header,
+        parent_block_header: sender_witness.parent_block_header,
+        committed_raw_transaction: bundle.raw_transaction.clone(),
+        sender_account: sender_witness.account,
+        sender_account_proof: sender_witness.proof,
+        raw_transaction: bundle.raw_transaction,
+        transaction_index: tx_index,
+        merkle_proof: bundle.merkle_proof,
+        prove_absence: false,
+        trie_backend: TrieBackendKind::default(),
+        expected_block_hash: None,
+        adjacent_transaction: None,
+        sender_ordering: None,
+        claimed_root: None,
+        gas_used: None,
+        transaction_count: None,
+        cross_block: None,
+        partial_header: None,
+        decode_mode: DecodeMode::default(),
+        slot: None,
+        proved_header: ProvedHeader::default(),
+        access_list_entry: None,
+    })
+}
+
+/// Concurrency limit used when a command does not set one.
+pub const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// Bounds how many RPC-heavy operations run at once.
+///
+/// Share one limit across every concurrent helper a command calls, so the bound holds for the
+/// command as a whole rather than per helper.
+#[derive(Debug)]
+pub struct ConcurrencyLimit(tokio::sync::Semaphore);
+
+impl ConcurrencyLimit {
+    pub fn new(max_concurrency: usize) -> Result<Self> {
+        if max_concurrency == 0 {
+            return Err(eyre::eyre!("max_concurrency must be at least 1"));
+        }
+        Ok(Self(tokio::sync::Semaphore::new(max_concurrency)))
+    }
+
+    /// Run `operation` once a permit is free, holding the permit until it completes.
+    pub async fn run<T>(
+        &self,
+        operation: impl std::future::Future<Output = Result<T>>,
+    ) -> Result<T> {
+        let _permit = self
+            .0
+            .acquire()
+            .await
+            .map_err(|e| eyre::eyre!("Semaphore closed: {}", e))?;
+        operation.await
+    }
+}
+
+impl Default for ConcurrencyLimit {
+    fn default() -> Self {
+        Self(tokio::sync::Semaphore::new(DEFAULT_MAX_CONCURRENCY))
+    }
+}
+
+/// Assemble [`inclusion_input_at`] inputs for independent `(block_number, tx_index)` targets, with
+/// fetches bounded by `limit` and blocks shared through `blocks`. Inputs are returned in target
+/// order.
+pub async fn inclusion_inputs_concurrently(
+    provider: &impl Provider,
+    targets: Vec<(u64, u64)>,
+    blocks: &BlockCache,
+    limit: &ConcurrencyLimit,
+    options: &ProofOptions,
+) -> Result<Vec<TransactionInclusionInput>> {
+    futures::future::try_join_all(targets.into_iter().map(|(block_number, tx_index)| {
+        limit.run(inclusion_input_at(
+            provider,
+            blocks,
+            block_number,
+            tx_index,
+            options,
+        ))
+    }))
+    .await
+}
+
+/// [`inclusion_inputs_concurrently`] for mined transactions named by hash. Each is first resolved to
+/// its verified position, under the same `limit`. Inputs are returned in hash order.
+pub async fn inclusion_inputs_for_transactions(
+    provider: &impl Provider,
+    transaction_hashes: &[B256],
+    blocks: &BlockCache,
+    limit: &ConcurrencyLimit,
+    options: &ProofOptions,
+) -> Result<Vec<TransactionInclusionInput>> {
+    let targets =
+        futures::future::try_join_all(transaction_hashes.iter().map(|&transaction_hash| {
+            limit.run(async move {
+                let tx = provider
+                    .get_transaction_by_hash(transaction_hash)
+                    .await?
+                    .ok_or_else(|| eyre::eyre!("Transaction not found: {:?}", transaction_hash))?;
+                verified_transaction_position(provider, &tx).await
+            })
+        }))
+        .await?;
+    inclusion_inputs_concurrently(provider, targets, blocks, limit, options).await
+}
+
+/// A provider's chain id, fetched on first use and reused afterwards.
+///
+/// Keep one next to each provider; it does not notice if the provider is pointed at another chain.
+#[derive(Debug, Default)]
+pub struct CachedChainId(tokio::sync::OnceCell<u64>);
+
+impl CachedChainId {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn get(&self, provider: &impl Provider) -> Result<u64> {
+        self.0
+            .get_or_try_init(|| async { Ok::<_, eyre::Report>(provider.get_chain_id().await?) })
+            .await
+            .copied()
+    }
+}
+
+/// Full blocks keyed by number and hash, so an entry for a block that was reorged out is never
+/// served in place of the canonical one.
+///
+/// Every lookup still fetches the header at the number to learn the canonical hash; that round trip
+/// is the price of not trusting a stale entry, and is much cheaper than refetching full transactions.
+/// Concurrent lookups of the same block wait on a single in-flight fetch rather than each making one.
+#[derive(Debug, Default)]
+pub struct BlockCache(std::sync::Mutex<BlockCacheEntries>);
+
+type BlockCacheEntries =
+    std::collections::HashMap<(u64, B256), std::sync::Arc<tokio::sync::OnceCell<Block>>>;
+
+impl BlockCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The canonical full block at `block_number`, from the cache when its hash is still canonical.
+    pub async fn get(&self, provider: &impl Provider, block_number: u64) -> Result<Block> {
+        let block_hash = provider
+            .get_block_by_number(block_number.into())
+            .await?
+            .ok_or_else(|| eyre::eyre!("Block not found: {}", block_number))?
+            .header
+            .hash;
+        let entry = {
+            let mut blocks = self.lock();
+            if !blocks.contains_key(&(block_number, block_hash)) {
+                // Whatever else is cached at this number was reorged out
+                blocks.retain(|(number, _), _| *number != block_number);
+            }
+            blocks
+                .entry((block_number, block_hash))
+                .or_default()
+                .clone()
+        };
+
+        // A failed fetch leaves the entry empty, so the next lookup retries it
+        let block = entry
+            .get_or_try_init(|| fetch_full_block(provider, BlockId::Hash(block_hash.into())))
+            .await?;
+        Ok(block.clone())
+    }
+
+    /// How many blocks have been fetched and are cached.
+    pub fn len(&self) -> usize {
+        self.lock()
+            .values()
+            .filter(|entry| entry.initialized())
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BlockCacheEntries> {
+        self.0
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// How many scanned blocks the monitor remembers to detect reorgs; deeper reorgs go unnoticed.
+pub const MONITOR_REORG_DEPTH: usize = 64;
+
+/// What [`monitor_inclusion`] looks for in each scanned block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorTarget {
+    /// The transaction with this hash.
+    Transaction(B256),
+    /// Any transaction sent by this address.
+    Sender(Address),
+}
+
+impl MonitorTarget {
+    fn matches(&self, tx: &alloy_rpc_types::Transaction) -> bool {
+        match self {
+            MonitorTarget::Transaction(hash) => tx.inner.hash() == hash,
+            MonitorTarget::Sender(sender) => tx.inner.signer() == *sender,
+        }
+    }
+}
+
+/// The blocks [`monitor_inclusion`] scans and how often it polls for new ones.
+#[derive(Debug, Clone)]
+pub struct MonitorOptions {
+    /// First block to scan; `None` starts at the current `latest`.
+    pub since_block: Option<u64>,
+    /// Last block to scan before giving up; `None` scans until the target is found.
+    pub until_block: Option<u64>,
+    pub poll_interval: std::time::Duration,
+}
+
+/// Where the monitor found the target, or the last block it scanned when the deadline passed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorOutcome {
+    Included {
+        block_number: u64,
+        block_hash: B256,
+        transaction_index: u64,
+    },
+    NotIncluded {
+        block_number: u64,
+        block_hash: B256,
+    },
+}
+
+/// Scan blocks as they arrive, polling `latest`, until `target` is included or `until_block` passes.
+///
+/// Only the hashes of the last [`MONITOR_REORG_DEPTH`] scanned blocks are kept. A block whose
+/// parent hash does not match the block scanned before it is evidence of a reorg, and the monitor
+/// steps back to re-check the replaced blocks on the new chain.
+pub async fn monitor_inclusion(
+    provider: &impl Provider,
+    target: MonitorTarget,
+    options: &MonitorOptions,
+) -> Result<MonitorOutcome> {
+    let mut latest = provider.get_block_number().await?;
+    let mut next = options.since_block.unwrap_or(latest);
+    if let Some(until_block) = options.until_block {
+        if until_block < next {
+            return Err(eyre::eyre!(
+                "--until-block {} is before the first block to scan, {}",
+                until_block,
+                next
+            ));
+        }
+    }
+    println!("Monitoring for {:?} from block {}", target, next);
+
+    let mut scanned: std::collections::VecDeque<(u64, B256)> = Default::default();
+    loop {
+        if next > latest {
+            tokio::time::sleep(options.poll_interval).await;
+            latest = provider.get_block_number().await?;
+            continue;
+        }
+
+        let block = fetch_full_block(provider, BlockId::Number(next.into())).await?;
+        if let Some(&(previous_number, previous_hash)) = scanned.back() {
+            if previous_number + 1 == next && block.header.parent_hash != previous_hash {
+                println!(
+                    "Reorg detected at block {}; re-checking block {}",
+                    next, previous_number
+                );
+                scanned.pop_back();
+                next = previous_number;
+                continue;
+            }
+        }
+
+        let BlockTransactions::Full(transactions) = &block.transactions else {
+            unreachable!("fetch_full_block only returns full transactions");
+        };
+        if let Some(position) = transactions.iter().position(|tx| target.matches(tx)) {
+            println!("Found {:?} in block {} at index {}", target, next, position);
+            return Ok(MonitorOutcome::Included {
+                block_number: next,
+                block_hash: block.header.hash,
+                transaction_index: position as u64,
+            });
+        }
+
+        if options.until_block == Some(next) {
+            println!("{:?} not included by block {}", target, next);
+            return Ok(MonitorOutcome::NotIncluded {
+                block_number: next,
+                block_hash: block.header.hash,
+            });
+        }
+        scanned.push_back((next, block.header.hash));
+        if scanned.len() > MONITOR_REORG_DEPTH {
+            scanned.pop_front();
+        }
+        next += 1;
+    }
+}
+
+/// One row of [`list_transaction_indices`]: a transaction hash and the index it is proved at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionIndexEntry {
+    pub index: u64,
+    pub transaction_hash: B256,
+}
+
+/// Every transaction in the block with the index a proof uses for it, without proving anything.
+///
+/// With a `filter`, indices count only the transactions the filter keeps, as in the trie.
+pub async fn list_transaction_indices(
+    provider: &impl Provider,
+    block_id: BlockId,
+    filter: Option<TransactionFilter>,
+) -> Result<Vec<TransactionIndexEntry>> {
+    let block = fetch_full_block(provider, block_id).await?;
+    let transactions = block
+        .transactions
+        .as_transactions()
+        .expect("fetch_full_block returns full transactions");
+    Ok(transactions
+        .iter()
+        .filter(|tx| filter.is_none_or(|keep| keep(tx)))
+        .enumerate()
+        .map(|(index, tx)| TransactionIndexEntry {
+            index: index as u64,
+            transaction_hash: *tx.inner.hash(),
+        })
+        .collect())
+}
+
+/// Look up each of `hashes` in a block's index table, failing on the first hash not in the block.
+pub fn resolve_transaction_indices(
+    entries: &[TransactionIndexEntry],
+    hashes: &[B256],
+) -> Result<Vec<TransactionIndexEntry>> {
+    hashes
+        .iter()
+        .map(|&hash| {
+            entries
+                .iter()
+                .find(|entry| entry.transaction_hash == hash)
+                .copied()
+                .ok_or_else(|| eyre::eyre!("Transaction {:?} is not in the block", hash))
+        })
+        .collect()
+}
+
+/// A transaction found in two blocks' index tables at different indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderingDifference {
+    pub transaction_hash: B256,
+    pub index_a: u64,
+    pub index_b: u64,
+}
+
+/// Transactions present in both `a` and `b` at different indices, in `a`'s order: candidates for a
+/// misplacement proof, e.g. of a proposer's block against a relay's expected ordering.
+///
+/// Transactions in only one of the two are not reported.
+pub fn ordering_differences(
+    a: &[TransactionIndexEntry],
+    b: &[TransactionIndexEntry],
+) -> Vec<OrderingDifference> {
+    let indices_b: std::collections::HashMap<B256, u64> = b
+        .iter()
+        .map(|entry| (entry.transaction_hash, entry.index))
+        .collect();
+    a.iter()
+        .filter_map(|entry| {
+            let &index_b = indices_b.get(&entry.transaction_hash)?;
+            (index_b != entry.index).then_some(OrderingDifference {
+                transaction_hash: entry.transaction_hash,
+                index_a: entry.index,
+                index_b,
+            })
+        })
+        .collect()
+}
+
+/// The transaction that emitted the log `selector` picks out, for proving by hash.
+///
+/// Several logs from the same transaction count as one match; logs from more than one
+/// transaction, or none at all, are an error.
+pub async fn transaction_hash_from_log(
+    provider: &impl Provider,
+    selector: &LogSelector,
+) -> Result<B256> {
+    let filter = alloy_rpc_types::Filter::new()
+        .address(selector.address)
+        .event_signature(selector.topic0)
+        .from_block(selector.from_block)
+        .to_block(selector.to_block);
+    let logs = provider.get_logs(&filter).await?;
+
+    let mut transaction_hashes = Vec::new();
+    for log in &logs {
+        let transaction_hash = log
+            .transaction_hash
+            .ok_or_else(|| eyre::eyre!("Log has no transaction hash; is it pending?"))?;
+        if !transaction_hashes.contains(&transaction_hash) {
+            transaction_hashes.push(transaction_hash);
+        }
+    }
+
+    match transaction_hashes[..] {
+        [transaction_hash] => {
+            println!(
+                "Log selector matched transaction {:?} ({} logs)",
+                transaction_hash,
+                logs.len()
+            );
+            Ok(transaction_hash)
+        }
+        [] => Err(eyre::eyre!(
+            "No log from {} with topic0 {:?} in blocks {}-{}",
+            selector.address,
+            selector.topic0,
+            selector.from_block,
+            selector.to_block
+        )),
+        _ => Err(eyre::eyre!(
+            "Logs from {} with topic0 {:?} in blocks {}-{} match {} transactions: {:?}; narrow the block range",
+            selector.address,
+            selector.topic0,
+            selector.from_block,
+            selector.to_block,
+            transaction_hashes.len(),
+            transaction_hashes
+        )),
+    }
+}
+
+/// Hash of `from`'s transaction with `nonce`, looked up in the node's sender-and-nonce index.
+///
+/// `eth_getTransactionBySenderAndNonce` is a Reth and Erigon extension. Against other nodes, search
+/// a known block with [`crate::transaction_index_by_sender_nonce`] instead.
+pub async fn transaction_hash_by_sender_nonce(
+    provider: &impl Provider,
+    from: Address,
+    nonce: u64,
+) -> Result<B256> {
+    let tx: Option<alloy_rpc_types::Transaction> = provider
+        .raw_request(
+            "eth_getTransactionBySenderAndNonce".into(),
+            (from, alloy_primitives::U64::from(nonce)),
+        )
+        .await
+        .map_err(|e| {
+            eyre::eyre!(
+                "eth_getTransactionBySenderAndNonce failed: {}; name the block with --block-hash or --block-tag to search it instead",
+                e
+            )
+        })?;
+    let tx = tx.ok_or_else(|| eyre::eyre!("No transaction from {} with nonce {}", from, nonce))?;
+    println!(
+        "Sender {} nonce {} is transaction {:?}",
+        from,
+        nonce,
+        tx.inner.hash()
+    );
+    Ok(*tx.inner.hash())
+}
+
+/// Block number and index of a mined transaction, with the index checked against the full block it
+/// was mined in; see [`reconcile_transaction_index`].
+pub async fn verified_transaction_position(
+    provider: &impl Provider,
+    tx: &alloy_rpc_types::Transaction,
+) -> Result<(u64, u64)> {
+    let (block_number, reported_index) = crate::mined_transaction_position(tx)?;
+    let block_id = match tx.block_hash {
+        Some(block_hash) => BlockId::Hash(block_hash.into()),
+        None => BlockId::Number(block_number.into()),
+    };
+    let block = fetch_full_block(provider, block_id).await?;
+    let tx_index = reconcile_transaction_index(&block, *tx.inner.hash(), reported_index)?;
+    Ok((block_number, tx_index))
+}
+
+/// Check `block` serializes to at most `max_block_bytes`, so a resource-constrained prover stops
+/// before building the trie of a block full of large blob transactions.
+pub fn check_block_size(block: &Block, max_block_bytes: Option<usize>) -> Result<()> {
+    let Some(max_block_bytes) = max_block_bytes else {
+        return Ok(());
+    };
+    let block_bytes = serde_json::to_vec(block)?.len();
+    if block_bytes > max_block_bytes {
+        return Err(eyre::eyre!(
+            "Block {} is {} bytes serialized, over --max-block-bytes {}; raise the limit or prove on a machine with more memory",
+            block.header.number,
+            block_bytes,
+            max_block_bytes
+        ));
+    }
+    Ok(())
+}
+
+/// Confirmations required by default before a proof is submitted to the prover network, deep
+/// enough that the block will not reorg out before the paid proof is done.
+pub const NETWORK_MIN_CONFIRMATIONS: u64 = 64;
+
+/// Confirmations of `block_number` when `latest` is the chain head, counting the head itself as
+/// one. A block past the head has none.
+pub fn confirmations(latest: u64, block_number: u64) -> u64 {
+    latest
+        .checked_sub(block_number)
+        .map_or(0, |depth| depth + 1)
+}
+
+/// Check `block_number` has at least `min_confirmations` against the current `latest` block,
+/// returning its confirmations.
+pub async fn check_confirmations(
+    provider: &impl Provider,
+    block_number: u64,
+    min_confirmations: u64,
+) -> Result<u64> {
+    let latest = provider.get_block_number().await?;
+    let depth = confirmations(latest, block_number);
+    println!(
+        "Block {} has {} confirmations (latest block {})",
+        block_number, depth, latest
+    );
+    if depth < min_confirmations {
+        return Err(eyre::eyre!(
+            "Block {} has {} confirmations, fewer than --min-confirmations {}; wait for it to settle so the proof is not made against a block that can still reorg",
+            block_number,
+            depth,
+            min_confirmations
+        ));
+    }
+    Ok(depth)
+}
+
+/// Fetch only the header at `block_number` with `eth_getHeaderByNumber`, a far smaller response than
+/// the block. Geth and Erigon serve it; Reth may not.
+pub async fn fetch_header_by_number(
+    provider: &impl Provider,
+    block_number: u64,
+) -> Result<alloy_rpc_types::Header> {
+    let header: Option<alloy_rpc_types::Header> = provider
+        .raw_request(
+            "eth_getHeaderByNumber".into(),
+            (alloy_eips::BlockNumberOrTag::Number(block_number),),
+        )
+        .await
+        .map_err(|e| eyre::eyre!("eth_getHeaderByNumber failed: {}", e))?;
+    header.ok_or_else(|| eyre::eyre!("Header not found: {}", block_number))
+}
+
+/// Check a fetched full block's header against a header-only fetch of the same number, so an RPC
+/// serving inconsistent data fails before proving. Both headers are hashed here rather than trusting
+/// the hashes the node reports.
+pub async fn cross_check_block_header(provider: &impl Provider, block: &Block) -> Result<()> {
+    let block_number = block.header.number;
+    let header_only = fetch_header_by_number(provider, block_number).await?;
+    let header_only_hash = header_only.inner.hash_slow();
+    let full_block_hash = block.header.inner.hash_slow();
+    if header_only_hash != full_block_hash {
+        return Err(eyre::eyre!(
+            "RPC is inconsistent: the header-only fetch of block {} hashes to {:?} but the full block's header hashes to {:?}",
+            block_number,
+            header_only_hash,
+            full_block_hash
+        ));
+    }
+    println!(
+        "Header-only fetch of block {} matches the full block ({:?})",
+        block_number, full_block_hash
+    );
+    Ok(())
+}
+
+/// Compare each transaction's re-encoding, which its trie leaf is built from, against the raw bytes
+/// the node serves from `eth_getRawTransactionByHash`, so an encoding divergence is pinned to one
+/// transaction instead of surfacing as a root mismatch. Nodes that do not serve the method, or no
+/// longer index the transaction, end the check with a warning. Returns how many were compared.
+pub async fn check_raw_transactions(provider: &impl Provider, block: &Block) -> Result<usize> {
+    let transactions = block
+        .transactions
+        .as_transactions()
+        .ok_or_else(|| eyre::eyre!("Expected full transactions but got hashes"))?;
+    for (compared, tx) in transactions.iter().enumerate() {
+        let tx_hash = *tx.inner.tx_hash();
+        let raw = match provider.get_raw_transaction_by_hash(tx_hash).await {
+            Ok(Some(raw)) => raw,
+            Ok(None) => {
+                println!(
+                    "⚠️  WARNING: the node has no raw transaction for {:?}; compared {} of {} re-encodings",
+                    tx_hash,
+                    compared,
+                    transactions.len()
+                );
+                return Ok(compared);
+            }
+            Err(e) => {
+                println!(
+                    "⚠️  WARNING: eth_getRawTransactionByHash failed ({}); compared {} of {} re-encodings",
+                    e,
+                    compared,
+                    transactions.len()
+                );
+                return Ok(compared);
+            }
+        };
+        check_reencoded_transaction(tx_hash, &encode_transaction_for_trie(tx)?, &raw)?;
+    }
+    println!(
+        "✅ All {} re-encoded transactions match the node's raw bytes",
+        transactions.len()
+    );
+    Ok(transactions.len())
+}
+
+/// The checks every full block fetched for a proof goes through: its size, then with
+/// `options.cross_check_header` its header against a header-only fetch, and with
+/// `options.check_raw_transactions` its transactions against their raw bytes. Pending blocks are
+/// never cross-checked, as two fetches need not see the same one.
+async fn check_fetched_block(
+    provider: &impl Provider,
+    block_id: BlockId,
+    block: &Block,
+    options: &ProofOptions,
+) -> Result<()> {
+    check_block_size(block, options.max_block_bytes)?;
+    if options.cross_check_header && !block_id.is_pending() {
+        cross_check_block_header(provider, block).await?;
+    }
+    if options.check_raw_transactions {
+        check_raw_transactions(provider, block).await?;
+    }
+    Ok(())
+}
+
+/// Fetch a block with FULL transaction details.
+pub async fn fetch_full_block(provider: &impl Provider, block_id: BlockId) -> Result<Block> {
+    let mut block = provider
+        .get_block(block_id)
+        .full()
+        .await?
+        .ok_or_else(|| eyre::eyre!("Block not found: {}", block_id))?;
+
+    // Extract full transactions from block (we requested .full() so should always get Full variant)
+    match &mut block.transactions {
+        BlockTransactions::Full(txs) => {
+            println!("Got {} full transactions from block", txs.len());
+            order_transactions_by_index(txs)?;
+        }
+        BlockTransactions::Hashes(_) => {
+            return Err(eyre::eyre!(
+                "Expected full transactions but got hashes - ensure .full() is used"
+            ));
+        }
+        _ => {
+            return Err(eyre::eyre!("Unexpected transaction format"));
+        }
+    }
+
+    // A block requested by hash must really be that block, e.g. a finalized hash from a consensus client
+    if let BlockId::Hash(block_hash) = block_id {
+        verify_block_header_hash(&block.header.clone().into(), block_hash.block_hash)?;
+    }
+
+    Ok(block)
+}
+
+/// Print the warning shown whenever a pending block is used.
+pub fn warn_pending_block() {
+    println!("⚠️  WARNING: using the PENDING block.");
+    println!("⚠️  Pending state is not canonical and can change or disappear at any time.");
+    println!("⚠️  Proofs against a pending block are for monitoring only and MUST NOT be used on-chain or for slashing.");
+}
+
+/// Generate the parent-block account witness needed to prove a signed transaction was still
+/// includable at the start of `block_number`.
+pub async fn generate_sender_account_witness(
+    provider: &impl Provider,
+    block_number: u64,
+    raw_transaction: &Bytes,
+) -> Result<SenderAccountWitness> {
+    if block_number == 0 {
+        return Err(eyre::eyre!("Block 0 has no parent state to prove against"));
+    }
+
+    let tx = TxEnvelope::decode_2718_exact(raw_transaction.as_ref())
+        .map_err(|e| eyre::eyre!("Failed to decode committed transaction: {:?}", e))?;
+    let sender = tx
+        .recover_signer()
+        .map_err(|e| eyre::eyre!("Failed to recover committed transaction signer: {:?}", e))?;
+
+    let parent_block_number = block_number - 1;
+    let parent_block = provider
+        .get_block(BlockId::Number(parent_block_number.into()))
+        .await?
+        .ok_or_else(|| eyre::eyre!("Parent block not found: {}", parent_block_number))?;
+
+    let proof_response = provider
+        .get_proof(sender, Vec::new())
+        .block_id(BlockId::Number(parent_block_number.into()))
+        .await?;
+
+    let account = AccountState {
+        nonce: proof_response.nonce,
+        balance: proof_response.balance,
+        storage_root: proof_response.storage_hash,
+        code_hash: proof_response.code_hash,
+    };
+
+    if account.nonce != tx.nonce() {
+        return Err(eyre::eyre!(
+            "Committed transaction is not includable at block {}: sender nonce is {}, transaction nonce is {}",
+            block_number,
+            account.nonce,
+            tx.nonce()
+        ));
+    }
+
+    let gas_cost = U256::from(tx.gas_limit()) * U256::from(tx.max_fee_per_gas());
+    let blob_cost = match (tx.blob_gas_used(), tx.max_fee_per_blob_gas()) {
+        (Some(blob_gas_used), Some(max_fee_per_blob_gas)) => {
+            U256::from(blob_gas_used) * U256::from(max_fee_per_blob_gas)
+        }
+        _ => U256::ZERO,
+    };
+    let upfront_cost = gas_cost
+        .checked_add(blob_cost)
+        .and_then(|cost| cost.checked_add(tx.value()))
+        .ok_or_else(|| eyre::eyre!("Committed transaction upfront cost overflowed"))?;
+    if account.balance < upfront_cost {
+        return Err(eyre::eyre!(
+            "Committed transaction is not includable at block {}: sender balance {} is below upfront cost {}",
+            block_number,
+            account.balance,
+            upfront_cost
+        ));
+    }
+
+    Ok(SenderAccountWitness {
+        parent_block_header: parent_block.header.into(),
+        sender,
+        account,
+        proof: proof_response.account_proof,
+    })
+}
+
+/// Generate a Merkle Patricia Trie exclusion proof for a transaction index in a block.
+pub async fn generate_merkle_absence_proof(
+    provider: &impl Provider,
+    block_number: u64,
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<Vec<Bytes>> {
+    generate_merkle_absence_proof_with_backend(
+        provider,
+        &KeccakMptBackend,
+        block_number,
+        tx_index,
+        options,
+    )
+    .await
+}
+
+/// Generate an exclusion proof for a transaction index in a block using the given trie backend.
+///
+/// `options` apply as for inclusion proofs: the fetched block is checked, the filtered transactions
+/// make up the trie, and a root mismatch is dumped before it fails the proof.
+pub async fn generate_merkle_absence_proof_with_backend(
+    provider: &impl Provider,
+    backend: &dyn TrieBackend,
+    block_number: u64,
+    tx_index: u64,
+    options: &ProofOptions,
+) -> Result<Vec<Bytes>> {
+    use alloy_primitives::U256;
+    use alloy_rlp::encode as rlp_encode;
+
+    println!(
+        "Generating Merkle absence proof for transaction index {} in block {}",
+        tx_index, block_number
+    );
+
+    let block_id = BlockId::Number(block_number.into());
+    let block = fetch_full_block(provider, block_id).await?;
+    check_fetched_block(provider, block_id, &block, options).await?;
+    let BlockTransactions::Full(complete_transactions) = &block.transactions else {
+        unreachable!("fetch_full_block only returns full transactions");
+    };
+
+    let encoded_transactions = encode_trie_transactions(complete_transactions, options)?;
+    if (tx_index as usize) < encoded_transactions.len() {
+        return Err(eyre::eyre!(
+            "Transaction index {} exists in block {}; cannot generate absence proof",
+            tx_index,
+            block_number
+        ));
+    }
+
+    let target_key = rlp_encode(U256::from(tx_index));
+    let leaves = transaction_trie_leaves(&encoded_transactions);
+    if options.verbose_trie {
+        for line in verbose_trie_log(backend, &leaves) {
+            println!("{}", line);
+        }
+    }
+
+    let (computed_root, proof_bytes) = backend.generate_proof(&leaves, &target_key);
+    let proof_bytes = dedup_proof_nodes(proof_bytes);
+    let block_root = block.header.transactions_root;
+    if computed_root != block_root {
+        if let Some(path) = &options.debug_dump {
+            write_root_mismatch_dump(path, computed_root, block_root, &leaves)?;
+            println!("Wrote root mismatch debug dump to {:?}", path);
+        }
+        return Err(eyre::eyre!(
+            "Computed trie root {:?} does not match block transactions root {:?}",
+            computed_root,
+            block_root
+        ));
+    }
+
+    backend
+        .verify_proof(computed_root, &target_key, None, &proof_bytes)
+        .map_err(|e| eyre::eyre!("Generated absence proof failed validation: {:?}", e))?;
+
+    println!(
+        "Generated absence proof with {} nodes for missing transaction index {}",
+        proof_bytes.len(),
+        tx_index
+    );
+
+    Ok(proof_bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{
+        header_with_transactions, mainnet_genesis_header, mock_provider, rpc_block,
+        rpc_transactions, MAINNET_GENESIS_HASH,
+    };
+
+    #[tokio::test]
+    async fn generate_merkle_proof_handles_single_transaction_block() {
+        use alloy_rlp::encode as rlp_encode;
+
+        let transactions = rpc_transactions(1);
+        let raw_transaction = encode_transaction_for_trie(&transactions[0]).unwrap();
+        let key = rlp_encode(U256::ZERO);
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        header.transactions_root =
+            KeccakMptBackend.build_root(&[(key.clone(), raw_transaction.clone())]);
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(header.clone(), transactions));
+
+        let (proof, encoded) = generate_merkle_proof(&provider, 1, 0)
+            .await
+            .expect("single-transaction proof should build");
+
+        // The root node is the lone leaf, so the proof is exactly that node
+        assert_eq!(proof.len(), 1);
+        assert_eq!(
+            alloy_primitives::keccak256(&proof[0]),
+            header.transactions_root
+        );
+        assert_eq!(encoded, raw_transaction);
+        KeccakMptBackend
+            .verify_proof(header.transactions_root, &key, Some(&encoded), &proof)
+            .expect("proof should verify against the header root");
+        assert!(KeccakMptBackend
+            .verify_proof(
+                header.transactions_root,
+                &rlp_encode(U256::from(1)),
+                None,
+                &proof
+            )
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn generate_merkle_proof_matches_a_recorded_block() {
+        use crate::test_utils::recorded_block;
+        use alloy_rlp::encode as rlp_encode;
+
+        let block = recorded_block();
+        let root = block.header.transactions_root;
+        assert_eq!(
+            block.header.hash,
+            "0x5c51674f3f0c83c89f2458e7e542758f0bd1dd839454341b8a343603e128e9eb"
+                .parse::<B256>()
+                .unwrap()
+        );
+
+        for tx_index in 0..3u64 {
+            let (provider, asserter) = mock_provider();
+            asserter.push_success(&block);
+
+            let (proof, encoded) = generate_merkle_proof(&provider, 1, tx_index)
+                .await
+                .expect("proof should build from the recorded block");
+            let transactions = block.transactions.as_transactions().unwrap();
+            assert_eq!(
+                encoded,
+                encode_transaction_for_trie(&transactions[tx_index as usize]).unwrap()
+            );
+            KeccakMptBackend
+                .verify_proof(
+                    root,
+                    &rlp_encode(U256::from(tx_index)),
+                    Some(&encoded),
+                    &proof,
+                )
+                .expect("proof should verify against the recorded root");
+        }
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&block);
+        assert!(generate_merkle_proof(&provider, 1, 3).await.is_err());
+    }
+
+    /// The transactions of an RLP-encoded block exactly as the block carries them: legacy list items
+    /// whole, typed transactions as the payload of their RLP string.
+    fn raw_transactions_in(block_rlp: &[u8]) -> Vec<Bytes> {
+        use alloy_rlp::Header as RlpHeader;
+
+        let mut block = block_rlp;
+        RlpHeader::decode(&mut block).unwrap();
+        let header = RlpHeader::decode(&mut block).unwrap();
+        block = &block[header.payload_length..];
+        let list = RlpHeader::decode(&mut block).unwrap();
+        let mut transactions = &block[..list.payload_length];
+
+        let mut raw = Vec::new();
+        while !transactions.is_empty() {
+            let item_start = transactions;
+            let item = RlpHeader::decode(&mut transactions).unwrap();
+            raw.push(Bytes::copy_from_slice(if item.list {
+                &item_start[..item_start.len() - transactions.len() + item.payload_length]
+            } else {
+                &transactions[..item.payload_length]
+            }));
+            transactions = &transactions[item.payload_length..];
+        }
+        raw
+    }
+
+    #[tokio::test]
+    async fn reencoded_transactions_are_compared_with_the_node_raw_bytes() {
+        use crate::test_utils::recorded_block;
+
+        // The RPC block's re-encodings match the bytes the RLP block carries
+        let block = recorded_block();
+        let raw = raw_transactions_in(include_bytes!("fixtures/block-3-transactions.rlp"));
+        assert_eq!(raw.len(), 3);
+        let (provider, asserter) = mock_provider();
+        for raw in &raw {
+            asserter.push_success(raw);
+        }
+        assert_eq!(check_raw_transactions(&provider, &block).await.unwrap(), 3);
+
+        let mut diverging = raw[1].to_vec();
+        *diverging.last_mut().unwrap() ^= 1;
+        asserter.push_success(&raw[0]);
+        asserter.push_success(&Bytes::from(diverging));
+        let error = check_raw_transactions(&provider, &block)
+            .await
+            .unwrap_err()
+            .to_string();
+        let transactions = block.transactions.as_transactions().unwrap();
+        assert!(error.contains(&format!("{:?}", transactions[1].inner.tx_hash())));
+        assert!(error.contains(&format!("at byte {}", raw[1].len() - 1)));
+
+        // Nodes without the method skip the check instead of failing the proof
+        asserter.push_failure_msg("the method eth_getRawTransactionByHash does not exist");
+        assert_eq!(check_raw_transactions(&provider, &block).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn shallow_blocks_fail_the_confirmation_check() {
+        assert_eq!(confirmations(100, 100), 1);
+        assert_eq!(confirmations(100, 37), 64);
+        assert_eq!(confirmations(100, 101), 0);
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&U256::from(100));
+        assert_eq!(
+            check_confirmations(&provider, 37, NETWORK_MIN_CONFIRMATIONS)
+                .await
+                .unwrap(),
+            64
+        );
+
+        asserter.push_success(&U256::from(100));
+        let shallow = check_confirmations(&provider, 38, NETWORK_MIN_CONFIRMATIONS)
+            .await
+            .unwrap_err();
+        assert!(shallow.to_string().contains("63 confirmations"));
+    }
+
+    #[tokio::test]
+    async fn fetch_block_header_by_hash_returns_header_for_known_hash() {
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(mainnet_genesis_header(), Vec::new()));
+
+        let header = fetch_block_header_by_hash(&provider, MAINNET_GENESIS_HASH)
+            .await
+            .expect("header should be fetched");
+        assert_eq!(header.number, 0);
+        assert_eq!(header.hash_slow(), MAINNET_GENESIS_HASH);
+    }
+
+    #[tokio::test]
+    async fn fetch_block_header_by_hash_rejects_mismatched_header() {
+        let (provider, asserter) = mock_provider();
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        asserter.push_success(&rpc_block(header, Vec::new()));
+
+        assert!(fetch_block_header_by_hash(&provider, MAINNET_GENESIS_HASH)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn inclusion_inputs_concurrently_preserves_target_order_across_blocks() {
+        use crate::test_utils::signed_rpc_transaction;
+        use alloy_rpc_types::EIP1186AccountProofResponse;
+
+        let signer = alloy_signer_local::PrivateKeySigner::random();
+        let block = |number: u64, parent: &Header, nonces: &[u64]| {
+            let transactions: Vec<_> = nonces
+                .iter()
+                .map(|&nonce| signed_rpc_transaction(&signer, nonce))
+                .collect();
+            let mut header = parent.clone();
+            header.number = number;
+            header.parent_hash = parent.hash_slow();
+            let (header, _, _) = header_with_transactions(header, &transactions);
+            (header, transactions)
+        };
+        let account = |nonce: u64| EIP1186AccountProofResponse {
+            address: signer.address(),
+            balance: U256::from(10).pow(U256::from(18)),
+            code_hash: alloy_primitives::KECCAK256_EMPTY,
+            nonce,
+            storage_hash: alloy_trie::EMPTY_ROOT_HASH,
+            account_proof: Vec::new(),
+            storage_proof: Vec::new(),
+        };
+
+        let genesis = mainnet_genesis_header();
+        let (header_1, transactions_1) = block(1, &genesis, &[0, 1]);
+        let (header_2, transactions_2) = block(2, &header_1, &[2]);
+        let rpc_block_1 = rpc_block(header_1.clone(), transactions_1.clone());
+        let rpc_block_2 = rpc_block(header_2.clone(), transactions_2.clone());
+
+        // A single permit runs the targets one after another, so responses are queued in target order
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block_1);
+        asserter.push_success(&rpc_block_1);
+        asserter.push_success(&rpc_block(genesis.clone(), Vec::new()));
+        asserter.push_success(&account(1));
+        asserter.push_success(&rpc_block_2);
+        asserter.push_success(&rpc_block_2);
+        asserter.push_success(&rpc_block_1);
+        asserter.push_success(&account(2));
+        // Block 1 is cached, so only its header is fetched again
+        asserter.push_success(&rpc_block_1);
+        asserter.push_success(&rpc_block(genesis.clone(), Vec::new()));
+        asserter.push_success(&account(0));
+
+        let blocks = BlockCache::new();
+        let limit = ConcurrencyLimit::new(1).unwrap();
+        let inputs = inclusion_inputs_concurrently(
+            &provider,
+            vec![(1, 1), (2, 0), (1, 0)],
+            &blocks,
+            &limit,
+            &Default::default(),
+        )
+        .await
+        .expect("inputs for both blocks should build");
+
+        assert!(asserter.read_q().is_empty());
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(inputs.len(), 3);
+        assert_eq!(inputs[0].block_header, header_1);
+        assert_eq!(inputs[0].parent_block_header, genesis);
+        assert_eq!(inputs[0].transaction_index, 1);
+        assert_eq!(
+            inputs[0].raw_transaction,
+            encode_transaction_for_trie(&transactions_1[1]).unwrap()
+        );
+        assert_eq!(inputs[0].sender_account.nonce, 1);
+        assert_eq!(inputs[1].block_header, header_2);
+        assert_eq!(inputs[1].parent_block_header, header_1);
+        assert_eq!(inputs[1].transaction_index, 0);
+        assert_eq!(
+            inputs[1].raw_transaction,
+            encode_transaction_for_trie(&transactions_2[0]).unwrap()
+        );
+        assert_eq!(inputs[1].sender_account.nonce, 2);
+        assert_eq!(inputs[2].block_header, header_1);
+        assert_eq!(inputs[2].transaction_index, 0);
+        assert_eq!(inputs[2].sender_account.nonce, 0);
+    }
+
+    #[test]
+    fn concurrency_limit_rejects_zero() {
+        assert!(ConcurrencyLimit::new(0).is_err());
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_is_never_exceeded() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let limit = ConcurrencyLimit::new(3).unwrap();
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        futures::future::try_join_all((0..20).map(|_| {
+            limit.run(async {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // Yield so other operations get the chance to start while this one holds a permit
+                for _ in 0..5 {
+                    tokio::task::yield_now().await;
+                }
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            })
+        }))
+        .await
+        .unwrap();
+
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn transaction_indices_follow_block_order_and_filter() {
+        let transactions = rpc_transactions(3);
+        let hashes: Vec<B256> = transactions.iter().map(|tx| *tx.inner.hash()).collect();
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(header.clone(), transactions.clone()));
+        let entries = list_transaction_indices(&provider, BlockId::number(1), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.index, entry.transaction_hash))
+                .collect::<Vec<_>>(),
+            [(0, hashes[0]), (1, hashes[1]), (2, hashes[2])]
+        );
+
+        let resolved = resolve_transaction_indices(&entries, &[hashes[2], hashes[0]]).unwrap();
+        assert_eq!(
+            resolved.iter().map(|entry| entry.index).collect::<Vec<_>>(),
+            [2, 0]
+        );
+        assert!(resolve_transaction_indices(&entries, &[B256::repeat_byte(9)]).is_err());
+
+        // Indices count only the transactions a chain filter keeps in the trie
+        asserter.push_success(&rpc_block(header, transactions));
+        let filtered = list_transaction_indices(
+            &provider,
+            BlockId::number(1),
+            Some(|tx| tx.inner.nonce() != 0),
+        )
+        .await
+        .unwrap();
+        assert_eq!(filtered[0].transaction_hash, hashes[1]);
+        assert_eq!(filtered[0].index, 0);
+    }
+
+    #[test]
+    fn ordering_differences_report_moved_transactions() {
+        let entries = |hashes: &[u8]| -> Vec<TransactionIndexEntry> {
+            hashes
+                .iter()
+                .enumerate()
+                .map(|(index, &byte)| TransactionIndexEntry {
+                    index: index as u64,
+                    transaction_hash: B256::repeat_byte(byte),
+                })
+                .collect()
+        };
+
+        // 1 and 2 swap places, 3 stays put, 4 and 5 are each in only one block
+        let differences = ordering_differences(&entries(&[1, 2, 3, 4]), &entries(&[2, 1, 3, 5]));
+        assert_eq!(
+            differences,
+            [
+                OrderingDifference {
+                    transaction_hash: B256::repeat_byte(1),
+                    index_a: 0,
+                    index_b: 1,
+                },
+                OrderingDifference {
+                    transaction_hash: B256::repeat_byte(2),
+                    index_a: 1,
+                    index_b: 0,
+                },
+            ]
+        );
+        assert!(ordering_differences(&entries(&[1, 2]), &entries(&[1, 2])).is_empty());
+    }
+
+    #[tokio::test]
+    async fn fetch_full_block_rejects_block_not_matching_requested_hash() {
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(mainnet_genesis_header(), Vec::new()));
+        let block = fetch_full_block(&provider, BlockId::Hash(MAINNET_GENESIS_HASH.into()))
+            .await
+            .expect("block matching the requested hash should be returned");
+        assert_eq!(block.header.number, 0);
+
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        asserter.push_success(&rpc_block(header, Vec::new()));
+        assert!(
+            fetch_full_block(&provider, BlockId::Hash(MAINNET_GENESIS_HASH.into()))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn generate_gas_used_proofs_matches_reported_gas() {
+        use crate::test_utils::rpc_receipts;
+        use crate::MAINNET_BYZANTIUM_BLOCK;
+        use alloy_primitives::U64;
+        use alloy_rlp::encode as rlp_encode;
+
+        let receipts = rpc_receipts(&[21_000, 50_000, 71_000]);
+        let leaves: Vec<_> = receipts
+            .iter()
+            .enumerate()
+            .map(|(i, receipt)| {
+                (
+                    rlp_encode(U256::from(i)),
+                    encode_receipt_for_trie(receipt, ReceiptFormat::Status).unwrap(),
+                )
+            })
+            .collect();
+        let mut header = mainnet_genesis_header();
+        header.number = MAINNET_BYZANTIUM_BLOCK;
+        header.receipts_root = KeccakMptBackend.build_root(&leaves);
+
+        let (provider, asserter) = mock_provider();
+        let chain_id = CachedChainId::new();
+        for tx_index in [0, 2] {
+            asserter.push_success(&receipts);
+            // Only the first proof asks for the chain id
+            if tx_index == 0 {
+                asserter.push_success(&U64::from(1));
+            }
+            let gas_used_input = generate_gas_used_proofs(
+                &provider,
+                &chain_id,
+                &KeccakMptBackend,
+                &header,
+                tx_index,
+            )
+            .await
+            .expect("receipt proofs should build");
+
+            assert_eq!(gas_used_input.receipt.transaction_index, tx_index);
+            assert_eq!(
+                gas_used_input
+                    .previous_receipt
+                    .as_ref()
+                    .map(|previous| previous.transaction_index),
+                tx_index.checked_sub(1)
+            );
+            let gas_used = receipt_gas_used(
+                &gas_used_input.receipt.raw_receipt,
+                gas_used_input
+                    .previous_receipt
+                    .as_ref()
+                    .map(|previous| previous.raw_receipt.as_ref()),
+            )
+            .unwrap();
+            assert_eq!(gas_used, receipts[tx_index as usize].gas_used);
+        }
+
+        // A node reporting a different per-transaction gas used is rejected
+        let mut misreported = receipts.clone();
+        misreported[1].gas_used += 1;
+        asserter.push_success(&misreported);
+        assert!(
+            generate_gas_used_proofs(&provider, &chain_id, &KeccakMptBackend, &header, 1)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn out_of_order_block_transactions_prove_the_reported_index() {
+        use alloy_rlp::encode as rlp_encode;
+
+        let transactions = rpc_transactions(4);
+        let mut block_1 = mainnet_genesis_header();
+        block_1.number = 1;
+        let (header, encoded_transactions, _) = header_with_transactions(block_1, &transactions);
+
+        let mut shuffled = transactions.clone();
+        shuffled.reverse();
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(header, shuffled));
+        let bundle = generate_merkle_proof_at(
+            &provider,
+            &KeccakMptBackend,
+            BlockId::Number(1.into()),
+            1,
+            &Default::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(bundle.raw_transaction, encoded_transactions[1]);
+        KeccakMptBackend
+            .verify_proof(
+                bundle.block_header.transactions_root,
+                &rlp_encode(U256::from(1)),
+                Some(&encoded_transactions[1]),
+                &bundle.merkle_proof,
+            )
+            .expect("proof for the reported index should verify");
+    }
+
+    fn monitor_options(since_block: u64, until_block: u64) -> MonitorOptions {
+        MonitorOptions {
+            since_block: Some(since_block),
+            until_block: Some(until_block),
+            poll_interval: std::time::Duration::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn monitor_stops_at_the_deadline_or_the_target() {
+        let transactions = rpc_transactions(2);
+        let target = MonitorTarget::Transaction(*transactions[1].inner.hash());
+        let mut first = mainnet_genesis_header();
+        first.number = 1;
+        first.parent_hash = MAINNET_GENESIS_HASH;
+        let mut second = first.clone();
+        second.number = 2;
+        second.parent_hash = first.hash_slow();
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&2u64);
+        asserter.push_success(&rpc_block(first.clone(), Vec::new()));
+        asserter.push_success(&rpc_block(second.clone(), vec![transactions[0].clone()]));
+        assert_eq!(
+            monitor_inclusion(&provider, target, &monitor_options(1, 2))
+                .await
+                .unwrap(),
+            MonitorOutcome::NotIncluded {
+                block_number: 2,
+                block_hash: second.hash_slow(),
+            }
+        );
+
+        // The block past the current head is picked up once a later poll reports it
+        let mut third = second.clone();
+        third.number = 3;
+        third.parent_hash = second.hash_slow();
+        asserter.push_success(&2u64);
+        asserter.push_success(&rpc_block(second.clone(), Vec::new()));
+        asserter.push_success(&3u64);
+        asserter.push_success(&rpc_block(third.clone(), transactions.clone()));
+        assert_eq!(
+            monitor_inclusion(&provider, target, &monitor_options(2, 5))
+                .await
+                .unwrap(),
+            MonitorOutcome::Included {
+                block_number: 3,
+                block_hash: third.hash_slow(),
+                transaction_index: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn monitor_rechecks_blocks_replaced_by_a_reorg() {
+        let transactions = rpc_transactions(1);
+        let target = MonitorTarget::Sender(transactions[0].inner.signer());
+        let mut first = mainnet_genesis_header();
+        first.number = 1;
+        first.parent_hash = MAINNET_GENESIS_HASH;
+        let mut replacement = first.clone();
+        replacement.extra_data = Bytes::from_static(b"reorg");
+        let mut second = first.clone();
+        second.number = 2;
+        second.parent_hash = replacement.hash_slow();
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&2u64);
+        asserter.push_success(&rpc_block(first, Vec::new()));
+        asserter.push_success(&rpc_block(second, Vec::new()));
+        asserter.push_success(&rpc_block(replacement.clone(), transactions));
+        assert_eq!(
+            monitor_inclusion(&provider, target, &monitor_options(1, 2))
+                .await
+                .unwrap(),
+            MonitorOutcome::Included {
+                block_number: 1,
+                block_hash: replacement.hash_slow(),
+                transaction_index: 0,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn absence_proofs_apply_the_proof_options() {
+        use alloy_rlp::encode as rlp_encode;
+
+        let transactions = rpc_transactions(4);
+        let kept = transactions[..3].to_vec();
+        let mut block_1 = mainnet_genesis_header();
+        block_1.number = 1;
+        let (header, _, root) = header_with_transactions(block_1, &kept);
+        let filtered = ProofOptions {
+            transaction_filter: Some(|tx| tx.inner.nonce() != 3),
+            ..Default::default()
+        };
+
+        // Index 3 is only absent from the trie once the filter drops the transaction there
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(header.clone(), transactions.clone()));
+        assert!(
+            generate_merkle_absence_proof(&provider, 1, 3, &Default::default())
+                .await
+                .unwrap_err()
+                .to_string()
+                .contains("exists in block 1")
+        );
+        asserter.push_success(&rpc_block(header.clone(), transactions.clone()));
+        let proof = generate_merkle_absence_proof(&provider, 1, 3, &filtered)
+            .await
+            .unwrap();
+        KeccakMptBackend
+            .verify_proof(root, &rlp_encode(U256::from(3)), None, &proof)
+            .expect("filtered absence proof should verify");
+
+        asserter.push_success(&rpc_block(header, transactions));
+        let size_capped = ProofOptions {
+            max_block_bytes: Some(100),
+            ..filtered
+        };
+        assert!(generate_merkle_absence_proof(&provider, 1, 3, &size_capped)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn transaction_count_proofs_count_only_filtered_transactions() {
+        let transactions = rpc_transactions(4);
+        let (header, _, _) = header_with_transactions(mainnet_genesis_header(), &transactions[..3]);
+        let filtered = ProofOptions {
+            transaction_filter: Some(|tx| tx.inner.nonce() != 3),
+            ..Default::default()
+        };
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(header.clone(), transactions.clone()));
+        let count_input =
+            generate_transaction_count_proofs(&provider, &KeccakMptBackend, &header, &filtered)
+                .await
+                .unwrap();
+        assert_eq!(count_input.last_transaction.transaction_index + 1, 3);
+        assert_eq!(
+            count_input.last_transaction.raw_transaction,
+            encode_transaction_for_trie(&transactions[2]).unwrap()
+        );
+
+        // Unfiltered, the fourth transaction is in the trie and the root no longer matches
+        asserter.push_success(&rpc_block(header.clone(), transactions));
+        assert!(generate_transaction_count_proofs(
+            &provider,
+            &KeccakMptBackend,
+            &header,
+            &Default::default()
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn cross_block_proof_requires_the_child_block() {
+        let transactions = rpc_transactions(2);
+        let parent = mainnet_genesis_header();
+        let mut child = parent.clone();
+        child.number = 1;
+        child.parent_hash = MAINNET_GENESIS_HASH;
+        let (child, encoded_transactions, _) = header_with_transactions(child, &transactions);
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&rpc_block(child.clone(), transactions.clone()));
+        let cross_block = generate_cross_block_proof(
+            &provider,
+            &KeccakMptBackend,
+            &parent,
+            1,
+            &Default::default(),
+        )
+        .await
+        .expect("child block proof should build");
+        assert_eq!(cross_block.next_block_header, child);
+        assert_eq!(cross_block.transaction.transaction_index, 1);
+        assert_eq!(
+            cross_block.transaction.raw_transaction,
+            encoded_transactions[1]
+        );
+
+        // A block at the next height that does not link to the parent is rejected
+        let mut reorged = child;
+        reorged.parent_hash = B256::repeat_byte(1);
+        asserter.push_success(&rpc_block(reorged, transactions));
+        assert!(generate_cross_block_proof(
+            &provider,
+            &KeccakMptBackend,
+            &parent,
+            1,
+            &Default::default(),
+        )
+        .await
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn block_tags_resolve_to_a_concrete_block() {
+        let (provider, asserter) = mock_provider();
+        for (tag, number) in [(BlockTag::Safe, 7), (BlockTag::Finalized, 5)] {
+            let mut header = mainnet_genesis_header();
+            header.number = number;
+            asserter.push_success(&rpc_block(header.clone(), Vec::new()));
+
+            let resolved = resolve_block_tag(&provider, tag).await.unwrap();
+            assert_eq!(resolved.number, number);
+            assert_eq!(resolved.hash_slow(), header.hash_slow());
+        }
+    }
+
+    #[tokio::test]
+    async fn log_selector_resolves_to_exactly_one_transaction() {
+        let selector = LogSelector {
+            address: Address::repeat_byte(0xaa),
+            topic0: B256::repeat_byte(0xbb),
+            from_block: 10,
+            to_block: 12,
+        };
+        let log = |transaction_hash: B256| alloy_rpc_types::Log {
+            inner: alloy_primitives::Log::new_unchecked(
+                selector.address,
+                vec![selector.topic0],
+                Bytes::new(),
+            ),
+            transaction_hash: Some(transaction_hash),
+            ..Default::default()
+        };
+        let (provider, asserter) = mock_provider();
+
+        // Two logs emitted by one transaction still name a single target
+        asserter.push_success(&vec![log(B256::repeat_byte(1)), log(B256::repeat_byte(1))]);
+        assert_eq!(
+            transaction_hash_from_log(&provider, &selector)
+                .await
+                .unwrap(),
+            B256::repeat_byte(1)
+        );
+
+        asserter.push_success(&Vec::<alloy_rpc_types::Log>::new());
+        let none = transaction_hash_from_log(&provider, &selector)
+            .await
+            .unwrap_err();
+        assert!(none.to_string().contains("No log"));
+
+        asserter.push_success(&vec![log(B256::repeat_byte(1)), log(B256::repeat_byte(2))]);
+        let ambiguous = transaction_hash_from_log(&provider, &selector)
+            .await
+            .unwrap_err();
+        assert!(ambiguous.to_string().contains("match 2 transactions"));
+    }
+
+    #[tokio::test]
+    async fn off_by_one_rpc_indices_are_corrected_from_the_block() {
+        let transactions = rpc_transactions(3);
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        let (provider, asserter) = mock_provider();
+
+        let mut tx = transactions[1].clone();
+        tx.block_number = Some(1);
+        asserter.push_success(&rpc_block(header.clone(), transactions.clone()));
+        assert_eq!(
+            verified_transaction_position(&provider, &tx).await.unwrap(),
+            (1, 1)
+        );
+
+        tx.transaction_index = Some(2);
+        asserter.push_success(&rpc_block(header.clone(), transactions.clone()));
+        assert_eq!(
+            verified_transaction_position(&provider, &tx).await.unwrap(),
+            (1, 1)
+        );
+
+        // A hash the block does not hold at all cannot be corrected
+        let mut foreign = rpc_transactions(5).remove(4);
+        foreign.block_number = Some(1);
+        asserter.push_success(&rpc_block(header, transactions));
+        assert!(verified_transaction_position(&provider, &foreign)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("not found in block 1"));
+    }
+
+    #[tokio::test]
+    async fn transactions_are_located_by_sender_and_nonce() {
+        use crate::transaction_index_by_sender_nonce;
+
+        let transactions = rpc_transactions(3);
+        let sender = transactions[0].inner.signer();
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        let block = rpc_block(header, transactions.clone());
+
+        assert_eq!(
+            transaction_index_by_sender_nonce(&block, sender, 2).unwrap(),
+            2
+        );
+        let missing = transaction_index_by_sender_nonce(&block, sender, 3).unwrap_err();
+        assert!(missing.to_string().contains("nonce 3 in block 1"));
+        assert!(transaction_index_by_sender_nonce(&block, Address::repeat_byte(0x33), 0).is_err());
+
+        // Nodes with a sender-and-nonce index answer directly
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&Some(transactions[1].clone()));
+        assert_eq!(
+            transaction_hash_by_sender_nonce(&provider, sender, 1)
+                .await
+                .unwrap(),
+            *transactions[1].inner.hash()
+        );
+        asserter.push_success(&None::<alloy_rpc_types::Transaction>);
+        assert!(transaction_hash_by_sender_nonce(&provider, sender, 9)
+            .await
+            .unwrap_err()
+            .to_string()
+            .contains("No transaction from"));
+    }
+
+    #[tokio::test]
+    async fn oversized_blocks_are_rejected_before_proving() {
+        let transactions = rpc_transactions(64);
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        let (header, _, _) = header_with_transactions(header, &transactions);
+        let block = rpc_block(header, transactions);
+        let block_bytes = serde_json::to_vec(&block).unwrap().len();
+
+        let (provider, asserter) = mock_provider();
+        let options = ProofOptions {
+            max_block_bytes: Some(block_bytes - 1),
+            ..Default::default()
+        };
+        asserter.push_success(&block);
+        let err = generate_merkle_proof_at(
+            &provider,
+            &KeccakMptBackend,
+            BlockId::Number(1.into()),
+            0,
+            &options,
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("over --max-block-bytes"));
+
+        let options = ProofOptions {
+            max_block_bytes: Some(block_bytes),
+            ..Default::default()
+        };
+        asserter.push_success(&block);
+        generate_merkle_proof_at(
+            &provider,
+            &KeccakMptBackend,
+            BlockId::Number(1.into()),
+            0,
+            &options,
+        )
+        .await
+        .expect("a block at the limit is proved");
+    }
+
+    #[tokio::test]
+    async fn block_cache_refreshes_a_reorged_block() {
+        let transactions = rpc_transactions(1);
+        let raw_transaction = encode_transaction_for_trie(&transactions[0]).unwrap();
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        header.parent_hash = MAINNET_GENESIS_HASH;
+        header.transactions_root =
+            KeccakMptBackend.build_root(&[(alloy_rlp::encode(U256::ZERO), raw_transaction)]);
+        let original = rpc_block(header.clone(), transactions);
+        let mut reorged_header = mainnet_genesis_header();
+        reorged_header.number = 1;
+        reorged_header.parent_hash = MAINNET_GENESIS_HASH;
+        reorged_header.timestamp = 12;
+        let reorged = rpc_block(reorged_header, Vec::new());
+        assert_ne!(original.header.hash, reorged.header.hash);
+
+        let (provider, asserter) = mock_provider();
+        let cache = BlockCache::new();
+        asserter.push_success(&original);
+        asserter.push_success(&original);
+        let cached = cache.get(&provider, 1).await.unwrap();
+        assert_eq!(cached.header.hash, original.header.hash);
+        assert_eq!(cached.transactions.len(), 1);
+
+        // Still canonical: only the header is refetched
+        asserter.push_success(&original);
+        assert_eq!(cache.get(&provider, 1).await.unwrap(), cached);
+        assert!(asserter.read_q().is_empty());
+
+        // The header at the same number now has another hash, so the cached block is dropped
+        asserter.push_success(&reorged);
+        asserter.push_success(&reorged);
+        let refreshed = cache.get(&provider, 1).await.unwrap();
+        assert_eq!(refreshed.header.hash, reorged.header.hash);
+        assert!(refreshed.transactions.is_empty());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn concurrent_block_cache_lookups_share_one_full_fetch() {
+        use crate::test_utils::block_server_provider;
+
+        let blocks: Vec<_> = (1..=2)
+            .map(|number| {
+                let mut header = mainnet_genesis_header();
+                header.number = number;
+                rpc_block(header, rpc_transactions(2))
+            })
+            .collect();
+        let (provider, server) = block_server_provider(blocks.clone());
+
+        let cache = BlockCache::new();
+        let limit = ConcurrencyLimit::new(4).unwrap();
+        let fetched = futures::future::try_join_all(
+            [1, 1, 2, 1, 2, 2]
+                .into_iter()
+                .map(|number| limit.run(cache.get(&provider, number))),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            fetched
+                .iter()
+                .map(|block| block.header.number)
+                .collect::<Vec<_>>(),
+            [1, 1, 2, 1, 2, 2]
+        );
+        assert_eq!(cache.len(), 2);
+        let requests = server.requests();
+        assert_eq!(
+            requests
+                .iter()
+                .filter(|(method, _)| method == "eth_getBlockByNumber")
+                .count(),
+            6
+        );
+        let mut full_fetches: Vec<_> = requests
+            .iter()
+            .filter(|(method, _)| method == "eth_getBlockByHash")
+            .map(|(_, hash)| hash.parse::<B256>().unwrap())
+            .collect();
+        full_fetches.sort();
+        let mut block_hashes: Vec<_> = blocks.iter().map(|block| block.header.hash).collect();
+        block_hashes.sort();
+        assert_eq!(full_fetches, block_hashes);
+    }
+
+    #[tokio::test]
+    async fn disagreeing_header_only_fetch_aborts_the_proof() {
+        let transactions = rpc_transactions(1);
+        let raw_transaction = encode_transaction_for_trie(&transactions[0]).unwrap();
+        let mut header = mainnet_genesis_header();
+        header.number = 1;
+        header.transactions_root =
+            KeccakMptBackend.build_root(&[(alloy_rlp::encode(U256::ZERO), raw_transaction)]);
+        let block = rpc_block(header.clone(), transactions);
+        let options = ProofOptions {
+            cross_check_header: true,
+            ..Default::default()
+        };
+
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&block);
+        asserter.push_success(&alloy_rpc_types::Header::new(header.clone()));
+        generate_merkle_proof_at(
+            &provider,
+            &KeccakMptBackend,
+            BlockId::Number(1.into()),
+            0,
+            &options,
+        )
+        .await
+        .expect("matching headers should prove");
+
+        let mut disagreeing = header;
+        disagreeing.gas_used = 21_000;
+        asserter.push_success(&block);
+        asserter.push_success(&alloy_rpc_types::Header::new(disagreeing));
+        let error = generate_merkle_proof_at(
+            &provider,
+            &KeccakMptBackend,
+            BlockId::Number(1.into()),
+            0,
+            &options,
+        )
+        .await
+        .unwrap_err();
+        assert!(error.to_string().contains("RPC is inconsistent"));
+    }
+
+    #[tokio::test]
+    async fn cached_chain_id_is_fetched_once() {
+        let (provider, asserter) = mock_provider();
+        asserter.push_success(&alloy_primitives::U64::from(17000));
+
+        let chain_id = CachedChainId::new();
+        assert_eq!(chain_id.get(&provider).await.unwrap(), 17000);
+        // The asserter has no responses left, so a second request would fail
+        assert_eq!(chain_id.get(&provider).await.unwrap(), 17000);
+        assert!(asserter.read_q().is_empty());
+    }
+}
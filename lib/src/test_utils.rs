@@ -0,0 +1,379 @@
+//! Helpers for building synthetic blocks and serving them from a mocked provider.
+
+use alloy_consensus::Header;
+use alloy_primitives::{address, b256, bytes, hex, Bloom, B256, B64, U256};
+
+/// Ethereum mainnet genesis block hash.
+pub(crate) const MAINNET_GENESIS_HASH: B256 =
+    b256!("d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3");
+
+/// Ethereum mainnet genesis header.
+pub(crate) fn mainnet_genesis_header() -> Header {
+    Header {
+        ommers_hash: b256!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"),
+        state_root: b256!("d7f8974fb5ac78d9ac099b9ad5018bedc2ce0a72dad1827a1709da30580f0544"),
+        transactions_root: alloy_trie::EMPTY_ROOT_HASH,
+        receipts_root: alloy_trie::EMPTY_ROOT_HASH,
+        logs_bloom: Bloom::ZERO,
+        difficulty: U256::from(0x400000000u64),
+        number: 0,
+        gas_limit: 5000,
+        timestamp: 0,
+        extra_data: bytes!("11bbe8db4e347b4e8c937c1c8370e4b5ed33adb3db69cbdb7a38e1e50b1b82fa"),
+        nonce: B64::from(0x42u64),
+        ..Default::default()
+    }
+}
+
+/// Ethereum mainnet block 1 hash.
+pub(crate) const MAINNET_BLOCK_1_HASH: B256 =
+    b256!("88e96d4537bea4d9c05d12549907b32561d3bf31f45aae734cdc119f13406cb6");
+
+/// Ethereum mainnet block 1 header, the child of genesis.
+pub(crate) fn mainnet_block_1_header() -> Header {
+    Header {
+        parent_hash: MAINNET_GENESIS_HASH,
+        ommers_hash: b256!("1dcc4de8dec75d7aab85b567b6ccd41ad312451b948a7413f0a142fd40d49347"),
+        beneficiary: address!("05a56e2d52c817161883f50c441c3228cfe54d9f"),
+        state_root: b256!("d67e4d450343046425ae4271474353857ab860dbc0a1dde64b41b5cd3a532bf3"),
+        transactions_root: alloy_trie::EMPTY_ROOT_HASH,
+        receipts_root: alloy_trie::EMPTY_ROOT_HASH,
+        logs_bloom: Bloom::ZERO,
+        difficulty: U256::from(0x3ff800000u64),
+        number: 1,
+        gas_limit: 5000,
+        gas_used: 0,
+        timestamp: 1438269988,
+        extra_data: bytes!("476574682f76312e302e302f6c696e75782f676f312e342e32"),
+        mix_hash: b256!("969b900de27b6ac6a67742365dd65f55a0526c41fd18e1b16f1a1215c2e66f59"),
+        nonce: B64::new(hex!("539bd4979fef1ec4")),
+        ..Default::default()
+    }
+}
+
+/// `header` with its transactions root set to the trie of `transactions`, returned with their
+/// trie encodings and that root.
+pub(crate) fn header_with_transactions(
+    mut header: Header,
+    transactions: &[alloy_rpc_types::Transaction],
+) -> (Header, Vec<alloy_primitives::Bytes>, B256) {
+    use crate::{
+        encode_transaction_for_trie, transaction_trie_leaves, KeccakMptBackend, TrieBackend,
+    };
+
+    let encoded_transactions: Vec<_> = transactions
+        .iter()
+        .map(|tx| encode_transaction_for_trie(tx).unwrap())
+        .collect();
+    let root = KeccakMptBackend.build_root(&transaction_trie_leaves(&encoded_transactions));
+    header.transactions_root = root;
+    (header, encoded_transactions, root)
+}
+
+/// Legacy transfers as returned by `eth_getBlockByNumber` with full transactions, one per nonce.
+pub(crate) fn rpc_transactions(count: u64) -> Vec<alloy_rpc_types::Transaction> {
+    use alloy_consensus::{Signed, TxEnvelope, TxLegacy};
+    use alloy_primitives::{Address, Signature, TxKind, U256};
+
+    (0..count)
+        .map(|nonce| {
+            let tx = TxLegacy {
+                chain_id: Some(1),
+                nonce,
+                gas_price: 1_000_000_000,
+                gas_limit: 21_000,
+                to: TxKind::Call(Address::repeat_byte(0x11)),
+                value: U256::from(nonce + 1),
+                input: Default::default(),
+            };
+            let signature = Signature::new(U256::from(nonce + 1), U256::from(1), false);
+            let envelope = TxEnvelope::Legacy(Signed::new_unhashed(tx, signature));
+            alloy_rpc_types::Transaction {
+                inner: alloy_consensus::transaction::Recovered::new_unchecked(
+                    envelope,
+                    Address::repeat_byte(0x22),
+                ),
+                block_hash: None,
+                block_number: None,
+                transaction_index: Some(nonce),
+                effective_gas_price: None,
+                block_timestamp: None,
+            }
+        })
+        .collect()
+}
+
+/// An EIP-1559 transfer signed by `signer`, as returned by `eth_getBlockByNumber` with full transactions.
+pub(crate) fn signed_rpc_transaction(
+    signer: &alloy_signer_local::PrivateKeySigner,
+    nonce: u64,
+) -> alloy_rpc_types::Transaction {
+    use alloy_consensus::{SignableTransaction, TxEip1559, TxEnvelope};
+    use alloy_primitives::{Address, TxKind, U256};
+    use alloy_signer::SignerSync;
+
+    let tx = TxEip1559 {
+        chain_id: 1,
+        nonce,
+        gas_limit: 21_000,
+        max_fee_per_gas: 2_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TxKind::Call(Address::repeat_byte(0x11)),
+        value: U256::from(nonce + 1),
+        access_list: Default::default(),
+        input: Default::default(),
+    };
+    let signature = signer
+        .sign_hash_sync(&tx.signature_hash())
+        .expect("test key should sign");
+    alloy_rpc_types::Transaction {
+        inner: alloy_consensus::transaction::Recovered::new_unchecked(
+            TxEnvelope::from(tx.into_signed(signature)),
+            signer.address(),
+        ),
+        block_hash: None,
+        block_number: None,
+        transaction_index: None,
+        effective_gas_price: None,
+        block_timestamp: None,
+    }
+}
+
+/// EIP-1559 receipts as returned by `eth_getBlockReceipts`, one per cumulative gas value.
+///
+/// Each receipt's `gas_used` is the difference to the previous cumulative value, as a node reports it.
+/// A signed EIP-7702 set-code transaction delegating `signer` to a fixed address.
+pub(crate) fn signed_set_code_rpc_transaction(
+    signer: &alloy_signer_local::PrivateKeySigner,
+    nonce: u64,
+) -> alloy_rpc_types::Transaction {
+    use alloy_consensus::{SignableTransaction, TxEip7702, TxEnvelope};
+    use alloy_eips::eip7702::Authorization;
+    use alloy_primitives::{Address, U256};
+    use alloy_signer::SignerSync;
+
+    let authorization = Authorization {
+        chain_id: U256::from(1),
+        address: Address::repeat_byte(0x33),
+        nonce: nonce + 1,
+    };
+    let authorization_signature = signer
+        .sign_hash_sync(&authorization.signature_hash())
+        .expect("test key should sign");
+    let tx = TxEip7702 {
+        chain_id: 1,
+        nonce,
+        gas_limit: 100_000,
+        max_fee_per_gas: 2_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: signer.address(),
+        value: U256::ZERO,
+        access_list: Default::default(),
+        authorization_list: vec![authorization.into_signed(authorization_signature)],
+        input: Default::default(),
+    };
+    let signature = signer
+        .sign_hash_sync(&tx.signature_hash())
+        .expect("test key should sign");
+    alloy_rpc_types::Transaction {
+        inner: alloy_consensus::transaction::Recovered::new_unchecked(
+            TxEnvelope::from(tx.into_signed(signature)),
+            signer.address(),
+        ),
+        block_hash: None,
+        block_number: None,
+        transaction_index: None,
+        effective_gas_price: None,
+        block_timestamp: None,
+    }
+}
+
+pub(crate) fn rpc_receipts(
+    cumulative_gas_used: &[u64],
+) -> Vec<alloy_rpc_types::TransactionReceipt> {
+    use alloy_consensus::{Eip658Value, Receipt, ReceiptEnvelope, ReceiptWithBloom};
+    use alloy_primitives::Address;
+
+    let mut previous_cumulative_gas_used = 0;
+    cumulative_gas_used
+        .iter()
+        .enumerate()
+        .map(|(i, &cumulative_gas_used)| {
+            let gas_used = cumulative_gas_used - previous_cumulative_gas_used;
+            previous_cumulative_gas_used = cumulative_gas_used;
+            alloy_rpc_types::TransactionReceipt {
+                inner: ReceiptEnvelope::Eip1559(ReceiptWithBloom {
+                    receipt: Receipt {
+                        status: Eip658Value::Eip658(true),
+                        cumulative_gas_used,
+                        logs: Vec::new(),
+                    },
+                    logs_bloom: Bloom::ZERO,
+                }),
+                transaction_hash: B256::with_last_byte(i as u8 + 1),
+                transaction_index: Some(i as u64),
+                block_hash: None,
+                block_number: None,
+                gas_used,
+                effective_gas_price: 2_000_000_000,
+                blob_gas_used: None,
+                blob_gas_price: None,
+                from: Address::repeat_byte(0x22),
+                to: Some(Address::repeat_byte(0x11)),
+                contract_address: None,
+            }
+        })
+        .collect()
+}
+
+/// Pre-Byzantium legacy receipts as [`rpc_receipts`] builds them, with the post-state root
+/// `0x40 + index` repeated in place of a status.
+pub(crate) fn pre_byzantium_rpc_receipts(
+    cumulative_gas_used: &[u64],
+) -> Vec<alloy_rpc_types::TransactionReceipt> {
+    use alloy_consensus::{Eip658Value, ReceiptEnvelope};
+
+    rpc_receipts(cumulative_gas_used)
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut receipt)| {
+            let ReceiptEnvelope::Eip1559(mut inner) = receipt.inner else {
+                unreachable!("rpc_receipts builds EIP-1559 receipts");
+            };
+            inner.receipt.status = Eip658Value::PostState(B256::repeat_byte(0x40 + i as u8));
+            receipt.inner = ReceiptEnvelope::Legacy(inner);
+            receipt
+        })
+        .collect()
+}
+
+#[cfg(feature = "rpc")]
+pub(crate) use mock::*;
+
+#[cfg(feature = "rpc")]
+mod mock {
+    use alloy::providers::{Provider, ProviderBuilder};
+    use alloy::rpc::client::RpcClient;
+    use alloy::transports::mock::Asserter;
+    use alloy::transports::{TransportError, TransportErrorKind, TransportFut};
+    use alloy_consensus::Header;
+    use alloy_json_rpc::{
+        RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest,
+    };
+    use alloy_primitives::B256;
+    use alloy_rpc_types::{Block, BlockTransactions, Transaction};
+    use std::sync::{Arc, Mutex};
+
+    /// A provider that answers requests from the returned [`Asserter`]'s queue.
+    pub(crate) fn mock_provider() -> (impl Provider, Asserter) {
+        let asserter = Asserter::new();
+        let provider = ProviderBuilder::new().connect_mocked_client(asserter.clone());
+        (provider, asserter)
+    }
+
+    /// The recorded `eth_getBlockByNumber` response for the block in `fixtures/block-3-transactions.rlp`.
+    pub(crate) fn recorded_block() -> Block {
+        serde_json::from_str(include_str!("fixtures/block-3-transactions.json"))
+            .expect("recorded block fixture should parse")
+    }
+
+    /// Serves `eth_getBlockByNumber` and `eth_getBlockByHash` from a fixed set of blocks, yielding
+    /// before each answer so concurrent callers really overlap. Every `(method, block)` request is
+    /// recorded, with the block given as its first param.
+    #[derive(Clone)]
+    pub(crate) struct BlockServer {
+        blocks: Arc<Vec<Block>>,
+        requests: Arc<Mutex<Vec<(String, String)>>>,
+    }
+
+    impl BlockServer {
+        pub(crate) fn requests(&self) -> Vec<(String, String)> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        fn respond(&self, request: &SerializedRequest) -> Result<Response, TransportError> {
+            let params: Vec<serde_json::Value> = request
+                .params()
+                .map(|params| serde_json::from_str(params.get()).unwrap())
+                .unwrap_or_default();
+            let target = params[0].as_str().unwrap_or_default().to_owned();
+            self.requests
+                .lock()
+                .unwrap()
+                .push((request.method().to_owned(), target.clone()));
+
+            let block = match request.method() {
+                "eth_getBlockByNumber" => {
+                    let number = u64::from_str_radix(target.trim_start_matches("0x"), 16).ok();
+                    self.blocks
+                        .iter()
+                        .find(|block| Some(block.header.number) == number)
+                }
+                "eth_getBlockByHash" => {
+                    let hash = target.parse::<B256>().ok();
+                    self.blocks
+                        .iter()
+                        .find(|block| Some(block.header.hash) == hash)
+                }
+                method => {
+                    return Err(TransportErrorKind::custom_str(&format!(
+                        "BlockServer does not serve {method}"
+                    )))
+                }
+            };
+            Ok(Response {
+                id: request.id().clone(),
+                payload: ResponsePayload::Success(serde_json::value::to_raw_value(&block).unwrap()),
+            })
+        }
+    }
+
+    impl tower::Service<RequestPacket> for BlockServer {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(
+            &mut self,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, packet: RequestPacket) -> Self::Future {
+            let server = self.clone();
+            Box::pin(async move {
+                tokio::task::yield_now().await;
+                match packet {
+                    RequestPacket::Single(request) => {
+                        Ok(ResponsePacket::Single(server.respond(&request)?))
+                    }
+                    RequestPacket::Batch(requests) => Ok(ResponsePacket::Batch(
+                        requests
+                            .iter()
+                            .map(|request| server.respond(request))
+                            .collect::<Result<_, _>>()?,
+                    )),
+                }
+            })
+        }
+    }
+
+    /// A provider backed by a [`BlockServer`] for `blocks`.
+    pub(crate) fn block_server_provider(blocks: Vec<Block>) -> (impl Provider, BlockServer) {
+        let server = BlockServer {
+            blocks: Arc::new(blocks),
+            requests: Arc::default(),
+        };
+        let provider = ProviderBuilder::new().connect_client(RpcClient::new(server.clone(), true));
+        (provider, server)
+    }
+
+    /// Wrap a consensus header and its transactions into an RPC block response.
+    pub(crate) fn rpc_block(header: Header, transactions: Vec<Transaction>) -> Block {
+        Block::new(
+            alloy_rpc_types::Header::new(header),
+            BlockTransactions::Full(transactions),
+        )
+    }
+}
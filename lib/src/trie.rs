@@ -0,0 +1,777 @@
+use alloy_primitives::{keccak256, Bytes, B256};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+
+/// Trie construction and proof verification used for a block's transactions root.
+///
+/// Leaves are `(key, value)` pairs where the key is the raw (un-nibbled) trie key, e.g. the RLP
+/// encoding of the transaction index.
+pub trait TrieBackend {
+    /// Compute the trie root over `leaves`.
+    fn build_root(&self, leaves: &[(Vec<u8>, Bytes)]) -> B256;
+
+    /// Compute the trie root over `leaves` and the proof nodes for `target_key`.
+    fn generate_proof(&self, leaves: &[(Vec<u8>, Bytes)], target_key: &[u8]) -> (B256, Vec<Bytes>);
+
+    /// Compute the trie root over `leaves` and one proof per target key.
+    ///
+    /// The default builds the trie once per key; backends that can share construction should override it.
+    fn generate_proofs(
+        &self,
+        leaves: &[(Vec<u8>, Bytes)],
+        target_keys: &[Vec<u8>],
+    ) -> (B256, Vec<Vec<Bytes>>) {
+        let proofs = target_keys
+            .iter()
+            .map(|target_key| self.generate_proof(leaves, target_key).1)
+            .collect();
+        (self.build_root(leaves), proofs)
+    }
+
+    /// Verify that `key` maps to `value` under `root`, or is absent when `value` is `None`.
+    fn verify_proof(
+        &self,
+        root: B256,
+        key: &[u8],
+        value: Option<&[u8]>,
+        proof: &[Bytes],
+    ) -> Result<()>;
+}
+
+/// Discriminant carried in the guest input to select the trie backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrieBackendKind {
+    /// Ethereum's keccak Merkle Patricia Trie.
+    #[default]
+    KeccakMpt,
+}
+
+impl TrieBackendKind {
+    pub fn backend(self) -> &'static dyn TrieBackend {
+        match self {
+            TrieBackendKind::KeccakMpt => &KeccakMptBackend,
+        }
+    }
+}
+
+/// Drop repeated proof nodes, keeping the first occurrence so the root-to-leaf order is preserved.
+pub fn dedup_proof_nodes(proof: Vec<Bytes>) -> Vec<Bytes> {
+    let mut seen = std::collections::HashSet::with_capacity(proof.len());
+    proof
+        .into_iter()
+        .filter(|node| seen.insert(node.clone()))
+        .collect()
+}
+
+/// The Ethereum keccak Merkle Patricia Trie, built with `alloy_trie`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KeccakMptBackend;
+
+impl KeccakMptBackend {
+    fn hash_builder(
+        leaves: &[(Vec<u8>, Bytes)],
+        target_keys: &[Vec<u8>],
+    ) -> alloy_trie::HashBuilder {
+        use alloy_trie::{proof::ProofRetainer, HashBuilder, Nibbles};
+
+        // ProofRetainer needs to know the target keys BEFORE trie construction
+        let mut trie_builder = if target_keys.is_empty() {
+            HashBuilder::default()
+        } else {
+            HashBuilder::default().with_proof_retainer(ProofRetainer::from_iter(
+                target_keys.iter().map(|key| Nibbles::unpack(key)),
+            ))
+        };
+
+        // Leaves must be added in key order for proper trie construction
+        let mut key_value_pairs: Vec<_> = leaves
+            .iter()
+            .map(|(key, value)| (Nibbles::unpack(key), value))
+            .collect();
+        key_value_pairs.sort_by(|a, b| a.0.cmp(&b.0));
+
+        for (nibbles, value) in key_value_pairs {
+            trie_builder.add_leaf(nibbles, value);
+        }
+
+        trie_builder
+    }
+}
+
+impl TrieBackend for KeccakMptBackend {
+    fn build_root(&self, leaves: &[(Vec<u8>, Bytes)]) -> B256 {
+        Self::hash_builder(leaves, &[]).root()
+    }
+
+    fn generate_proof(&self, leaves: &[(Vec<u8>, Bytes)], target_key: &[u8]) -> (B256, Vec<Bytes>) {
+        let (root, mut proofs) = self.generate_proofs(leaves, &[target_key.to_vec()]);
+        (root, proofs.remove(0))
+    }
+
+    fn generate_proofs(
+        &self,
+        leaves: &[(Vec<u8>, Bytes)],
+        target_keys: &[Vec<u8>],
+    ) -> (B256, Vec<Vec<Bytes>>) {
+        use alloy_trie::Nibbles;
+
+        let mut trie_builder = Self::hash_builder(leaves, target_keys);
+        let root = trie_builder.root();
+
+        // The retained nodes cover every target; split them per target using built-in sorting
+        let proof_nodes = trie_builder.take_proof_nodes();
+        let proofs = target_keys
+            .iter()
+            .map(|target_key| {
+                proof_nodes
+                    .matching_nodes_sorted(&Nibbles::unpack(target_key))
+                    .into_iter()
+                    .map(|(_, bytes)| bytes)
+                    .collect()
+            })
+            .collect();
+
+        (root, proofs)
+    }
+
+    fn verify_proof(
+        &self,
+        root: B256,
+        key: &[u8],
+        value: Option<&[u8]>,
+        proof: &[Bytes],
+    ) -> Result<()> {
+        use alloy_trie::{proof::verify_proof, Nibbles, EMPTY_ROOT_HASH};
+
+        // The empty trie holds no leaves, whatever proof nodes came with the claim
+        if root == EMPTY_ROOT_HASH {
+            return match value {
+                Some(_) => Err(eyre::eyre!("The empty trie contains no values")),
+                None => Ok(()),
+            };
+        }
+
+        // As in the guest: the root node is hashed even when small enough to inline elsewhere
+        match proof.first() {
+            Some(root_node) if keccak256(root_node) == root => {}
+            Some(root_node) => {
+                return Err(eyre::eyre!(
+                    "First proof node hashes to {:?}, not the root {:?}",
+                    keccak256(root_node),
+                    root
+                ))
+            }
+            None => return Err(eyre::eyre!("Proof is empty but the trie is not")),
+        }
+
+        verify_proof(root, Nibbles::unpack(key), value.map(<[u8]>::to_vec), proof)
+            .map_err(|e| eyre::eyre!("{:?}", e))
+    }
+}
+
+/// How far a proof gets along a key before it stops, for explaining why it fails to verify.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofWalk {
+    /// Key nibbles consumed by the nodes that matched.
+    pub matched_nibbles: usize,
+    pub key_nibbles: usize,
+    pub end: ProofWalkEnd,
+}
+
+/// Where a [`ProofWalk`] stopped.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProofWalkEnd {
+    /// The path reached the key and the trie stores this value there.
+    Value(Bytes),
+    /// The path leaves the key, so the trie stores nothing under it.
+    Absent,
+    /// Proof node `node` is not the node its parent references, or the root for node 0.
+    NodeMismatch { node: usize },
+    /// Proof node `node` is not a branch, extension or leaf.
+    Malformed { node: usize },
+    /// The proof ran out after `nodes` nodes, before the path settled the key.
+    Truncated { nodes: usize },
+}
+
+impl ProofWalk {
+    /// Why the walk does not prove `expected`, a value or absence for `None`; `None` when it does.
+    pub fn failure(&self, expected: Option<&[u8]>) -> Option<String> {
+        let progress = format!(
+            "matched {} of {} nibbles",
+            self.matched_nibbles, self.key_nibbles
+        );
+        match (&self.end, expected) {
+            (ProofWalkEnd::Value(value), Some(expected)) if value.as_ref() == expected => None,
+            (ProofWalkEnd::Absent, None) => None,
+            (ProofWalkEnd::Value(value), Some(expected)) => Some(format!(
+                "{} then reached a leaf holding {} bytes other than the expected {}",
+                progress,
+                value.len(),
+                expected.len()
+            )),
+            (ProofWalkEnd::Value(value), None) => Some(format!(
+                "{} then reached a leaf holding {} bytes, so the key is present",
+                progress,
+                value.len()
+            )),
+            (ProofWalkEnd::Absent, Some(_)) => Some(format!(
+                "{} then the path left the key, so it is absent",
+                progress
+            )),
+            (ProofWalkEnd::NodeMismatch { node }, _) => {
+                Some(format!("{} then node {} hash mismatch", progress, node))
+            }
+            (ProofWalkEnd::Malformed { node }, _) => Some(format!(
+                "{} then node {} is not a trie node",
+                progress, node
+            )),
+            (ProofWalkEnd::Truncated { nodes }, _) => Some(format!(
+                "{} then the proof ended after {} nodes",
+                progress, nodes
+            )),
+        }
+    }
+}
+
+/// Walk a keccak Merkle Patricia Trie `proof` from `root` towards `key` node by node, stopping
+/// where it breaks.
+///
+/// Unlike [`TrieBackend::verify_proof`], this says where a failing proof goes wrong: it was
+/// truncated, a node does not hash to its parent's reference, or the leaf holds another value.
+pub fn walk_proof(root: B256, key: &[u8], proof: &[Bytes]) -> ProofWalk {
+    walk_proof_with(|node| keccak256(node), root, key, proof)
+}
+
+/// [`walk_proof`] for a trie whose nodes are referenced by `hash` rather than keccak.
+pub(crate) fn walk_proof_with(
+    hash: impl Fn(&[u8]) -> B256,
+    root: B256,
+    key: &[u8],
+    proof: &[Bytes],
+) -> ProofWalk {
+    let key = alloy_trie::Nibbles::unpack(key).to_vec();
+    let mut walk = ProofWalk {
+        matched_nibbles: 0,
+        key_nibbles: key.len(),
+        end: ProofWalkEnd::Truncated { nodes: proof.len() },
+    };
+
+    // Nodes under 32 bytes are inlined in their parent rather than hashed, except the root
+    let mut reference = Vec::new();
+    for (index, node) in proof.iter().enumerate() {
+        let matches = if index == 0 {
+            hash(node) == root
+        } else if node.len() < 32 {
+            node.as_ref() == reference.as_slice()
+        } else {
+            alloy_rlp::encode(hash(node)) == reference
+        };
+        if !matches {
+            walk.end = ProofWalkEnd::NodeMismatch { node: index };
+            return walk;
+        }
+
+        let malformed = ProofWalkEnd::Malformed { node: index };
+        let Some(items) = rlp_list_items(node) else {
+            walk.end = malformed;
+            return walk;
+        };
+        let depth = walk.matched_nibbles;
+        match items.len() {
+            // A branch holds the value of the key that ends at it in its 17th slot
+            17 if depth == key.len() => {
+                walk.end = match rlp_string_payload(items[16]) {
+                    Some([]) => ProofWalkEnd::Absent,
+                    Some(value) => ProofWalkEnd::Value(Bytes::copy_from_slice(value)),
+                    None => malformed,
+                };
+                return walk;
+            }
+            17 => {
+                let child = items[key[depth] as usize];
+                walk.matched_nibbles += 1;
+                if child == [alloy_rlp::EMPTY_STRING_CODE] {
+                    walk.end = ProofWalkEnd::Absent;
+                    return walk;
+                }
+                reference = child.to_vec();
+            }
+            2 => {
+                let next = items[1];
+                let Some((path, is_leaf)) = decode_compact_path(items[0]) else {
+                    walk.end = malformed;
+                    return walk;
+                };
+                let rest = &key[depth..];
+                let common = path.iter().zip(rest).take_while(|(a, b)| a == b).count();
+                walk.matched_nibbles += common;
+                if common < path.len() || (is_leaf && common < rest.len()) {
+                    walk.end = ProofWalkEnd::Absent;
+                    return walk;
+                }
+                if is_leaf {
+                    walk.end = match rlp_string_payload(next) {
+                        Some(value) => ProofWalkEnd::Value(Bytes::copy_from_slice(value)),
+                        None => malformed,
+                    };
+                    return walk;
+                }
+                reference = next.to_vec();
+            }
+            _ => {
+                walk.end = malformed;
+                return walk;
+            }
+        }
+    }
+    walk
+}
+
+/// The proof for a keccak trie of one leaf, whose root node is that leaf, for when proof generation
+/// retained no nodes.
+///
+/// Nothing can be synthesized for any other trie, so this fails with an explanation instead of
+/// handing the guest an empty proof it would reject.
+pub fn single_leaf_proof(root: B256, leaves: &[(Vec<u8>, Bytes)]) -> Result<Vec<Bytes>> {
+    use alloy_trie::{nodes::LeafNode, Nibbles};
+
+    let [(key, value)] = leaves else {
+        return Err(eyre::eyre!(
+            "No proof nodes were retained for a trie of {} leaves; only a single-leaf trie's proof can be rebuilt",
+            leaves.len()
+        ));
+    };
+    let node = alloy_rlp::encode(LeafNode::new(Nibbles::unpack(key), value.to_vec()));
+    if keccak256(&node) != root {
+        return Err(eyre::eyre!(
+            "No proof nodes were retained, and the rebuilt single-leaf node does not hash to root {:?}",
+            root
+        ));
+    }
+    Ok(vec![node.into()])
+}
+
+/// Keep only the nodes of `proof` on the path from `root` to `key`, in path order.
+///
+/// Whatever the path does not reach is dropped, so a proof carrying nodes of unrelated branches
+/// shrinks to the nodes the guest actually walks. Where the path breaks, the nodes walked so far
+/// are returned.
+pub fn prune_proof_nodes(root: B256, key: &[u8], proof: &[Bytes]) -> Vec<Bytes> {
+    let mut remaining: Vec<&Bytes> = proof.iter().collect();
+    let mut path: Vec<Bytes> = Vec::with_capacity(proof.len());
+    loop {
+        let mut next = None;
+        for (position, node) in remaining.iter().enumerate() {
+            path.push((*node).clone());
+            let end = walk_proof(root, key, &path).end;
+            path.pop();
+            match end {
+                ProofWalkEnd::NodeMismatch { node } if node == path.len() => continue,
+                ProofWalkEnd::Truncated { .. } => next = Some((position, false)),
+                _ => next = Some((position, true)),
+            }
+            break;
+        }
+        let Some((position, settled)) = next else {
+            return path;
+        };
+        path.push(remaining.remove(position).clone());
+        if settled {
+            return path;
+        }
+    }
+}
+
+/// The raw encoding of each item of the RLP list `node`, or `None` if it is not exactly one list.
+fn rlp_list_items(node: &[u8]) -> Option<Vec<&[u8]>> {
+    let mut payload = node;
+    let header = alloy_rlp::Header::decode(&mut payload).ok()?;
+    if !header.list || header.payload_length != payload.len() {
+        return None;
+    }
+    let mut items = Vec::new();
+    while !payload.is_empty() {
+        let mut rest = payload;
+        let item = alloy_rlp::Header::decode(&mut rest).ok()?;
+        let length = (payload.len() - rest.len()).checked_add(item.payload_length)?;
+        items.push(payload.get(..length)?);
+        payload = &payload[length..];
+    }
+    Some(items)
+}
+
+fn rlp_string_payload(mut item: &[u8]) -> Option<&[u8]> {
+    let header = alloy_rlp::Header::decode(&mut item).ok()?;
+    (!header.list && header.payload_length == item.len()).then_some(item)
+}
+
+/// Nibbles and leaf flag of a hex-prefix encoded leaf or extension path.
+fn decode_compact_path(item: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let (&first, rest) = rlp_string_payload(item)?.split_first()?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return None;
+    }
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    for byte in rest {
+        nibbles.extend([byte >> 4, byte & 0x0f]);
+    }
+    Some((nibbles, flag & 2 == 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::U256;
+    use alloy_rlp::encode as rlp_encode;
+
+    fn leaves(count: usize) -> Vec<(Vec<u8>, Bytes)> {
+        (0..count)
+            .map(|i| {
+                (
+                    rlp_encode(U256::from(i)),
+                    Bytes::from(vec![i as u8; 40 + i % 7]),
+                )
+            })
+            .collect()
+    }
+
+    /// Keccak from `sha3`, independent of the `tiny-keccak` backing `alloy_primitives::keccak256`.
+    fn reference_keccak(data: &[u8]) -> B256 {
+        use sha3::{Digest, Keccak256};
+        B256::from_slice(&Keccak256::digest(data))
+    }
+
+    #[test]
+    fn transaction_hashes_match_an_independent_keccak() {
+        for tx in crate::test_utils::rpc_transactions(3) {
+            let encoded = crate::encode_transaction_for_trie(&tx).unwrap();
+            assert_eq!(reference_keccak(&encoded), *tx.inner.hash());
+        }
+    }
+
+    #[test]
+    fn dedup_proof_nodes_removes_injected_duplicate() {
+        let leaves = leaves(200);
+        let target_key = rlp_encode(U256::from(150));
+        let (root, proof) = KeccakMptBackend.generate_proof(&leaves, &target_key);
+
+        let mut duplicated = proof.clone();
+        duplicated.insert(1, proof[0].clone());
+        let deduped = dedup_proof_nodes(duplicated.clone());
+
+        assert!(deduped.len() < duplicated.len());
+        assert_eq!(deduped, proof);
+        KeccakMptBackend
+            .verify_proof(root, &target_key, Some(&leaves[150].1), &deduped)
+            .expect("deduplicated proof should verify");
+    }
+
+    #[test]
+    fn shared_trie_proofs_match_individual_proofs() {
+        let leaves = leaves(300);
+        let target_keys = vec![rlp_encode(U256::from(127)), rlp_encode(U256::from(128))];
+        let (root, proofs) = KeccakMptBackend.generate_proofs(&leaves, &target_keys);
+
+        assert_eq!(root, KeccakMptBackend.build_root(&leaves));
+        for (target_key, proof) in target_keys.iter().zip(&proofs) {
+            let (_, individual_proof) = KeccakMptBackend.generate_proof(&leaves, target_key);
+            assert_eq!(proof, &individual_proof);
+        }
+        KeccakMptBackend
+            .verify_proof(root, &target_keys[0], Some(&leaves[127].1), &proofs[0])
+            .expect("first proof should verify");
+        KeccakMptBackend
+            .verify_proof(root, &target_keys[1], Some(&leaves[128].1), &proofs[1])
+            .expect("second proof should verify");
+    }
+
+    #[test]
+    fn proofs_through_extension_nodes_verify() {
+        use alloy_rlp::Decodable;
+        use alloy_trie::nodes::TrieNode;
+
+        // The first two keys share the nibbles `abcdef0` beneath a root branch, forcing an extension node
+        let leaves: Vec<(Vec<u8>, Bytes)> = [
+            vec![0xab, 0xcd, 0xef, 0x01],
+            vec![0xab, 0xcd, 0xef, 0x02],
+            vec![0x10],
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, key)| (key, Bytes::from(vec![i as u8 + 1; 40])))
+        .collect();
+
+        for (key, value) in &leaves {
+            let (root, proof) = KeccakMptBackend.generate_proof(&leaves, key);
+            KeccakMptBackend
+                .verify_proof(root, key, Some(value), &proof)
+                .expect("proof should verify");
+        }
+
+        let (root, proof) = KeccakMptBackend.generate_proof(&leaves, &leaves[0].0);
+        assert_eq!(root, KeccakMptBackend.build_root(&leaves));
+        assert_eq!(proof.len(), 4);
+        assert!(matches!(
+            TrieNode::decode(&mut &proof[1][..]).expect("proof node should decode"),
+            TrieNode::Extension(_)
+        ));
+
+        // A key that leaves the shared prefix part-way is proven absent by the same extension
+        let absent_key = [0xab, 0xcd, 0x00];
+        let (_, absent_proof) = KeccakMptBackend.generate_proof(&leaves, &absent_key);
+        KeccakMptBackend
+            .verify_proof(root, &absent_key, None, &absent_proof)
+            .expect("absence proof should verify");
+    }
+
+    #[test]
+    fn empty_trie_root_settles_claims_without_the_proof() {
+        use alloy_trie::EMPTY_ROOT_HASH;
+
+        let key = rlp_encode(U256::from(0));
+        let (_, unrelated_proof) = KeccakMptBackend.generate_proof(&leaves(8), &key);
+        for proof in [Vec::new(), unrelated_proof] {
+            assert!(KeccakMptBackend
+                .verify_proof(EMPTY_ROOT_HASH, &key, Some(&[0x02, 0xc0]), &proof)
+                .is_err());
+            KeccakMptBackend
+                .verify_proof(EMPTY_ROOT_HASH, &key, None, &proof)
+                .expect("nothing is present in the empty trie");
+        }
+    }
+
+    #[test]
+    fn single_leaf_proofs_are_rebuilt_from_the_leaf() {
+        let leaves = vec![(rlp_encode(U256::ZERO), Bytes::from(vec![0x02; 40]))];
+        let (root, proof) = KeccakMptBackend.generate_proof(&leaves, &leaves[0].0);
+        assert_eq!(single_leaf_proof(root, &leaves).unwrap(), proof);
+
+        assert!(single_leaf_proof(B256::repeat_byte(1), &leaves).is_err());
+        let two_leaves = vec![
+            leaves[0].clone(),
+            (rlp_encode(U256::from(1)), Bytes::from(vec![0x02; 40])),
+        ];
+        let root = KeccakMptBackend.build_root(&two_leaves);
+        assert!(single_leaf_proof(root, &two_leaves)
+            .unwrap_err()
+            .to_string()
+            .contains("trie of 2 leaves"));
+    }
+
+    #[test]
+    fn pruning_drops_nodes_off_the_path() {
+        let backend = KeccakMptBackend;
+        let leaves = leaves(200);
+        let key = &leaves[150].0;
+        let (root, proof) = backend.generate_proof(&leaves, key);
+        // The proof retainer keeps only the path, so there is nothing to prune
+        assert_eq!(prune_proof_nodes(root, key, &proof), proof);
+
+        let (_, other_proof) = backend.generate_proof(&leaves, &leaves[3].0);
+        let unrelated: Vec<_> = other_proof
+            .iter()
+            .filter(|node| !proof.contains(node))
+            .cloned()
+            .collect();
+        assert!(!unrelated.is_empty());
+        let mut padded = unrelated.clone();
+        padded.extend(proof.iter().rev().cloned());
+        padded.extend(unrelated);
+
+        let pruned = prune_proof_nodes(root, key, &padded);
+        assert_eq!(pruned, proof);
+        backend
+            .verify_proof(root, key, Some(&leaves[150].1), &pruned)
+            .expect("pruned proof should verify");
+
+        let absent_key = rlp_encode(U256::from(5_000));
+        let (_, absence_proof) = backend.generate_proof(&leaves, &absent_key);
+        assert_eq!(
+            prune_proof_nodes(root, &absent_key, &absence_proof),
+            absence_proof
+        );
+        assert!(prune_proof_nodes(B256::ZERO, key, &proof).is_empty());
+    }
+
+    #[test]
+    fn proofs_must_open_with_the_root_node() {
+        let backend = KeccakMptBackend;
+        let leaves = leaves(20);
+        let (root, proof) = backend.generate_proof(&leaves, &leaves[3].0);
+
+        let mut tampered = proof.clone();
+        let mut root_node = tampered[0].to_vec();
+        *root_node.last_mut().unwrap() ^= 1;
+        tampered[0] = root_node.into();
+        let err = backend
+            .verify_proof(root, &leaves[3].0, Some(&leaves[3].1), &tampered)
+            .unwrap_err();
+        assert!(err.to_string().contains("First proof node hashes to"));
+        assert!(backend
+            .verify_proof(root, &leaves[3].0, Some(&leaves[3].1), &[])
+            .unwrap_err()
+            .to_string()
+            .contains("Proof is empty"));
+
+        // A lone short leaf is under 32 bytes, yet the root is still its hash and passes the check.
+        // alloy-trie's walk then compares the node inline against the root hash and rejects it; no
+        // signed transaction is short enough for this to arise in a transactions trie.
+        let small = vec![(rlp_encode(U256::ZERO), Bytes::from_static(&[0x01]))];
+        let (root, proof) = backend.generate_proof(&small, &small[0].0);
+        assert!(proof[0].len() < 32);
+        assert_eq!(alloy_primitives::keccak256(&proof[0]), root);
+        let err = backend
+            .verify_proof(root, &small[0].0, Some(&small[0].1), &proof)
+            .unwrap_err()
+            .to_string();
+        assert!(!err.contains("First proof node"), "{}", err);
+        assert!(err.contains("ValueMismatch"), "{}", err);
+    }
+
+    #[test]
+    fn walks_settle_valid_proofs_and_locate_broken_ones() {
+        let backend = KeccakMptBackend;
+        let leaves = leaves(200);
+        for (key, value) in leaves.iter().step_by(17) {
+            let (root, proof) = backend.generate_proof(&leaves, key);
+            let walk = walk_proof(root, key, &proof);
+            assert_eq!(walk.end, ProofWalkEnd::Value(value.clone()));
+            assert_eq!(walk.failure(Some(value)), None);
+        }
+
+        let key = &leaves[150].0;
+        let (root, proof) = backend.generate_proof(&leaves, key);
+        assert!(proof.len() > 2);
+
+        let truncated = walk_proof(root, key, &proof[..proof.len() - 1]);
+        assert_eq!(
+            truncated.end,
+            ProofWalkEnd::Truncated {
+                nodes: proof.len() - 1
+            }
+        );
+        assert!(truncated.matched_nibbles > 0);
+        assert!(truncated
+            .failure(Some(&leaves[150].1))
+            .unwrap()
+            .contains("proof ended after"));
+
+        let wrong_leaf = walk_proof(root, key, &proof)
+            .failure(Some(&leaves[151].1))
+            .unwrap();
+        assert!(wrong_leaf.contains(&format!(
+            "matched {} of {} nibbles then reached a leaf",
+            key.len() * 2,
+            key.len() * 2
+        )));
+
+        let mut tampered = proof.clone();
+        let mut middle = tampered[1].to_vec();
+        *middle.last_mut().unwrap() ^= 1;
+        tampered[1] = middle.into();
+        let mismatch = walk_proof(root, key, &tampered);
+        assert_eq!(mismatch.end, ProofWalkEnd::NodeMismatch { node: 1 });
+        assert_eq!(mismatch.matched_nibbles, 1);
+
+        let wrong_root = walk_proof(B256::repeat_byte(1), key, &proof);
+        assert_eq!(wrong_root.end, ProofWalkEnd::NodeMismatch { node: 0 });
+
+        let absent_key = rlp_encode(U256::from(500));
+        let (_, absence_proof) = backend.generate_proof(&leaves, &absent_key);
+        let absent = walk_proof(root, &absent_key, &absence_proof);
+        assert_eq!(absent.end, ProofWalkEnd::Absent);
+        assert_eq!(absent.failure(None), None);
+        assert!(absent.failure(Some(&leaves[0].1)).is_some());
+    }
+
+    #[test]
+    fn host_and_guest_index_keys_agree() {
+        // The host keys leaves by `U256` index and the guest by `u64`; RLP drops leading zero bytes
+        // from both, so the keys match, including past 127 where the single-byte form ends
+        for (index, expected) in [
+            (0u64, vec![0x80]),
+            (1, vec![0x01]),
+            (0x7f, vec![0x7f]),
+            (0x80, vec![0x81, 0x80]),
+            (0xff, vec![0x81, 0xff]),
+            (0x100, vec![0x82, 0x01, 0x00]),
+            (u64::MAX, [vec![0x88], vec![0xff; 8]].concat()),
+        ] {
+            assert_eq!(rlp_encode(U256::from(index)), expected, "{}", index);
+            assert_eq!(rlp_encode(index), expected, "{}", index);
+        }
+
+        let leaves = leaves(300);
+        for index in [0u64, 127, 128, 255, 256, 299] {
+            let (root, proof) =
+                KeccakMptBackend.generate_proof(&leaves, &rlp_encode(U256::from(index)));
+            KeccakMptBackend
+                .verify_proof(
+                    root,
+                    &rlp_encode(index),
+                    Some(&leaves[index as usize].1),
+                    &proof,
+                )
+                .expect("a host proof should verify under the guest's key");
+        }
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+        use proptest::sample::Index;
+
+        /// Keys over a four-symbol byte alphabet so random sets share prefixes and exercise
+        /// extension and branch nodes. Values of at least 32 bytes keep every node hashed.
+        fn entries() -> impl Strategy<Value = Vec<(Vec<u8>, Bytes)>> {
+            prop::collection::btree_map(
+                prop::array::uniform4(0u8..4),
+                prop::collection::vec(any::<u8>(), 32..64),
+                1..64,
+            )
+            .prop_map(|entries| {
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key.to_vec(), Bytes::from(value)))
+                    .collect()
+            })
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(128))]
+
+            #[test]
+            fn index_keys_agree_for_every_index(index in any::<u64>()) {
+                prop_assert_eq!(rlp_encode(U256::from(index)), rlp_encode(index));
+            }
+
+            #[test]
+            fn proof_round_trips_and_rejects_corruption(
+                leaves in entries(),
+                target in any::<Index>(),
+                corrupt_node in any::<Index>(),
+                corrupt_byte in any::<Index>(),
+            ) {
+                let (key, value) = &leaves[target.index(leaves.len())];
+                let (root, proof) = KeccakMptBackend.generate_proof(&leaves, key);
+
+                prop_assert_eq!(root, KeccakMptBackend.build_root(&leaves));
+                prop_assert!(KeccakMptBackend.verify_proof(root, key, Some(value), &proof).is_ok());
+
+                let mut corrupted = proof.clone();
+                let node = &mut corrupted[corrupt_node.index(proof.len())];
+                let mut bytes = node.to_vec();
+                let position = corrupt_byte.index(bytes.len());
+                bytes[position] ^= 0x01;
+                *node = Bytes::from(bytes);
+                prop_assert!(KeccakMptBackend
+                    .verify_proof(root, key, Some(value), &corrupted)
+                    .is_err());
+            }
+        }
+    }
+}
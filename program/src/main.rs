@@ -1,7 +1,10 @@
 #![no_main]
 sp1_zkvm::entrypoint!(main);
 
-use alloy_consensus::{transaction::SignerRecoverable, Header, Transaction, TxEnvelope};
+use alloy_consensus::{
+    transaction::SignerRecoverable, Eip658Value, Header, ReceiptEnvelope, Transaction, TxEnvelope,
+    TxReceipt,
+};
 use alloy_eips::eip2718::Decodable2718;
 use alloy_primitives::{keccak256, Address, Bytes, B256, U256};
 use alloy_rlp::{encode as rlp_encode, BufMut, Encodable, Header as RlpHeader};
@@ -28,6 +31,128 @@ pub struct TransactionInclusionInput {
     pub merkle_proof: Vec<Bytes>,
     /// When true, prove that no transaction exists at the precise index.
     pub prove_absence: bool,
+    /// Trie backend used to verify `merkle_proof`.
+    pub trie_backend: TrieBackendKind,
+    /// Block hash the proof was requested for; the proved header must hash to it.
+    pub expected_block_hash: Option<B256>,
+    /// Transaction at `transaction_index + 1`, when proving the two transactions are adjacent.
+    pub adjacent_transaction: Option<TransactionProofInput>,
+    /// Two transactions from one sender, when proving their relative order in the block.
+    pub sender_ordering: Option<SenderOrderingInput>,
+    /// Transactions root the caller trusts, e.g. from a header relay; must equal the proved header's transactions root.
+    pub claimed_root: Option<B256>,
+    /// Receipts at `transaction_index` and the index before it, when proving the transaction's gas used.
+    pub gas_used: Option<GasUsedInput>,
+    /// The block's last transaction and an absence proof after it, when proving the transaction count.
+    pub transaction_count: Option<TransactionCountInput>,
+    /// The child of `block_header` and a transaction in it, when proving ordering across the two blocks.
+    pub cross_block: Option<CrossBlockInput>,
+    /// Block number and transactions root standing in for both headers, which are then ignored.
+    pub partial_header: Option<PartialHeaderInput>,
+    /// Whether a committed transaction or verified leaf that fails to decode aborts the proof.
+    pub decode_mode: DecodeMode,
+    /// Beacon slot the proved header was proposed in; its start must equal the header timestamp.
+    pub slot: Option<SlotInput>,
+    /// Which of the two linked headers the transaction proofs are against.
+    pub proved_header: ProvedHeader,
+    /// Address and storage key to look up in the included transaction's access list.
+    pub access_list_entry: Option<AccessListEntryInput>,
+}
+
+/// An access-list address and one of its storage keys
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct AccessListEntryInput {
+    pub address: Address,
+    pub storage_key: B256,
+}
+
+/// Length of a beacon chain slot on every Ethereum network
+const SECONDS_PER_SLOT: u64 = 12;
+
+/// A beacon slot and the genesis time of its chain
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SlotInput {
+    pub slot: u64,
+    pub beacon_genesis_time: u64,
+}
+
+/// How a committed transaction or verified leaf that fails to decode, or whose signer does not
+/// recover, is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DecodeMode {
+    /// Abort the proof.
+    Strict,
+    /// Commit zero sentinels with `decodeFailed` set.
+    Lenient,
+}
+
+/// Which header the transaction proofs are against, for commitments that name a block by its parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProvedHeader {
+    /// `block_header`, with the committed transaction checked for inclusion there.
+    Block,
+    /// `parent_block_header`, whose fields are then committed; the sender witness is not checked.
+    Parent,
+}
+
+/// The only header fields the transaction proofs need; nothing binds them to a canonical block
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PartialHeaderInput {
+    pub block_number: u64,
+    pub transactions_root: B256,
+}
+
+/// An EIP-2718 encoded receipt at a given index with its proof against the block receipts root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiptProofInput {
+    pub raw_receipt: Bytes,
+    pub transaction_index: u64,
+    pub merkle_proof: Vec<Bytes>,
+}
+
+/// Receipts whose cumulative gas difference is the gas used by the receipt's transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GasUsedInput {
+    pub receipt: ReceiptProofInput,
+    pub previous_receipt: Option<ReceiptProofInput>,
+}
+
+/// The block's last transaction and an absence proof for the index after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionCountInput {
+    pub last_transaction: TransactionProofInput,
+    pub absence_proof: Vec<Bytes>,
+}
+
+/// A transaction in the direct child of the proved block, with the child's header
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrossBlockInput {
+    #[serde_as(as = "alloy_consensus::serde_bincode_compat::Header")]
+    pub next_block_header: Header,
+    pub transaction: TransactionProofInput,
+}
+
+/// Two transactions that must share a recovered sender
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SenderOrderingInput {
+    pub first: TransactionProofInput,
+    pub second: TransactionProofInput,
+}
+
+/// A transaction at a given index with its own proof against the block transactions root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionProofInput {
+    pub raw_transaction: Bytes,
+    pub transaction_index: u64,
+    pub merkle_proof: Vec<Bytes>,
+}
+
+/// Discriminant selecting the trie backend the proof is verified with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrieBackendKind {
+    /// Ethereum's keccak Merkle Patricia Trie.
+    KeccakMpt,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,11 +191,118 @@ alloy_sol_types::sol! {
         bool transactionCanBeIncluded;
         bytes32 verifiedAgainstRoot;
     }
+
+    // Always appended after PublicValuesStruct
+    struct PublicValuesExtensionStruct {
+        // Length of the header RLP that blockHash is the keccak256 of
+        uint32 headerRlpLength;
+        // EIP-2718 type of the verified leaf; zero when no inclusion was proved
+        uint8 txType;
+        // Which optional section follows, if any
+        uint8 appendedSection;
+        // Caller-supplied transactions root the proofs were verified against; zero when none was claimed
+        bytes32 claimedTransactionsRoot;
+        // Per-gas price the included transaction paid at this block's base fee; zero when no inclusion was proved
+        uint256 effectiveGasPrice;
+        // The proof used a partial header: blockHash is zero and nothing binds the block to the chain
+        bool headerUnverified;
+        // Lenient decoding only: the committed transaction or verified leaf failed to decode, so
+        // transactionCanBeIncluded, txType or effectiveGasPrice is a zero sentinel
+        bool decodeFailed;
+        // The block header's gas limit and gas used, bound to blockHash; zero for a partial header
+        uint64 blockGasLimit;
+        uint64 blockGasUsed;
+        // Length of the verified encoded transaction, for block-space accounting; zero when no
+        // inclusion was proved
+        uint32 transactionSizeBytes;
+        // Beacon slot the block was proposed in, checked against its timestamp, and the genesis
+        // time it was checked with; both zero when no slot was claimed
+        uint64 slot;
+        uint64 beaconGenesisTime;
+        // The block is proof-of-stake: EIP-3675 fixes its difficulty at zero; false for a partial
+        // header
+        bool postMerge;
+        // Number of nodes in the transactions trie proof the guest verified
+        uint8 proofDepth;
+        // keccak256 of the included transaction's input data; zero when no inclusion was proved or
+        // the leaf failed to decode
+        bytes32 calldataHash;
+        // The proofs are against the parent header, so every header field above is the parent's and
+        // transactionCanBeIncluded is false
+        bool provedInParent;
+        // Access-list entry the caller asked about, zero when none was, and whether the included
+        // transaction's access list holds it; false for absence and legacy transactions
+        address accessListAddress;
+        bytes32 accessListStorageKey;
+        bool hasAccessListEntry;
+    }
+
+    // Appended after PublicValuesExtensionStruct when appendedSection is APPENDED_SECTION_ADJACENT
+    struct AdjacentPublicValuesStruct {
+        bytes32 transactionHash;
+        bytes32 nextTransactionHash;
+        uint64 nextTransactionIndex;
+        bool areAdjacent;
+    }
+
+    // Appended after PublicValuesExtensionStruct when appendedSection is APPENDED_SECTION_SENDER_ORDERING
+    struct SenderOrderingPublicValuesStruct {
+        address sender;
+        bytes32 firstTransactionHash;
+        uint64 firstIndex;
+        bytes32 secondTransactionHash;
+        uint64 secondIndex;
+        bool inOrder;
+    }
+
+    // Appended after PublicValuesExtensionStruct when appendedSection is APPENDED_SECTION_GAS_USED
+    struct GasUsedPublicValuesStruct {
+        bytes32 transactionHash;
+        uint64 transactionIndex;
+        uint64 gasUsed;
+        // Status of the transaction's verified receipt; false for a pre-Byzantium receipt, which has none
+        bool transactionSucceeded;
+        // Post-state root of a pre-Byzantium receipt; zero for a receipt with a status
+        bytes32 postStateRoot;
+    }
+
+    // Appended after PublicValuesExtensionStruct when appendedSection is APPENDED_SECTION_TRANSACTION_COUNT
+    struct TransactionCountPublicValuesStruct {
+        bytes32 transactionHash;
+        uint64 transactionIndex;
+        uint64 transactionCount;
+        // transactionCount - 1 - transactionIndex; zero for the last transaction
+        uint64 indexFromEnd;
+    }
+
+    // Appended after PublicValuesExtensionStruct when appendedSection is APPENDED_SECTION_CROSS_BLOCK
+    struct CrossBlockPublicValuesStruct {
+        uint64 firstBlockNumber;
+        bytes32 firstTransactionHash;
+        uint64 secondBlockNumber;
+        bytes32 secondBlockHash;
+        bytes32 secondTransactionHash;
+        uint64 secondTransactionIndex;
+        // Both transactions are included and the second block is the child of the first
+        bool aIsEarlierBlock;
+    }
 }
 
+/// `appendedSection` values; at most one optional section follows the extension values.
+const APPENDED_SECTION_NONE: u8 = 0;
+const APPENDED_SECTION_ADJACENT: u8 = 1;
+const APPENDED_SECTION_SENDER_ORDERING: u8 = 2;
+const APPENDED_SECTION_GAS_USED: u8 = 3;
+const APPENDED_SECTION_TRANSACTION_COUNT: u8 = 4;
+const APPENDED_SECTION_CROSS_BLOCK: u8 = 5;
+
 impl Encodable for AccountState {
     fn encode(&self, out: &mut dyn BufMut) {
-        RlpHeader { list: true, payload_length: self.payload_length() }.encode(out);
+        RlpHeader {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
         self.nonce.encode(out);
         self.balance.encode(out);
         self.storage_root.encode(out);
@@ -79,7 +311,12 @@ impl Encodable for AccountState {
 
     fn length(&self) -> usize {
         let payload_length = self.payload_length();
-        RlpHeader { list: true, payload_length }.length() + payload_length
+        RlpHeader {
+            list: true,
+            payload_length,
+        }
+        .length()
+            + payload_length
     }
 }
 
@@ -92,23 +329,20 @@ impl AccountState {
     }
 }
 
-/// Verify Merkle Patricia Trie inclusion proof for transaction at precise index using alloy-trie
+/// Verify a transactions trie inclusion proof for transaction at precise index with the selected backend
 fn verify_merkle_proof(
+    backend: TrieBackendKind,
     key: &[u8],
     transaction_data: Option<Vec<u8>>,
     proof: &[Bytes],
     root: B256,
 ) -> bool {
-    use alloy_trie::{proof::verify_proof, Nibbles};
-
     println!(
-        "Verifying MPT proof using alloy-trie with {} proof nodes",
+        "Verifying {:?} proof with {} proof nodes",
+        backend,
         proof.len()
     );
 
-    // Convert key to nibbles (proper MPT format)
-    let key_nibbles = Nibbles::unpack(key);
-    println!("Target key nibbles: {:?}", key_nibbles);
     if let Some(transaction_data) = &transaction_data {
         println!("Target transaction size: {} bytes", transaction_data.len());
     } else {
@@ -116,11 +350,52 @@ fn verify_merkle_proof(
     }
     println!("Expected root: {:?}", root);
 
+    // The empty trie holds no leaves, so the claim is settled without walking any proof nodes
+    if root == alloy_trie::EMPTY_ROOT_HASH {
+        println!("Root is the empty trie; no transaction can be present");
+        return transaction_data.is_none();
+    }
+
+    match backend {
+        TrieBackendKind::KeccakMpt => verify_keccak_mpt_proof(key, transaction_data, proof, root),
+    }
+}
+
+/// Verify Merkle Patricia Trie inclusion proof using alloy-trie
+fn verify_keccak_mpt_proof(
+    key: &[u8],
+    transaction_data: Option<Vec<u8>>,
+    proof: &[Bytes],
+    root: B256,
+) -> bool {
+    use alloy_trie::{proof::verify_proof, Nibbles};
+
+    // The root node is always referenced by its hash, even when it is under 32 bytes and would be
+    // inlined anywhere else, so a proof that does not open with it cannot verify
+    match proof.first() {
+        Some(root_node) if keccak256(root_node) == root => {}
+        Some(root_node) => {
+            println!(
+                "✗ First proof node hashes to {:?}, not the root",
+                keccak256(root_node)
+            );
+            return false;
+        }
+        None => {
+            println!("✗ Proof is empty but the trie is not");
+            return false;
+        }
+    }
+
+    // Convert key to nibbles (proper MPT format)
+    let key_nibbles = Nibbles::unpack(key);
+    println!("Target key nibbles: {:?}", key_nibbles);
+
     // Use alloy-trie's built-in proof verification
     // verify_proof expects IntoIterator<Item = &Bytes>
     match verify_proof(
         root,
-        key_nibbles.clone(),
+        key_nibbles,
         transaction_data, // Some(value) for inclusion, None for absence
         proof,            // Pass the proof slice directly
     ) {
@@ -135,7 +410,57 @@ fn verify_merkle_proof(
     }
 }
 
-fn verify_account_proof(address: Address, account: &AccountState, proof: &[Bytes], root: B256) -> bool {
+/// Verify a transaction's inclusion proof and recover its sender, panicking if either fails
+fn verified_transaction_sender(
+    backend: TrieBackendKind,
+    transaction: &TransactionProofInput,
+    root: B256,
+) -> Address {
+    assert!(
+        verify_merkle_proof(
+            backend,
+            &rlp_encode(transaction.transaction_index),
+            Some(transaction.raw_transaction.to_vec()),
+            &transaction.merkle_proof,
+            root,
+        ),
+        "transaction at index {} must be included",
+        transaction.transaction_index
+    );
+    TxEnvelope::decode_2718_exact(&transaction.raw_transaction)
+        .expect("included transaction must decode")
+        .recover_signer()
+        .expect("included transaction signer must recover")
+}
+
+/// Verify a receipt's proof against the receipts root and decode it, legacy or typed, panicking if
+/// either fails. Pre-Byzantium legacy receipts decode with their post-state root in place of a
+/// status
+fn verified_receipt(
+    backend: TrieBackendKind,
+    receipt: &ReceiptProofInput,
+    receipts_root: B256,
+) -> ReceiptEnvelope {
+    assert!(
+        verify_merkle_proof(
+            backend,
+            &rlp_encode(receipt.transaction_index),
+            Some(receipt.raw_receipt.to_vec()),
+            &receipt.merkle_proof,
+            receipts_root,
+        ),
+        "receipt at index {} must be in the receipts trie",
+        receipt.transaction_index
+    );
+    ReceiptEnvelope::decode_2718_exact(&receipt.raw_receipt).expect("verified receipt must decode")
+}
+
+fn verify_account_proof(
+    address: Address,
+    account: &AccountState,
+    proof: &[Bytes],
+    root: B256,
+) -> bool {
     use alloy_trie::{proof::verify_proof, Nibbles};
 
     let key = keccak256(address);
@@ -154,8 +479,27 @@ fn verify_account_proof(address: Address, account: &AccountState, proof: &[Bytes
     }
 }
 
+/// Decode a committed transaction and recover its signer, or `None` if either fails
+fn decode_committed_transaction(raw_transaction: &[u8]) -> Option<(TxEnvelope, Address)> {
+    let tx = match TxEnvelope::decode_2718_exact(raw_transaction) {
+        Ok(tx) => tx,
+        Err(e) => {
+            println!("✗ Committed transaction decoding failed: {:?}", e);
+            return None;
+        }
+    };
+    match tx.recover_signer() {
+        Ok(sender) => Some((tx, sender)),
+        Err(e) => {
+            println!("✗ Committed transaction signer recovery failed: {:?}", e);
+            None
+        }
+    }
+}
+
 fn transaction_can_be_included(
-    raw_transaction: &[u8],
+    tx: &TxEnvelope,
+    sender: Address,
     parent_header: &Header,
     target_header: &Header,
     sender_account: &AccountState,
@@ -170,21 +514,6 @@ fn transaction_can_be_included(
         return false;
     }
 
-    let tx = match TxEnvelope::decode_2718_exact(raw_transaction) {
-        Ok(tx) => tx,
-        Err(e) => {
-            println!("✗ Committed transaction decoding failed: {:?}", e);
-            return false;
-        }
-    };
-    let sender = match tx.recover_signer() {
-        Ok(sender) => sender,
-        Err(e) => {
-            println!("✗ Committed transaction signer recovery failed: {:?}", e);
-            return false;
-        }
-    };
-
     if !verify_account_proof(
         sender,
         sender_account,
@@ -240,18 +569,103 @@ pub fn main() {
     let input_bytes = sp1_zkvm::io::read::<Vec<u8>>();
     let input: TransactionInclusionInput = bincode::deserialize(&input_bytes).unwrap();
 
-    // Validate block header consistency
-    let computed_block_hash = input.block_header.hash_slow();
+    // A partial header skips every header check, so the block is committed as unverified
+    let header_unverified = input.partial_header.is_some();
+    // Every committed header field is read from the proved header
+    let proved_in_parent = input.proved_header == ProvedHeader::Parent;
+    let proved_header = if proved_in_parent {
+        &input.parent_block_header
+    } else {
+        &input.block_header
+    };
+    let (computed_block_hash, block_number, transactions_root) = match &input.partial_header {
+        Some(partial) => {
+            assert!(
+                !proved_in_parent,
+                "a partial header has no parent to prove inclusion in"
+            );
+            assert!(
+                input.expected_block_hash.is_none(),
+                "a partial header cannot be bound to an expected block hash"
+            );
+            assert!(
+                input.slot.is_none(),
+                "a partial header has no timestamp to check the slot against"
+            );
+            (B256::ZERO, partial.block_number, partial.transactions_root)
+        }
+        None => {
+            // The child is what a commitment naming the parent refers to, so the pair must be linked
+            if proved_in_parent {
+                assert_eq!(
+                    input.parent_block_header.number.checked_add(1),
+                    Some(input.block_header.number),
+                    "block header must be the direct child of the parent block header"
+                );
+                assert_eq!(
+                    input.block_header.parent_hash,
+                    input.parent_block_header.hash_slow(),
+                    "block header parent hash must match the parent block header"
+                );
+            }
+
+            // Validate block header consistency
+            let computed_block_hash = proved_header.hash_slow();
+            if let Some(expected_block_hash) = input.expected_block_hash {
+                assert_eq!(
+                    computed_block_hash, expected_block_hash,
+                    "block header must hash to the expected block hash"
+                );
+            }
+            // Post-merge, a block's timestamp is the start of the slot it was proposed in
+            if let Some(slot) = &input.slot {
+                let slot_start = slot
+                    .slot
+                    .checked_mul(SECONDS_PER_SLOT)
+                    .and_then(|offset| offset.checked_add(slot.beacon_genesis_time));
+                assert_eq!(
+                    slot_start,
+                    Some(proved_header.timestamp),
+                    "block timestamp must be the start of the claimed slot"
+                );
+            }
+            (
+                computed_block_hash,
+                proved_header.number,
+                proved_header.transactions_root,
+            )
+        }
+    };
     let committed_tx_hash = keccak256(&input.committed_raw_transaction);
-    let committed_tx_can_be_included = transaction_can_be_included(
-        &input.committed_raw_transaction,
-        &input.parent_block_header,
-        &input.block_header,
-        &input.sender_account,
-        &input.sender_account_proof,
-    );
+    // Includability needs the parent state and base fee, which a partial header does not carry
+    let committed_tx = if header_unverified {
+        None
+    } else {
+        let committed_tx = decode_committed_transaction(&input.committed_raw_transaction);
+        assert!(
+            committed_tx.is_some() || input.decode_mode == DecodeMode::Lenient,
+            "committed transaction must decode and its signer must recover"
+        );
+        committed_tx
+    };
+    let committed_decode_failed = !header_unverified && committed_tx.is_none();
+    // Includability is checked at the child, so a proof in the parent never reports it
+    let committed_tx_can_be_included = !proved_in_parent
+        && committed_tx.is_some_and(|(tx, sender)| {
+            transaction_can_be_included(
+                &tx,
+                sender,
+                &input.parent_block_header,
+                &input.block_header,
+                &input.sender_account,
+                &input.sender_account_proof,
+            )
+        });
     assert!(
-        committed_tx_can_be_included,
+        committed_tx_can_be_included
+            || header_unverified
+            || committed_decode_failed
+            || proved_in_parent,
         "committed transaction must be includable at the target block"
     );
 
@@ -262,35 +676,93 @@ pub fn main() {
         keccak256(&input.raw_transaction)
     };
 
+    // Verify against the caller's trusted root, which must be the header's own root
+    let verification_root = match input.claimed_root {
+        Some(claimed_root) => {
+            assert_eq!(
+                claimed_root, transactions_root,
+                "claimed transactions root must equal the block header transactions root"
+            );
+            claimed_root
+        }
+        None => transactions_root,
+    };
+
     // RLP encode the transaction index as the key
     let key = rlp_encode(input.transaction_index);
 
     // Verify the transaction is included using the Merkle proof
     let is_included = if input.prove_absence {
         !verify_merkle_proof(
+            input.trie_backend,
             &key,
             None,
             &input.merkle_proof,
-            input.block_header.transactions_root,
+            verification_root,
         )
     } else {
         verify_merkle_proof(
+            input.trie_backend,
             &key,
             Some(input.raw_transaction.to_vec()),
             &input.merkle_proof,
-            input.block_header.transactions_root,
+            verification_root,
         )
     };
 
+    // A leaf that verifies must still be a transaction, not an arbitrary value in a crafted trie
+    let (tx_type, effective_gas_price, calldata_hash, has_access_list_entry, leaf_decode_failed) =
+        if is_included && !input.prove_absence {
+            match TxEnvelope::decode_2718_exact(&input.raw_transaction) {
+                Ok(tx) => {
+                    // Legacy and EIP-2930 pay gasPrice; EIP-1559, blob and set-code transactions pay
+                    // the base fee plus their priority fee, capped at maxFeePerGas
+                    // Without a base fee the price is unknown, so a partial header commits zero
+                    let effective_gas_price = if header_unverified {
+                        U256::ZERO
+                    } else {
+                        U256::from(tx.effective_gas_price(proved_header.base_fee_per_gas))
+                    };
+                    // Legacy transactions carry no access list, so they never have the entry
+                    let has_access_list_entry = input.access_list_entry.is_some_and(|entry| {
+                        tx.access_list().is_some_and(|access_list| {
+                            access_list.iter().any(|item| {
+                                item.address == entry.address
+                                    && item.storage_keys.contains(&entry.storage_key)
+                            })
+                        })
+                    });
+                    (
+                        tx.tx_type() as u8,
+                        effective_gas_price,
+                        keccak256(tx.input()),
+                        has_access_list_entry,
+                        false,
+                    )
+                }
+                Err(e) => {
+                    assert!(
+                        input.decode_mode == DecodeMode::Lenient,
+                        "verified leaf must decode as a transaction: {:?}",
+                        e
+                    );
+                    println!("✗ Verified leaf decoding failed: {:?}", e);
+                    (0, U256::ZERO, B256::ZERO, false, true)
+                }
+            }
+        } else {
+            (0, U256::ZERO, B256::ZERO, false, false)
+        };
+
     let proof = TransactionInclusionProof {
         block_hash: computed_block_hash,
-        block_number: input.block_header.number,
+        block_number,
         committed_transaction_hash: committed_tx_hash,
         transaction_hash: target_tx_hash,
         transaction_index: input.transaction_index,
         is_included,
         transaction_can_be_included: committed_tx_can_be_included,
-        verified_against_root: input.block_header.transactions_root,
+        verified_against_root: transactions_root,
     };
 
     // Create Solidity-compatible struct for ABI encoding
@@ -307,4 +779,272 @@ pub fn main() {
 
     // Commit ABI-encoded public values (compatible with Solidity)
     sp1_zkvm::io::commit_slice(&PublicValuesStruct::abi_encode(&solidity_public_values));
+
+    let requested_sections = [
+        (
+            input.adjacent_transaction.is_some(),
+            APPENDED_SECTION_ADJACENT,
+        ),
+        (
+            input.sender_ordering.is_some(),
+            APPENDED_SECTION_SENDER_ORDERING,
+        ),
+        (input.gas_used.is_some(), APPENDED_SECTION_GAS_USED),
+        (
+            input.transaction_count.is_some(),
+            APPENDED_SECTION_TRANSACTION_COUNT,
+        ),
+        (input.cross_block.is_some(), APPENDED_SECTION_CROSS_BLOCK),
+    ];
+    let mut appended_sections = requested_sections
+        .iter()
+        .filter(|(requested, _)| *requested)
+        .map(|&(_, section)| section);
+    let appended_section = appended_sections.next().unwrap_or(APPENDED_SECTION_NONE);
+    assert!(
+        appended_sections.next().is_none(),
+        "adjacency, sender ordering, gas used, transaction count and cross-block ordering cannot be proved together"
+    );
+
+    // Expose the header encoding length so contracts parsing the header can cross-check it
+    let extension_public_values = PublicValuesExtensionStruct {
+        headerRlpLength: if header_unverified {
+            0
+        } else {
+            u32::try_from(proved_header.length()).expect("block header RLP length must fit in u32")
+        },
+        txType: tx_type,
+        appendedSection: appended_section,
+        claimedTransactionsRoot: input.claimed_root.unwrap_or(B256::ZERO),
+        effectiveGasPrice: effective_gas_price,
+        headerUnverified: header_unverified,
+        decodeFailed: committed_decode_failed || leaf_decode_failed,
+        blockGasLimit: if header_unverified {
+            0
+        } else {
+            proved_header.gas_limit
+        },
+        blockGasUsed: if header_unverified {
+            0
+        } else {
+            proved_header.gas_used
+        },
+        transactionSizeBytes: if is_included && !input.prove_absence {
+            u32::try_from(input.raw_transaction.len())
+                .expect("encoded transaction length must fit in u32")
+        } else {
+            0
+        },
+        slot: input.slot.map_or(0, |slot| slot.slot),
+        beaconGenesisTime: input.slot.map_or(0, |slot| slot.beacon_genesis_time),
+        postMerge: !header_unverified && proved_header.difficulty.is_zero(),
+        proofDepth: u8::try_from(input.merkle_proof.len())
+            .expect("transactions trie proof must have at most 255 nodes"),
+        calldataHash: calldata_hash,
+        provedInParent: proved_in_parent,
+        accessListAddress: input
+            .access_list_entry
+            .map_or(Address::ZERO, |entry| entry.address),
+        accessListStorageKey: input
+            .access_list_entry
+            .map_or(B256::ZERO, |entry| entry.storage_key),
+        hasAccessListEntry: has_access_list_entry,
+    };
+    sp1_zkvm::io::commit_slice(&PublicValuesExtensionStruct::abi_encode(
+        &extension_public_values,
+    ));
+
+    if let Some(adjacent) = &input.adjacent_transaction {
+        assert!(
+            !input.prove_absence,
+            "adjacency requires an inclusion proof for the first transaction"
+        );
+        assert_eq!(
+            Some(adjacent.transaction_index),
+            input.transaction_index.checked_add(1),
+            "adjacent transaction index must directly follow the transaction index"
+        );
+
+        let next_is_included = verify_merkle_proof(
+            input.trie_backend,
+            &rlp_encode(adjacent.transaction_index),
+            Some(adjacent.raw_transaction.to_vec()),
+            &adjacent.merkle_proof,
+            verification_root,
+        );
+
+        let adjacent_public_values = AdjacentPublicValuesStruct {
+            transactionHash: target_tx_hash,
+            nextTransactionHash: keccak256(&adjacent.raw_transaction),
+            nextTransactionIndex: adjacent.transaction_index,
+            areAdjacent: is_included && next_is_included,
+        };
+        sp1_zkvm::io::commit_slice(&AdjacentPublicValuesStruct::abi_encode(
+            &adjacent_public_values,
+        ));
+    }
+
+    if let Some(ordering) = &input.sender_ordering {
+        let first_sender =
+            verified_transaction_sender(input.trie_backend, &ordering.first, verification_root);
+        let second_sender =
+            verified_transaction_sender(input.trie_backend, &ordering.second, verification_root);
+        assert_eq!(
+            first_sender, second_sender,
+            "sender ordering requires both transactions from the same sender"
+        );
+
+        let ordering_public_values = SenderOrderingPublicValuesStruct {
+            sender: first_sender,
+            firstTransactionHash: keccak256(&ordering.first.raw_transaction),
+            firstIndex: ordering.first.transaction_index,
+            secondTransactionHash: keccak256(&ordering.second.raw_transaction),
+            secondIndex: ordering.second.transaction_index,
+            inOrder: ordering.first.transaction_index < ordering.second.transaction_index,
+        };
+        sp1_zkvm::io::commit_slice(&SenderOrderingPublicValuesStruct::abi_encode(
+            &ordering_public_values,
+        ));
+    }
+
+    if let Some(gas_used) = &input.gas_used {
+        assert!(
+            !header_unverified,
+            "gas used needs the receipts root from the full block header"
+        );
+        assert!(
+            is_included && !input.prove_absence,
+            "gas used requires an inclusion proof for the transaction"
+        );
+        assert_eq!(
+            gas_used.receipt.transaction_index, input.transaction_index,
+            "receipt index must equal the transaction index"
+        );
+        assert_eq!(
+            gas_used
+                .previous_receipt
+                .as_ref()
+                .map(|previous| previous.transaction_index),
+            input.transaction_index.checked_sub(1),
+            "previous receipt must directly precede the transaction, and only it"
+        );
+
+        let receipts_root = proved_header.receipts_root;
+        let receipt = verified_receipt(input.trie_backend, &gas_used.receipt, receipts_root);
+        let previous_cumulative_gas_used = gas_used
+            .previous_receipt
+            .as_ref()
+            .map(|previous| {
+                verified_receipt(input.trie_backend, previous, receipts_root).cumulative_gas_used()
+            })
+            .unwrap_or(0);
+
+        let (transaction_succeeded, post_state_root) = match receipt.status_or_post_state() {
+            Eip658Value::Eip658(succeeded) => (succeeded, B256::ZERO),
+            Eip658Value::PostState(root) => (false, root),
+        };
+        let gas_used_public_values = GasUsedPublicValuesStruct {
+            transactionHash: target_tx_hash,
+            transactionIndex: input.transaction_index,
+            gasUsed: receipt
+                .cumulative_gas_used()
+                .checked_sub(previous_cumulative_gas_used)
+                .expect("cumulative gas used must not decrease"),
+            transactionSucceeded: transaction_succeeded,
+            postStateRoot: post_state_root,
+        };
+        sp1_zkvm::io::commit_slice(&GasUsedPublicValuesStruct::abi_encode(
+            &gas_used_public_values,
+        ));
+    }
+
+    if let Some(count) = &input.transaction_count {
+        assert!(
+            is_included && !input.prove_absence,
+            "transaction count requires an inclusion proof for the transaction"
+        );
+        let last = &count.last_transaction;
+        assert!(
+            verify_merkle_proof(
+                input.trie_backend,
+                &rlp_encode(last.transaction_index),
+                Some(last.raw_transaction.to_vec()),
+                &last.merkle_proof,
+                verification_root,
+            ),
+            "last transaction at index {} must be included",
+            last.transaction_index
+        );
+        let transaction_count = last
+            .transaction_index
+            .checked_add(1)
+            .expect("transaction count must fit in u64");
+        assert!(
+            verify_merkle_proof(
+                input.trie_backend,
+                &rlp_encode(transaction_count),
+                None,
+                &count.absence_proof,
+                verification_root,
+            ),
+            "no transaction may follow the last transaction"
+        );
+
+        let count_public_values = TransactionCountPublicValuesStruct {
+            transactionHash: target_tx_hash,
+            transactionIndex: input.transaction_index,
+            transactionCount: transaction_count,
+            indexFromEnd: last
+                .transaction_index
+                .checked_sub(input.transaction_index)
+                .expect("transaction index must not follow the last transaction"),
+        };
+        sp1_zkvm::io::commit_slice(&TransactionCountPublicValuesStruct::abi_encode(
+            &count_public_values,
+        ));
+    }
+
+    if let Some(cross_block) = &input.cross_block {
+        assert!(
+            !header_unverified,
+            "cross-block ordering needs the hash of the full block header"
+        );
+        assert!(
+            !input.prove_absence,
+            "cross-block ordering requires an inclusion proof for the first transaction"
+        );
+        let next_header = &cross_block.next_block_header;
+        assert_eq!(
+            Some(next_header.number),
+            block_number.checked_add(1),
+            "cross-block header must be at the next block number"
+        );
+        assert_eq!(
+            next_header.parent_hash, computed_block_hash,
+            "cross-block header must be the child of the proved block"
+        );
+
+        // The second transaction is proved against its own block's root
+        let second = &cross_block.transaction;
+        let second_is_included = verify_merkle_proof(
+            input.trie_backend,
+            &rlp_encode(second.transaction_index),
+            Some(second.raw_transaction.to_vec()),
+            &second.merkle_proof,
+            next_header.transactions_root,
+        );
+
+        let cross_block_public_values = CrossBlockPublicValuesStruct {
+            firstBlockNumber: block_number,
+            firstTransactionHash: target_tx_hash,
+            secondBlockNumber: next_header.number,
+            secondBlockHash: next_header.hash_slow(),
+            secondTransactionHash: keccak256(&second.raw_transaction),
+            secondTransactionIndex: second.transaction_index,
+            aIsEarlierBlock: is_included && second_is_included,
+        };
+        sp1_zkvm::io::commit_slice(&CrossBlockPublicValuesStruct::abi_encode(
+            &cross_block_public_values,
+        ));
+    }
 }
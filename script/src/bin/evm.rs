@@ -7,23 +7,37 @@
 //! ```
 //!
 //! To use the Succinct Prover Network, set `SP1_PROVER=network` and provide
-//! `NETWORK_PRIVATE_KEY`, then run the same command.
+//! `NETWORK_PRIVATE_KEY`, then run the same command. Point it at a different prover
+//! network with `--prover-network-rpc <url>` or `PROVER_NETWORK_RPC`.
 
 use alloy::network::Ethereum;
-use alloy::primitives::Bytes;
+use alloy::primitives::{Address, Bytes, B256};
 use alloy::providers::{Provider, RootProvider};
+use alloy::signers::local::PrivateKeySigner;
 use alloy_rpc_types::BlockId;
 use clap::Parser;
 use eyre::Result;
-use sp1_sdk::{include_elf, Elf, ProveRequest, Prover, ProverClient, ProvingKey, SP1Stdin};
+use sp1_sdk::{
+    include_elf, Elf, HashableKey, ProveRequest, Prover, ProverClient, ProvingKey, SP1Stdin,
+};
 use tx_inclusion_precise_index::{
-    default_fixture_output_path, fixture_from_proof, load_repo_dotenv,
-    select_first_transaction_from_recent_finalized_block, write_fixture_file,
-    RECENT_FINALIZED_OFFSET,
+    check_pinned_vkey, default_fixture_output_path, explain_slashing, fixture_from_proof,
+    format_table, load_repo_dotenv, organized_fixture_output_path,
+    select_first_transaction_from_recent_finalized_block, sign_public_values, verify_fixture,
+    vkey_fixture_output_path, write_bundle_file, BundleFormat, PhaseTimings, RunSummary,
+    SP1TransactionInclusionProofFixture, RECENT_FINALIZED_OFFSET,
 };
 use tx_inclusion_precise_index_lib::{
-    encode_transaction_for_trie, generate_merkle_absence_proof, generate_merkle_proof,
-    generate_sender_account_witness, TransactionInclusionInput,
+    check_confirmations, encode_transaction_for_trie, fetch_block_header_by_hash, fetch_full_block,
+    generate_adjacent_merkle_proofs_at, generate_cross_block_proof, generate_gas_used_proofs,
+    generate_merkle_absence_proof, generate_merkle_proof, generate_merkle_proof_at,
+    generate_sender_account_witness, generate_sender_ordering_proofs_at,
+    generate_transaction_count_proofs, monitor_inclusion, resolve_block_tag,
+    transaction_hash_by_sender_nonce, transaction_hash_from_log, transaction_index_by_sender_nonce,
+    verification_root, verified_transaction_position, AccessListEntryInput, BeaconClient, BlockTag,
+    CachedChainId, ConcurrencyLimit, DecodeMode, KeccakMptBackend, LogSelector, MonitorOptions,
+    MonitorOutcome, MonitorTarget, ProofOptions, ProvedHeader, SenderOrderingInput,
+    TransactionInclusionInput, TrieBackendKind, DEFAULT_MAX_CONCURRENCY, NETWORK_MIN_CONFIRMATIONS,
 };
 use url::Url;
 
@@ -38,16 +52,141 @@ struct EVMArgs {
     eth_rpc_url: Url,
     #[arg(long, help = "Optional output path for the generated fixture JSON")]
     output_path: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        conflicts_with = "output_path",
+        help = "Write the fixture under this directory as {block_number}-{tx_index}-groth16.json"
+    )]
+    output_dir: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["output_path", "output_dir"],
+        help = "Write the default fixture as groth16-fixture.json instead of groth16-{vkey8}-fixture.json"
+    )]
+    legacy_fixture_name: bool,
+    #[arg(
+        long,
+        default_value = "json",
+        help = "Encoding of the written fixture: json, or cbor for embedded verifiers; cbor swaps the default file extension"
+    )]
+    bundle_format: BundleFormat,
+    #[arg(
+        long,
+        value_name = "PRIVATE_KEY",
+        help = "Sign keccak256 of the public values with this operator key and store the signature in the fixture as operatorSignature"
+    )]
+    sign_with: Option<PrivateKeySigner>,
+    #[arg(
+        long,
+        help = "Also save the full SP1 proof with public values to this path, reloadable with SP1ProofWithPublicValues::load"
+    )]
+    save_proof: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        help = "Re-verify the written fixture off-chain: its vkey against the ELF and its Groth16 proof against its public values"
+    )]
+    verify_fixture: bool,
+    #[arg(
+        long,
+        help = "Also print the timing breakdown of the run as one line of JSON"
+    )]
+    json: bool,
+    #[arg(
+        long,
+        conflicts_with = "json",
+        help = "After proving, explain in plain English whether the committed values are slashable"
+    )]
+    explain: bool,
     #[arg(
         long,
         help = "Transaction hash to prove; omitted means first transaction from finalized - 2"
     )]
     transaction_hash: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["transaction_hash", "block_hash", "block_tag", "monitor", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Prove the transaction that emitted the one matching log, given as ADDRESS:TOPIC0:FROM-TO"
+    )]
+    log: Option<LogSelector>,
+    #[arg(
+        long,
+        requires = "nonce",
+        conflicts_with_all = ["transaction_hash", "log", "transaction_index", "monitor", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Prove the sender's transaction with --nonce, searched in --block-hash or --block-tag if given, otherwise looked up by the node"
+    )]
+    from: Option<Address>,
+    #[arg(
+        long,
+        requires = "from",
+        help = "Nonce of the --from transaction to prove"
+    )]
+    nonce: Option<u64>,
     #[arg(
         long,
         help = "Committed transaction hash when proving that a different transaction was included at the promised index"
     )]
     committed_transaction_hash: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["transaction_hash", "block_tag", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Block hash to prove against, e.g. a finalized hash from a consensus client; the proof is bound to this exact block"
+    )]
+    block_hash: Option<String>,
+    #[arg(
+        long,
+        conflicts_with_all = ["transaction_hash", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Block tag (latest, safe, finalized) resolved to one block before proving; only finalized is safe for slashing"
+    )]
+    block_tag: Option<BlockTag>,
+    #[arg(
+        long,
+        requires = "beacon_api_url",
+        conflicts_with_all = ["transaction_hash", "log", "block_hash", "block_tag", "monitor", "relayed_root", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Prove against the execution block of this beacon slot, failing on a missed or orphaned slot; the slot is committed"
+    )]
+    slot: Option<u64>,
+    #[arg(
+        long,
+        help = "Beacon node REST API that maps --slot to its execution block"
+    )]
+    beacon_api_url: Option<Url>,
+    #[arg(
+        long,
+        help = "Transaction index to prove when using --block-hash or --block-tag"
+    )]
+    transaction_index: Option<u64>,
+    #[arg(
+        long,
+        conflicts_with_all = ["block_hash", "block_tag", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Scan new blocks for --transaction-hash or --monitor-sender and prove the transaction once it is included"
+    )]
+    monitor: bool,
+    #[arg(
+        long,
+        requires = "monitor",
+        conflicts_with = "transaction_hash",
+        help = "Sender whose next transaction the monitor waits for, instead of a transaction hash"
+    )]
+    monitor_sender: Option<Address>,
+    #[arg(
+        long,
+        requires = "monitor",
+        help = "First block to monitor; defaults to latest"
+    )]
+    since_block: Option<u64>,
+    #[arg(
+        long,
+        requires = "monitor",
+        help = "Monitor deadline; if the target is not included by this block, prove the transaction at --transaction-index there"
+    )]
+    until_block: Option<u64>,
+    #[arg(
+        long,
+        requires = "monitor",
+        default_value_t = 12,
+        help = "Seconds between polls for new blocks while monitoring"
+    )]
+    poll_interval_secs: u64,
     #[arg(
         long,
         help = "Block number for a no-transaction-at-index absence proof"
@@ -63,6 +202,140 @@ struct EVMArgs {
         help = "Generate an absence proof for the first index past the selected block's transaction count"
     )]
     absence_past_end: bool,
+    #[arg(
+        long,
+        help = "Write per-transaction encoded lengths and key nibbles here if the computed root mismatches"
+    )]
+    debug_dump: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        help = "Log every trie leaf (index, key nibbles, encoded length, leaf hash) and the root so far while building"
+    )]
+    verbose_trie: bool,
+
+    #[arg(
+        long,
+        help = "Fail instead of proving when a fetched block serializes to more than this many bytes"
+    )]
+    max_block_bytes: Option<usize>,
+
+    #[arg(
+        long,
+        help = "Refetch each block's header with eth_getHeaderByNumber and abort unless it matches the full block's header"
+    )]
+    cross_check_header: bool,
+    #[arg(
+        long,
+        help = "Compare every fetched transaction's re-encoding with eth_getRawTransactionByHash and abort on a divergence"
+    )]
+    check_raw_transactions: bool,
+
+    #[arg(
+        long,
+        help = "Refuse to prove unless the block has at least this many confirmations; defaults to 64 with SP1_PROVER=network, 0 otherwise"
+    )]
+    min_confirmations: Option<u64>,
+    #[arg(
+        long,
+        default_value = "strict",
+        help = "strict aborts when the committed transaction or verified leaf fails to decode; lenient commits decodeFailed instead"
+    )]
+    decode_mode: DecodeMode,
+    #[arg(
+        long,
+        help = "Commit whether the included transaction's access list holds this ADDRESS:STORAGE_KEY entry as hasAccessListEntry"
+    )]
+    access_list_entry: Option<AccessListEntryInput>,
+    #[arg(
+        long,
+        conflicts_with_all = ["absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Also prove the transaction at the next index, committing whether the two are adjacent"
+    )]
+    prove_adjacent: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["prove_adjacent", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Index of a second transaction from the same sender; commits whether the proved transaction comes first"
+    )]
+    sender_ordering_index: Option<u64>,
+    #[arg(
+        long,
+        conflicts_with_all = ["prove_adjacent", "sender_ordering_index", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Also prove the receipts at the index and the one before it, committing the transaction's gas used"
+    )]
+    prove_gas_used: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["prove_adjacent", "sender_ordering_index", "prove_gas_used", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Also prove the block's transaction count, committing the index counted from the end of the block"
+    )]
+    prove_transaction_count: bool,
+    #[arg(
+        long,
+        conflicts_with_all = ["prove_adjacent", "sender_ordering_index", "prove_gas_used", "prove_transaction_count", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Index of a transaction in the next block; commits both inclusions and that the blocks are linked"
+    )]
+    cross_block_index: Option<u64>,
+    #[arg(
+        long,
+        conflicts_with_all = ["absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Transactions root the verifying contract trusts; the guest requires it to match the header"
+    )]
+    claimed_root: Option<B256>,
+    #[arg(
+        long,
+        conflicts_with_all = ["claimed_root", "block_hash", "block_tag", "monitor", "prove_gas_used", "cross_block_index", "absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Prove against this transactions root, relayed by an L1 contract that proves its provenance itself; sends no header"
+    )]
+    relayed_root: Option<B256>,
+    #[arg(
+        long,
+        conflicts_with_all = ["absence_block_number", "absence_transaction_index", "absence_past_end"],
+        help = "Exit non-zero before proving unless the computed transactions root is this hash"
+    )]
+    assert_root: Option<B256>,
+    #[arg(
+        long,
+        help = "Prover network RPC endpoint (also PROVER_NETWORK_RPC); defaults to the SP1 network endpoint"
+    )]
+    prover_network_rpc: Option<Url>,
+    #[arg(
+        long,
+        help = "Execute the guest locally and print the cycle count and prover gas a network proof would need, without submitting"
+    )]
+    dry_run_network: bool,
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_CONCURRENCY,
+        help = "Most RPC-bound proofs (--prove-gas-used, --prove-transaction-count, --cross-block-index, sender witness) to fetch at once. Failed requests are not retried (there is no --max-retries), so lower it for rate-limited providers"
+    )]
+    max_concurrency: usize,
+    #[arg(
+        long,
+        help = "Pinned program vkey (bytes32); the vkey derived from the ELF must match it before proving and before --verify-fixture"
+    )]
+    expected_vkey: Option<B256>,
+}
+
+/// Resolve the prover network endpoint from the flag, then `PROVER_NETWORK_RPC`.
+fn prover_network_rpc(flag: Option<Url>) -> Result<Option<Url>> {
+    let url = match flag {
+        Some(url) => url,
+        None => match std::env::var("PROVER_NETWORK_RPC") {
+            Ok(value) if !value.trim().is_empty() => value
+                .trim()
+                .parse()
+                .map_err(|e| eyre::eyre!("Invalid PROVER_NETWORK_RPC {:?}: {}", value, e))?,
+            _ => return Ok(None),
+        },
+    };
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(eyre::eyre!(
+            "Prover network RPC must be an http(s) URL, got {}",
+            url
+        ));
+    }
+    Ok(Some(url))
 }
 
 #[tokio::main]
@@ -74,7 +347,7 @@ async fn main() -> Result<()> {
     let args = EVMArgs::parse();
 
     let prover_mode = std::env::var("SP1_PROVER").unwrap_or_else(|_| "cpu".to_string());
-    if prover_mode == "network" {
+    if prover_mode == "network" && !args.dry_run_network {
         if std::env::var("NETWORK_PRIVATE_KEY").is_err() {
             eprintln!("Error: NETWORK_PRIVATE_KEY environment variable is required when SP1_PROVER=network");
             eprintln!("Make sure your .env file contains: NETWORK_PRIVATE_KEY=0x...");
@@ -82,13 +355,25 @@ async fn main() -> Result<()> {
         }
         println!("✅ Network private key found in environment");
     }
+    let prover_network_rpc = prover_network_rpc(args.prover_network_rpc.clone())?;
 
     let provider = RootProvider::<Ethereum>::new_http(args.eth_rpc_url.clone());
+    let chain_id = CachedChainId::new();
+    let limit = ConcurrencyLimit::new(args.max_concurrency)?;
 
     println!("Generating EVM-compatible proof for transaction inclusion verification");
     println!("Proof System: Groth16");
     println!("SP1 prover mode: {}", prover_mode);
 
+    let mut timings = PhaseTimings::start();
+    let proof_options = ProofOptions {
+        debug_dump: args.debug_dump.clone(),
+        transaction_filter: None,
+        verbose_trie: args.verbose_trie,
+        max_block_bytes: args.max_block_bytes,
+        cross_check_header: args.cross_check_header,
+        check_raw_transactions: args.check_raw_transactions,
+    };
     let input = if args.absence_block_number.is_some()
         || args.absence_transaction_index.is_some()
         || args.absence_past_end
@@ -139,8 +424,12 @@ async fn main() -> Result<()> {
             .get_block(BlockId::Number(block_number.into()))
             .await?
             .ok_or_else(|| eyre::eyre!("Block not found"))?;
+        timings.lap("RPC fetch");
 
-        let merkle_proof = generate_merkle_absence_proof(&provider, block_number, tx_index).await?;
+        let merkle_proof =
+            generate_merkle_absence_proof(&provider, block_number, tx_index, &proof_options)
+                .await?;
+        timings.lap("Merkle proof");
         let committed_hash = args
             .committed_transaction_hash
             .clone()
@@ -159,6 +448,7 @@ async fn main() -> Result<()> {
         let sender_witness =
             generate_sender_account_witness(&provider, block_number, &committed_raw_transaction)
                 .await?;
+        timings.lap("Sender witness");
 
         TransactionInclusionInput {
             block_header: block.header.clone().into(),
@@ -170,23 +460,133 @@ async fn main() -> Result<()> {
             transaction_index: tx_index,
             merkle_proof,
             prove_absence: true,
+            trie_backend: TrieBackendKind::default(),
+            expected_block_hash: None,
+            adjacent_transaction: None,
+            sender_ordering: None,
+            claimed_root: None,
+            gas_used: None,
+            transaction_count: None,
+            cross_block: None,
+            partial_header: None,
+            decode_mode: args.decode_mode,
+            slot: None,
+            proved_header: ProvedHeader::default(),
+            access_list_entry: None,
         }
     } else {
-        let (block_number, tx_index) = if let Some(transaction_hash) = args.transaction_hash.clone()
-        {
+        let mut committed_transaction_hash = args
+            .committed_transaction_hash
+            .as_deref()
+            .map(str::parse::<B256>)
+            .transpose()?;
+        let mut monitored_index = None;
+
+        // A slot resolves to its execution block, pinned by hash like --block-hash
+        let slot_block = match (args.slot, &args.beacon_api_url) {
+            (Some(slot), Some(beacon_api_url)) => Some(
+                BeaconClient::new(beacon_api_url.as_str())
+                    .execution_block_at_slot(slot)
+                    .await?,
+            ),
+            _ => None,
+        };
+
+        // A tag or monitored block is pinned by hash as soon as it is chosen, like --block-hash
+        let expected_block_hash = if args.monitor {
+            let target = match (args.monitor_sender, args.transaction_hash.as_deref()) {
+                (Some(sender), _) => MonitorTarget::Sender(sender),
+                (None, Some(transaction_hash)) => {
+                    MonitorTarget::Transaction(transaction_hash.parse()?)
+                }
+                (None, None) => {
+                    return Err(eyre::eyre!(
+                        "--monitor requires --transaction-hash or --monitor-sender"
+                    ))
+                }
+            };
+            let monitor_options = MonitorOptions {
+                since_block: args.since_block,
+                until_block: args.until_block,
+                poll_interval: std::time::Duration::from_secs(args.poll_interval_secs),
+            };
+            match monitor_inclusion(&provider, target, &monitor_options).await? {
+                MonitorOutcome::Included {
+                    block_hash,
+                    transaction_index,
+                    ..
+                } => {
+                    monitored_index = Some(transaction_index);
+                    Some(block_hash)
+                }
+                MonitorOutcome::NotIncluded { block_hash, .. } => {
+                    // Past the deadline, prove what took the promised index instead
+                    if let MonitorTarget::Transaction(transaction_hash) = target {
+                        committed_transaction_hash.get_or_insert(transaction_hash);
+                    }
+                    Some(block_hash)
+                }
+            }
+        } else {
+            match (args.block_tag, slot_block) {
+                (Some(tag), _) => Some(resolve_block_tag(&provider, tag).await?.hash_slow()),
+                (None, Some(slot_block)) => Some(slot_block.block_hash),
+                (None, None) => args
+                    .block_hash
+                    .as_deref()
+                    .map(str::parse::<B256>)
+                    .transpose()?,
+            }
+        };
+        if args.monitor {
+            timings.lap("Monitor");
+        }
+
+        // A log selector names the transaction by an event it emitted; a sender and nonce without a
+        // block to search go through the node's index
+        let transaction_hash = match (&args.log, args.from.zip(args.nonce)) {
+            (Some(selector), _) => Some(transaction_hash_from_log(&provider, selector).await?),
+            (None, Some((from, nonce))) if expected_block_hash.is_none() => {
+                Some(transaction_hash_by_sender_nonce(&provider, from, nonce).await?)
+            }
+            _ => args
+                .transaction_hash
+                .as_deref()
+                .map(str::parse::<B256>)
+                .transpose()?,
+        };
+
+        let (block_number, tx_index) = if let Some(block_hash) = expected_block_hash {
+            let header = fetch_block_header_by_hash(&provider, block_hash).await?;
+            if let Some(slot_block) = &slot_block {
+                slot_block.slot.check_header(&header)?;
+            }
+            let tx_index = match (monitored_index, args.from.zip(args.nonce)) {
+                (Some(tx_index), _) => tx_index,
+                (None, Some((from, nonce))) => {
+                    let block = fetch_full_block(&provider, BlockId::Hash(block_hash.into())).await?;
+                    transaction_index_by_sender_nonce(&block, from, nonce)?
+                }
+                (None, None) => args.transaction_index.ok_or_else(|| {
+                    eyre::eyre!(
+                        "--transaction-index or --from/--nonce is required with --block-hash, --block-tag, --slot or a monitor deadline"
+                    )
+                })?,
+            };
+
+            println!(
+                "Block {:?} resolved to number: {}, index: {}",
+                block_hash, header.number, tx_index
+            );
+            (header.number, tx_index)
+        } else if let Some(transaction_hash) = transaction_hash {
             // Get the transaction details
             let tx = provider
-                .get_transaction_by_hash(transaction_hash.parse()?)
+                .get_transaction_by_hash(transaction_hash)
                 .await?
                 .ok_or_else(|| eyre::eyre!("Transaction not found"))?;
 
-            let block_number = tx
-                .block_number
-                .ok_or_else(|| eyre::eyre!("Transaction not mined"))?;
-            let tx_index = tx
-                .transaction_index
-                .ok_or_else(|| eyre::eyre!("Transaction index not found"))?
-                as u64;
+            let (block_number, tx_index) = verified_transaction_position(&provider, &tx).await?;
 
             println!(
                 "Transaction found in block: {}, index: {}",
@@ -204,32 +604,127 @@ async fn main() -> Result<()> {
             );
             (selection.block_number, selection.transaction_index)
         };
+        timings.lap("RPC fetch");
 
-        // Get the block with all transactions
-        let block = provider
-            .get_block(BlockId::Number(block_number.into()))
-            .await?
-            .ok_or_else(|| eyre::eyre!("Block not found"))?;
+        // A hash-bound block is fetched by that hash and checked against it before proving
+        let block_id = match expected_block_hash {
+            Some(block_hash) => BlockId::Hash(block_hash.into()),
+            None => BlockId::Number(block_number.into()),
+        };
 
         // Generate Merkle proof
-        let (merkle_proof, encoded_tx_bytes) =
-            generate_merkle_proof(&provider, block_number, tx_index).await?;
+        let mut adjacent_transaction = None;
+        let mut sender_ordering = None;
+        let bundle = if args.prove_adjacent {
+            let (bundle, next_bundle) = generate_adjacent_merkle_proofs_at(
+                &provider,
+                &KeccakMptBackend,
+                block_id,
+                tx_index,
+                &proof_options,
+            )
+            .await?;
+            adjacent_transaction = Some(next_bundle.into());
+            bundle
+        } else if let Some(second_index) = args.sender_ordering_index {
+            let (bundle, second_bundle) = generate_sender_ordering_proofs_at(
+                &provider,
+                &KeccakMptBackend,
+                block_id,
+                tx_index,
+                second_index,
+                &proof_options,
+            )
+            .await?;
+            sender_ordering = Some(SenderOrderingInput {
+                first: bundle.clone().into(),
+                second: second_bundle.into(),
+            });
+            bundle
+        } else {
+            generate_merkle_proof_at(
+                &provider,
+                &KeccakMptBackend,
+                block_id,
+                tx_index,
+                &proof_options,
+            )
+            .await?
+        };
+        // Reject a proof/index mismatch here rather than after spending proving resources
+        bundle.check_index(tx_index)?;
+        if let Some(expected_root) = args.assert_root {
+            bundle.check_root(expected_root)?;
+            println!("✅ Computed transactions root matches --assert-root");
+        }
+        verification_root(&bundle.block_header, args.claimed_root)?;
         let committed_raw_transaction =
-            if let Some(committed_transaction_hash) = args.committed_transaction_hash {
+            if let Some(committed_transaction_hash) = committed_transaction_hash {
                 let committed_tx = provider
-                    .get_transaction_by_hash(committed_transaction_hash.parse()?)
+                    .get_transaction_by_hash(committed_transaction_hash)
                     .await?
                     .ok_or_else(|| eyre::eyre!("Committed transaction not found"))?;
                 encode_transaction_for_trie(&committed_tx)?
             } else {
-                encoded_tx_bytes.clone()
+                bundle.raw_transaction.clone()
             };
-        let sender_witness =
-            generate_sender_account_witness(&provider, block_number, &committed_raw_transaction)
-                .await?;
+
+        // The optional proofs and the sender witness are independent, so they are fetched
+        // concurrently under --max-concurrency
+        let block_header = &bundle.block_header;
+        let (gas_used, transaction_count, cross_block, sender_witness) = tokio::try_join!(
+            limit.run(async {
+                if !args.prove_gas_used {
+                    return Ok(None);
+                }
+                generate_gas_used_proofs(
+                    &provider,
+                    &chain_id,
+                    &KeccakMptBackend,
+                    block_header,
+                    tx_index,
+                )
+                .await
+                .map(Some)
+            }),
+            limit.run(async {
+                if !args.prove_transaction_count {
+                    return Ok(None);
+                }
+                generate_transaction_count_proofs(
+                    &provider,
+                    &KeccakMptBackend,
+                    block_header,
+                    &proof_options,
+                )
+                .await
+                .map(Some)
+            }),
+            limit.run(async {
+                let Some(next_tx_index) = args.cross_block_index else {
+                    return Ok(None);
+                };
+                generate_cross_block_proof(
+                    &provider,
+                    &KeccakMptBackend,
+                    block_header,
+                    next_tx_index,
+                    &proof_options,
+                )
+                .await
+                .map(Some)
+            }),
+            limit.run(generate_sender_account_witness(
+                &provider,
+                block_number,
+                &committed_raw_transaction,
+            )),
+        )?;
+        timings.lap("Merkle proof and sender witness");
+        let (merkle_proof, encoded_tx_bytes) = (bundle.merkle_proof, bundle.raw_transaction);
 
         TransactionInclusionInput {
-            block_header: block.header.clone().into(),
+            block_header: bundle.block_header,
             parent_block_header: sender_witness.parent_block_header,
             committed_raw_transaction,
             sender_account: sender_witness.account,
@@ -238,28 +733,95 @@ async fn main() -> Result<()> {
             transaction_index: tx_index,
             merkle_proof,
             prove_absence: false,
+            trie_backend: TrieBackendKind::default(),
+            expected_block_hash,
+            adjacent_transaction,
+            sender_ordering,
+            claimed_root: args.claimed_root,
+            gas_used,
+            transaction_count,
+            cross_block,
+            partial_header: None,
+            decode_mode: args.decode_mode,
+            slot: slot_block.map(|slot_block| slot_block.slot),
+            proved_header: ProvedHeader::default(),
+            access_list_entry: args.access_list_entry,
         }
     };
 
+    let input = match args.relayed_root {
+        Some(relayed_root) => {
+            println!("⚠️  WARNING: relayed root mode; the proof is bound only to {:?}, whose provenance the verifying contract must prove itself", relayed_root);
+            input.into_relayed_root(relayed_root)?
+        }
+        None => input,
+    };
+
     // Serialize input
     let input_bytes = bincode::serialize(&input)?;
     let mut stdin = SP1Stdin::new();
     stdin.write(&input_bytes);
 
+    if args.dry_run_network {
+        // Execution only: nothing is sent to the prover network
+        println!("Dry run: executing the guest locally to estimate network proving...");
+        let client = ProverClient::builder().light().build().await;
+        let (_, report) = client
+            .execute(TX_INCLUSION_ELF, stdin)
+            .calculate_gas(true)
+            .await
+            .map_err(|e| eyre::eyre!("Execution failed: {}", e))?;
+        timings.lap("Execute");
+        let prover_gas = report
+            .gas()
+            .map_or_else(|| "unavailable".to_string(), |gas| format!("{} PGUs", gas));
+        println!(
+            "\n{}",
+            format_table(&[
+                ("Proof system", "Groth16".to_string()),
+                ("Cycles", report.total_instruction_count().to_string()),
+                ("Prover gas", prover_gas),
+            ])
+        );
+        // The SDK meters prover gas but does not quote a price; the network prices each PGU
+        println!("Projected cost is the prover gas times the network's price per PGU. Nothing was submitted.");
+        timings.print(args.json)?;
+        return Ok(());
+    }
+
+    let min_confirmations = args
+        .min_confirmations
+        .unwrap_or(if prover_mode == "network" {
+            NETWORK_MIN_CONFIRMATIONS
+        } else {
+            0
+        });
+    check_confirmations(&provider, input.block_number(), min_confirmations).await?;
+
     println!("Generating Groth16 proof...");
     let (proof, vkey) = if prover_mode == "network" {
-        let client = ProverClient::builder().network().build().await;
+        let mut builder = ProverClient::builder().network();
+        if let Some(rpc_url) = &prover_network_rpc {
+            println!("Using prover network RPC: {}", rpc_url);
+            builder = builder.rpc_url(rpc_url.as_str());
+        }
+        let client = builder.build().await;
         let pk = client
             .setup(TX_INCLUSION_ELF)
             .await
             .map_err(|e| eyre::eyre!("Setup failed: {}", e))?;
+        timings.lap("Setup");
         let vkey = pk.verifying_key().clone();
+        if let Some(expected_vkey) = args.expected_vkey {
+            check_pinned_vkey(&vkey.bytes32(), expected_vkey)?;
+        }
         let proof = client
             .prove(&pk, stdin)
             .groth16()
             .skip_simulation(true)
             .await
             .map_err(|e| eyre::eyre!("Proof generation failed: {}", e))?;
+        timings.lap("Prove");
         (proof, vkey)
     } else {
         let client = ProverClient::from_env().await;
@@ -267,12 +829,17 @@ async fn main() -> Result<()> {
             .setup(TX_INCLUSION_ELF)
             .await
             .map_err(|e| eyre::eyre!("Setup failed: {}", e))?;
+        timings.lap("Setup");
         let vkey = pk.verifying_key().clone();
+        if let Some(expected_vkey) = args.expected_vkey {
+            check_pinned_vkey(&vkey.bytes32(), expected_vkey)?;
+        }
         let proof = client
             .prove(&pk, stdin)
             .groth16()
             .await
             .map_err(|e| eyre::eyre!("Proof generation failed: {}", e))?;
+        timings.lap("Prove");
         (proof, vkey)
     };
 
@@ -284,8 +851,35 @@ async fn main() -> Result<()> {
         println!("✅ EVM-compatible proof generated successfully locally!");
     }
 
-    create_proof_fixture(&proof, &vkey, args.output_path.as_deref())?;
+    let fixture = create_proof_fixture(
+        &proof,
+        &vkey,
+        args.output_path.as_deref(),
+        args.output_dir.as_deref(),
+        args.legacy_fixture_name,
+        args.bundle_format,
+        args.sign_with.as_ref(),
+    )?;
+    timings.lap("Write fixture");
+    if args.explain {
+        println!("\n{}", explain_slashing(proof.public_values.as_slice())?);
+    }
+
+    if args.verify_fixture {
+        let client = ProverClient::builder().cpu().build().await;
+        verify_fixture(&client, &fixture, TX_INCLUSION_ELF, args.expected_vkey).await?;
+        timings.lap("Verify fixture");
+        println!("✅ Fixture verified off-chain against the ELF's vkey");
+    }
 
+    if let Some(save_proof) = &args.save_proof {
+        proof
+            .save(save_proof)
+            .map_err(|e| eyre::eyre!("Failed to save proof to {:?}: {}", save_proof, e))?;
+        println!("✅ Proof saved to: {:?}", save_proof);
+    }
+
+    timings.print(args.json)?;
     Ok(())
 }
 
@@ -294,46 +888,49 @@ fn create_proof_fixture(
     proof: &sp1_sdk::SP1ProofWithPublicValues,
     vk: &sp1_sdk::SP1VerifyingKey,
     output_path: Option<&std::path::Path>,
-) -> Result<()> {
-    let fixture = fixture_from_proof(proof, vk)?;
+    output_dir: Option<&std::path::Path>,
+    legacy_fixture_name: bool,
+    bundle_format: BundleFormat,
+    sign_with: Option<&PrivateKeySigner>,
+) -> Result<SP1TransactionInclusionProofFixture> {
+    let mut fixture = fixture_from_proof(proof, vk)?;
+    if let Some(operator) = sign_with {
+        sign_public_values(&mut fixture, operator)?;
+        println!(
+            "Signed the public values as operator {}",
+            operator.address()
+        );
+    }
     // The verification key is used to verify that the proof corresponds to the execution of the
     // program on the given input.
-    println!("\n=== EVM PROOF FIXTURE GENERATED ===");
-    println!("Verification Key: {}", fixture.vkey);
-    println!("Block Hash: {}", fixture.block_hash);
-    println!("Block Number: {}", fixture.block_number);
-    println!(
-        "Committed Transaction Hash: {}",
-        fixture.committed_transaction_hash
-    );
-    println!("Transaction Hash: {}", fixture.transaction_hash);
-    println!("Transaction Index: {}", fixture.transaction_index);
-    println!("Is Included: {}", fixture.is_included);
-    println!(
-        "Transaction Can Be Included: {}",
-        fixture.transaction_can_be_included
-    );
-    println!("Verified Against Root: {}", fixture.verified_against_root);
-    println!("Public Values: {}", fixture.public_values);
-    println!(
-        "Proof Bytes Length: {} bytes",
-        hex::decode(&fixture.proof[2..]).unwrap().len()
-    );
+    println!("\n{}", RunSummary::from_proof(proof, vk)?.table());
 
     // Save the fixture to a file.
-    let fixture_file_path = output_path
-        .map(std::path::Path::to_path_buf)
-        .unwrap_or_else(|| default_fixture_output_path("groth16"));
+    let fixture_file_path = match (output_path, output_dir) {
+        (Some(output_path), _) => output_path.to_path_buf(),
+        (None, Some(output_dir)) => organized_fixture_output_path(
+            output_dir,
+            fixture.block_number,
+            fixture.transaction_index,
+            "groth16",
+        ),
+        (None, None) if legacy_fixture_name => default_fixture_output_path("groth16"),
+        (None, None) => vkey_fixture_output_path("groth16", &fixture.vkey),
+    };
+    let fixture_file_path = match (output_path, bundle_format) {
+        (None, BundleFormat::Cbor) => fixture_file_path.with_extension(bundle_format.extension()),
+        _ => fixture_file_path,
+    };
     std::fs::create_dir_all(
         fixture_file_path
             .parent()
             .expect("fixture path should have a parent"),
     )
     .expect("failed to create fixture path");
-    write_fixture_file(&fixture, &fixture_file_path)?;
+    write_bundle_file(&fixture, &fixture_file_path, bundle_format)?;
 
     println!("\n✅ Fixture saved to: {:?}", fixture_file_path);
     println!("This fixture can be used for on-chain verification testing.");
 
-    Ok(())
+    Ok(fixture)
 }
@@ -1,15 +1,27 @@
 use alloy::network::Ethereum;
+use alloy::primitives::{Address, B256};
 use alloy::providers::{Provider, RootProvider};
 use alloy_rpc_types::BlockId;
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use eyre::Result;
-use sp1_sdk::{include_elf, utils, Elf, Prover, ProverClient, ProvingKey, SP1Stdin};
+use sp1_sdk::{include_elf, utils, Elf, HashableKey, Prover, ProverClient, ProvingKey, SP1Stdin};
 use tx_inclusion_precise_index::{
-    decode_public_values, load_repo_dotenv, select_first_transaction_from_recent_finalized_block,
-    RECENT_FINALIZED_OFFSET,
+    check_pinned_vkey, cycle_samples_csv, decode_adjacent_public_values, decode_public_values,
+    decode_public_values_extension, explain_slashing, load_repo_dotenv, public_values_json,
+    select_first_transaction_from_recent_finalized_block, verify_inclusion_proof, CycleSample,
+    CycleSummary, PhaseTimings, RunSummary, RECENT_FINALIZED_OFFSET,
 };
 use tx_inclusion_precise_index_lib::{
-    generate_merkle_proof, generate_sender_account_witness, TransactionInclusionInput,
+    build_adjacent_merkle_proofs, build_merkle_proof, check_block_size, check_confirmations,
+    check_input_transactions_root, fetch_block_header_by_hash, fetch_child_header,
+    fetch_full_block, generate_adjacent_merkle_proofs_at, generate_merkle_proof_at,
+    generate_sender_account_witness, inclusion_inputs_for_transactions, list_transaction_indices,
+    ordering_differences, resolve_block_tag, resolve_transaction_indices,
+    transaction_hash_by_sender_nonce, transaction_hash_from_log, transaction_index_by_sender_nonce,
+    transaction_index_in_block, verified_transaction_position, warn_pending_block,
+    AccessListEntryInput, BeaconClient, BlockCache, BlockTag, ConcurrencyLimit, DecodeMode,
+    InputFormat, KeccakMptBackend, LogSelector, ProofOptions, ProvedHeader,
+    TransactionInclusionInput, TrieBackendKind, DEFAULT_MAX_CONCURRENCY, NETWORK_MIN_CONFIRMATIONS,
 };
 
 use url::Url;
@@ -30,9 +42,400 @@ struct Args {
         help = "Transaction hash to prove; omitted means first transaction from finalized - 2"
     )]
     transaction_hash: Option<String>,
+    #[clap(
+        long,
+        conflicts_with_all = ["transaction_hash", "block_hash", "block_tag"],
+        help = "Prove the transaction that emitted the one matching log, given as ADDRESS:TOPIC0:FROM-TO"
+    )]
+    log: Option<LogSelector>,
+    #[clap(
+        long,
+        requires = "nonce",
+        conflicts_with_all = ["transaction_hash", "log", "transaction_index", "input_file", "stdin"],
+        help = "Prove the sender's transaction with --nonce, searched in --block-hash or --block-tag if given, otherwise looked up by the node"
+    )]
+    from: Option<Address>,
+    #[clap(
+        long,
+        requires = "from",
+        help = "Nonce of the --from transaction to prove"
+    )]
+    nonce: Option<u64>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["transaction_hash", "block_tag"],
+        help = "Block hash to prove against, e.g. a finalized hash from a consensus client; the proof is bound to this exact block"
+    )]
+    block_hash: Option<String>,
+    #[clap(
+        long,
+        conflicts_with = "transaction_hash",
+        help = "Block tag (latest, safe, finalized) resolved to one block before proving; only finalized is safe for slashing"
+    )]
+    block_tag: Option<BlockTag>,
+    #[clap(
+        long,
+        requires = "beacon_api_url",
+        conflicts_with_all = ["transaction_hash", "log", "block_hash", "block_tag", "partial_header", "relayed_root", "input_file", "stdin"],
+        help = "Prove against the execution block of this beacon slot, failing on a missed or orphaned slot; the slot is committed"
+    )]
+    slot: Option<u64>,
+    #[clap(
+        long,
+        help = "Beacon node REST API that maps --slot to its execution block"
+    )]
+    beacon_api_url: Option<Url>,
+
+    #[clap(
+        long,
+        help = "Transaction index to prove when using --block-hash or --block-tag"
+    )]
+    transaction_index: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Allow proving a not-yet-mined transaction against the pending block (monitoring only, never usable on-chain)"
+    )]
+    allow_pending: bool,
+
+    #[clap(
+        long,
+        help = "Write per-transaction encoded lengths and key nibbles here if the computed root mismatches"
+    )]
+    debug_dump: Option<std::path::PathBuf>,
+
+    #[clap(
+        long,
+        help = "Log every trie leaf (index, key nibbles, encoded length, leaf hash) and the root so far while building"
+    )]
+    verbose_trie: bool,
+
+    #[clap(
+        long,
+        help = "Fail instead of proving when a fetched block serializes to more than this many bytes"
+    )]
+    max_block_bytes: Option<usize>,
+
+    #[clap(
+        long,
+        help = "Refetch each block's header with eth_getHeaderByNumber and abort unless it matches the full block's header"
+    )]
+    cross_check_header: bool,
+    #[clap(
+        long,
+        help = "Compare every fetched transaction's re-encoding with eth_getRawTransactionByHash and abort on a divergence"
+    )]
+    check_raw_transactions: bool,
+
+    #[clap(
+        long,
+        help = "Refuse to run unless the block has at least this many confirmations; defaults to 64 for --prove with SP1_PROVER=network, 0 otherwise"
+    )]
+    min_confirmations: Option<u64>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["transaction_hash", "block_hash", "prove_adjacent"],
+        help = "Read the input from this file instead of building it over RPC"
+    )]
+    input_file: Option<std::path::PathBuf>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["transaction_hash", "block_hash", "block_tag", "prove_adjacent", "input_file"],
+        help = "Read the input from stdin instead of building it over RPC, e.g. piped from another tool"
+    )]
+    stdin: bool,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["block_hash", "block_tag"],
+        help = "Send only the block number and transactions root; the proof is not bound to a canonical block"
+    )]
+    partial_header: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["block_hash", "block_tag", "partial_header", "input_file", "stdin"],
+        help = "Prove against this transactions root, relayed by an L1 contract that proves its provenance itself; sends no header"
+    )]
+    relayed_root: Option<B256>,
+    #[clap(
+        long,
+        conflicts_with_all = ["block_tag", "partial_header", "relayed_root", "input_file", "stdin"],
+        help = "Prove against the block as the parent of its child, for commitments that name a block by its parent; commits provedInParent"
+    )]
+    prove_in_parent: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["input_file", "stdin"],
+        help = "Exit non-zero before proving unless the computed transactions root is this hash"
+    )]
+    assert_root: Option<B256>,
+
+    #[clap(
+        long,
+        default_value = "strict",
+        conflicts_with_all = ["input_file", "stdin"],
+        help = "strict aborts when the committed transaction or verified leaf fails to decode; lenient commits decodeFailed instead"
+    )]
+    decode_mode: DecodeMode,
+    #[clap(
+        long,
+        conflicts_with_all = ["input_file", "stdin"],
+        help = "Commit whether the included transaction's access list holds this ADDRESS:STORAGE_KEY entry as hasAccessListEntry"
+    )]
+    access_list_entry: Option<AccessListEntryInput>,
+
+    #[clap(long, help = "Also write the built or loaded input to this file")]
+    dump_input: Option<std::path::PathBuf>,
+    #[clap(
+        long,
+        requires = "dump_input",
+        help = "zstd-compress the --dump-input file; --input-file and --stdin detect compressed input themselves"
+    )]
+    compress_input: bool,
+    #[clap(
+        long,
+        conflicts_with_all = ["prove", "execute"],
+        help = "Build or load the input, print its content hash (keccak256 of the bincode the guest reads) and exit, e.g. to precompute cache keys"
+    )]
+    input_hash_only: bool,
+
+    #[clap(
+        long,
+        default_value = "json",
+        help = "Encoding of --input-file, --stdin and --dump-input: json or bincode"
+    )]
+    input_format: InputFormat,
+
+    #[clap(
+        long,
+        help = "Also prove the transaction at the next index, committing whether the two are adjacent"
+    )]
+    prove_adjacent: bool,
 
     #[clap(long, conflicts_with = "prove")]
     execute: bool,
+
+    #[clap(
+        long,
+        requires = "prove",
+        help = "Pinned program vkey (bytes32); the vkey derived from the ELF must match it before proving and verifying"
+    )]
+    expected_vkey: Option<B256>,
+
+    #[clap(
+        long,
+        conflicts_with_all = ["prove", "execute"],
+        requires = "cycle_transaction_hashes",
+        help = "Only execute the guest for --cycle-transaction-hashes and report min/max/avg cycles"
+    )]
+    count_cycles_only: bool,
+
+    #[clap(
+        long,
+        value_delimiter = ',',
+        help = "Comma-separated mined transaction hashes to execute with --count-cycles-only"
+    )]
+    cycle_transaction_hashes: Vec<B256>,
+
+    #[clap(
+        long,
+        default_value_t = DEFAULT_MAX_CONCURRENCY,
+        help = "Most RPC-bound input builds for --count-cycles-only to run at once. Failed requests are not retried (there is no --max-retries), so lower it for rate-limited providers"
+    )]
+    max_concurrency: usize,
+
+    #[clap(
+        long,
+        requires = "count_cycles_only",
+        help = "Fail with status 2 if any --count-cycles-only run exceeds this many cycles"
+    )]
+    max_cycles: Option<u64>,
+
+    #[clap(
+        long,
+        requires = "count_cycles_only",
+        help = "Write --count-cycles-only cycles aggregated by transaction type and proof node count to this CSV"
+    )]
+    cycle_csv: Option<std::path::PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "BLOCK",
+        conflicts_with_all = ["execute", "prove", "count_cycles_only"],
+        help = "Print the index -> transaction hash table of a block (number, hash or tag) and exit without proving"
+    )]
+    list_tx_indices: Option<BlockId>,
+
+    #[clap(
+        long,
+        value_delimiter = ',',
+        requires = "list_tx_indices",
+        help = "Comma-separated transaction hashes to resolve to their indices instead of listing the whole block"
+    )]
+    resolve_hashes: Vec<B256>,
+
+    #[clap(
+        long,
+        num_args = 2,
+        value_names = ["BLOCK_A", "BLOCK_B"],
+        conflicts_with_all = ["execute", "prove", "count_cycles_only", "list_tx_indices"],
+        help = "Fetch two blocks (number, hash or tag) and print the transactions both hold at different indices, then exit without proving"
+    )]
+    compare_orderings: Vec<BlockId>,
+
+    #[clap(
+        long,
+        help = "Also print the --list-tx-indices table, or the public values and timing breakdown of a run, as JSON lines"
+    )]
+    json: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "json",
+        help = "After executing or proving, explain in plain English whether the committed values are slashable"
+    )]
+    explain: bool,
+
+    #[clap(
+        long,
+        value_enum,
+        default_value = "included",
+        help = "Outcome --execute must observe; any other outcome exits with status 2"
+    )]
+    expect: ExpectedOutcome,
+}
+
+/// Inclusion outcome the execute branch asserts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ExpectedOutcome {
+    Included,
+    Excluded,
+}
+
+/// Exit status when execution succeeds but the outcome differs from `--expect`, or a
+/// `--count-cycles-only` run exceeds `--max-cycles`.
+///
+/// Distinct from the status 1 used for usage and runtime errors.
+const EXIT_UNEXPECTED_OUTCOME: i32 = 2;
+
+/// Execute the guest once per `--cycle-transaction-hashes` entry without proving and summarise cycles.
+async fn count_cycles(args: &Args, provider: &impl Provider) -> Result<()> {
+    let proof_options = ProofOptions {
+        debug_dump: args.debug_dump.clone(),
+        transaction_filter: None,
+        verbose_trie: args.verbose_trie,
+        max_block_bytes: args.max_block_bytes,
+        cross_check_header: args.cross_check_header,
+        check_raw_transactions: args.check_raw_transactions,
+    };
+    // Inputs are fetched concurrently; the guest then runs on them one at a time. Hashes in the same
+    // block share one fetch of its full transactions.
+    let blocks = BlockCache::new();
+    let limit = ConcurrencyLimit::new(args.max_concurrency)?;
+    let inputs = inclusion_inputs_for_transactions(
+        provider,
+        &args.cycle_transaction_hashes,
+        &blocks,
+        &limit,
+        &proof_options,
+    )
+    .await?;
+    let client = ProverClient::from_env().await;
+
+    let mut cycles = Vec::with_capacity(inputs.len());
+    let mut samples = Vec::with_capacity(inputs.len());
+    for (&transaction_hash, input) in args.cycle_transaction_hashes.iter().zip(&inputs) {
+        let mut stdin = SP1Stdin::new();
+        stdin.write(&bincode::serialize(input)?);
+
+        let (_, report) = client
+            .execute(ELF, stdin)
+            .await
+            .map_err(|e| eyre::eyre!("Execution failed: {}", e))?;
+        let count = report.total_instruction_count();
+        let sample = CycleSample::from_input(input, count)?;
+        println!(
+            "{:?}: {} cycles (type {}, {} proof nodes, {} proof bytes)",
+            transaction_hash, count, sample.tx_type, sample.proof_nodes, sample.proof_bytes
+        );
+        cycles.push(count);
+        samples.push(sample);
+    }
+
+    let summary = CycleSummary::from_counts(&cycles)
+        .ok_or_else(|| eyre::eyre!("No transactions given to --cycle-transaction-hashes"))?;
+    println!("\n=== CYCLE SUMMARY ===");
+    println!("Runs: {}", summary.runs);
+    println!("Min cycles: {}", summary.min);
+    println!("Max cycles: {}", summary.max);
+    println!("Avg cycles: {}", summary.avg);
+
+    let csv = cycle_samples_csv(&samples);
+    println!("\n=== CYCLES BY TRANSACTION TYPE AND PROOF NODES ===");
+    print!("{}", csv);
+    if let Some(path) = &args.cycle_csv {
+        std::fs::write(path, &csv)?;
+        println!("Wrote cycle breakdown to {:?}", path);
+    }
+
+    if let Some(max_cycles) = args.max_cycles {
+        if summary.exceeds(max_cycles) {
+            println!(
+                "❌ FAILURE: max cycles {} exceeds threshold {}",
+                summary.max, max_cycles
+            );
+            std::process::exit(EXIT_UNEXPECTED_OUTCOME);
+        }
+        println!("✅ All runs within {} cycles", max_cycles);
+    }
+
+    Ok(())
+}
+
+/// Print which index each transaction of a block occupies, or only the `--resolve-hashes` ones.
+async fn list_tx_indices(args: &Args, provider: &impl Provider, block_id: BlockId) -> Result<()> {
+    let entries = list_transaction_indices(provider, block_id, None).await?;
+    let entries = if args.resolve_hashes.is_empty() {
+        entries
+    } else {
+        resolve_transaction_indices(&entries, &args.resolve_hashes)?
+    };
+
+    if args.json {
+        println!("{}", serde_json::to_string(&entries)?);
+    } else {
+        println!("{:>6}  Transaction hash", "Index");
+        for entry in &entries {
+            println!("{:>6}  {:?}", entry.index, entry.transaction_hash);
+        }
+    }
+    Ok(())
+}
+
+async fn compare_orderings(args: &Args, provider: &impl Provider) -> Result<()> {
+    let entries_a = list_transaction_indices(provider, args.compare_orderings[0], None).await?;
+    let entries_b = list_transaction_indices(provider, args.compare_orderings[1], None).await?;
+    let differences = ordering_differences(&entries_a, &entries_b);
+
+    if args.json {
+        println!("{}", serde_json::to_string(&differences)?);
+    } else {
+        println!("{:>7}  {:>7}  Transaction hash", "Index A", "Index B");
+        for difference in &differences {
+            println!(
+                "{:>7}  {:>7}  {:?}",
+                difference.index_a, difference.index_b, difference.transaction_hash
+            );
+        }
+        println!(
+            "{} transactions in both blocks at different indices",
+            differences.len()
+        );
+    }
+    Ok(())
 }
 
 #[tokio::main]
@@ -45,68 +448,303 @@ async fn main() -> Result<()> {
 
     println!("Running transaction inclusion at precise index proof test");
 
+    if args.count_cycles_only {
+        return count_cycles(&args, &provider).await;
+    }
+    if let Some(block_id) = args.list_tx_indices {
+        return list_tx_indices(&args, &provider, block_id).await;
+    }
+    if !args.compare_orderings.is_empty() {
+        return compare_orderings(&args, &provider).await;
+    }
+
     // Error handling if neither option is selected
-    if !args.execute && !args.prove {
-        eprintln!("Error: You must specify either --execute or --prove");
+    if !args.execute && !args.prove && !args.input_hash_only {
+        eprintln!("Error: You must specify either --execute, --prove or --input-hash-only");
         std::process::exit(1);
     }
 
     println!("=== Testing transaction inclusion at precise index ===");
 
-    let (block_number, tx_index) = if let Some(transaction_hash) = args.transaction_hash {
-        // Get the transaction details
-        let tx = provider
-            .get_transaction_by_hash(transaction_hash.parse()?)
-            .await?
-            .ok_or_else(|| eyre::eyre!("Transaction not found"))?;
+    let mut timings = PhaseTimings::start();
+    let input = if let Some(input_file) = &args.input_file {
+        println!(
+            "Loading {:?} input from {:?}",
+            args.input_format, input_file
+        );
+        let input = args.input_format.decode(&std::fs::read(input_file)?)?;
+        timings.lap("Load input");
+        input
+    } else if args.stdin {
+        use std::io::Read;
+
+        println!("Loading {:?} input from stdin", args.input_format);
+        let mut bytes = Vec::new();
+        std::io::stdin().read_to_end(&mut bytes)?;
+        let input = args
+            .input_format
+            .decode(&bytes)
+            .map_err(|e| eyre::eyre!("Failed to decode input from stdin: {}", e))?;
+        timings.lap("Load input");
+        input
+    } else {
+        // A slot resolves to its execution block, pinned by hash like --block-hash
+        let slot_block = match (args.slot, &args.beacon_api_url) {
+            (Some(slot), Some(beacon_api_url)) => Some(
+                BeaconClient::new(beacon_api_url.as_str())
+                    .execution_block_at_slot(slot)
+                    .await?,
+            ),
+            _ => None,
+        };
+        // A tag is pinned to the block it names now, then proven by hash like --block-hash
+        let expected_block_hash = match (args.block_tag, slot_block) {
+            (Some(tag), _) => Some(resolve_block_tag(&provider, tag).await?.hash_slow()),
+            (None, Some(slot_block)) => Some(slot_block.block_hash),
+            (None, None) => args
+                .block_hash
+                .as_deref()
+                .map(str::parse::<B256>)
+                .transpose()?,
+        };
+
+        // A log selector names the transaction by an event it emitted; a sender and nonce without a
+        // block to search go through the node's index
+        let transaction_hash = match (&args.log, args.from.zip(args.nonce)) {
+            (Some(selector), _) => Some(transaction_hash_from_log(&provider, selector).await?),
+            (None, Some((from, nonce))) if expected_block_hash.is_none() => {
+                Some(transaction_hash_by_sender_nonce(&provider, from, nonce).await?)
+            }
+            _ => args
+                .transaction_hash
+                .as_deref()
+                .map(str::parse::<B256>)
+                .transpose()?,
+        };
+
+        let mut pending_block = None;
+        let (block_number, tx_index) = if let Some(block_hash) = expected_block_hash {
+            let header = fetch_block_header_by_hash(&provider, block_hash).await?;
+            if let Some(slot_block) = &slot_block {
+                slot_block.slot.check_header(&header)?;
+            }
+            let tx_index = match args.from.zip(args.nonce) {
+                Some((from, nonce)) => {
+                    let block = fetch_full_block(&provider, BlockId::Hash(block_hash.into())).await?;
+                    transaction_index_by_sender_nonce(&block, from, nonce)?
+                }
+                None => args.transaction_index.ok_or_else(|| {
+                    eyre::eyre!(
+                        "--transaction-index or --from/--nonce is required with --block-hash, --block-tag or --slot"
+                    )
+                })?,
+            };
+
+            println!(
+                "Block {:?} resolved to number: {}, index: {}",
+                block_hash, header.number, tx_index
+            );
+            (header.number, tx_index)
+        } else if let Some(transaction_hash) = transaction_hash {
+            // Get the transaction details
+            let tx = provider
+                .get_transaction_by_hash(transaction_hash)
+                .await?
+                .ok_or_else(|| eyre::eyre!("Transaction not found"))?;
+
+            if tx.block_number.is_none() && args.allow_pending {
+                warn_pending_block();
+                let block = fetch_full_block(&provider, BlockId::pending()).await?;
+                let tx_index = transaction_index_in_block(&block, transaction_hash)?;
+                let block_number = block.header.number;
 
-        let block_number = tx
-            .block_number
-            .ok_or_else(|| eyre::eyre!("Transaction not mined"))?;
-        let tx_index =
-            tx.transaction_index
-                .ok_or_else(|| eyre::eyre!("Transaction index not found"))? as u64;
+                println!(
+                    "Transaction found in pending block: {}, index: {}",
+                    block_number, tx_index
+                );
+                pending_block = Some(block);
+                (block_number, tx_index)
+            } else {
+                let (block_number, tx_index) =
+                    verified_transaction_position(&provider, &tx).await?;
 
+                println!(
+                    "Transaction found in block: {}, index: {}",
+                    block_number, tx_index
+                );
+                (block_number, tx_index)
+            }
+        } else {
+            let selection = select_first_transaction_from_recent_finalized_block(&provider).await?;
+            println!(
+                "Selected first transaction from block {} (finalized block {} - {}, {} transactions)",
+                selection.block_number,
+                selection.finalized_block_number,
+                RECENT_FINALIZED_OFFSET,
+                selection.transaction_count
+            );
+            (selection.block_number, selection.transaction_index)
+        };
+        timings.lap("RPC fetch");
+
+        // Generate Merkle proof which includes the block header and the actual encoded transaction
+        let proof_options = ProofOptions {
+            debug_dump: args.debug_dump.clone(),
+            transaction_filter: None,
+            verbose_trie: args.verbose_trie,
+            max_block_bytes: args.max_block_bytes,
+            cross_check_header: args.cross_check_header,
+            check_raw_transactions: args.check_raw_transactions,
+        };
+        let (bundle, next_bundle) = match &pending_block {
+            // Reuse the fetched pending block: a second fetch could return a different pending block
+            Some(block) => {
+                check_block_size(block, proof_options.max_block_bytes)?;
+                let transactions = block
+                    .transactions
+                    .as_transactions()
+                    .ok_or_else(|| eyre::eyre!("Expected full transactions in pending block"))?;
+                let header = block.header.clone().into();
+                if args.prove_adjacent {
+                    let (bundle, next_bundle) = build_adjacent_merkle_proofs(
+                        &KeccakMptBackend,
+                        &header,
+                        transactions,
+                        tx_index,
+                        &proof_options,
+                    )?;
+                    (bundle, Some(next_bundle))
+                } else {
+                    let bundle = build_merkle_proof(
+                        &KeccakMptBackend,
+                        &header,
+                        transactions,
+                        tx_index,
+                        &proof_options,
+                    )?;
+                    (bundle, None)
+                }
+            }
+            None => {
+                let block_id = match expected_block_hash {
+                    Some(block_hash) => BlockId::Hash(block_hash.into()),
+                    None => BlockId::Number(block_number.into()),
+                };
+                if args.prove_adjacent {
+                    let (bundle, next_bundle) = generate_adjacent_merkle_proofs_at(
+                        &provider,
+                        &KeccakMptBackend,
+                        block_id,
+                        tx_index,
+                        &proof_options,
+                    )
+                    .await?;
+                    (bundle, Some(next_bundle))
+                } else {
+                    let bundle = generate_merkle_proof_at(
+                        &provider,
+                        &KeccakMptBackend,
+                        block_id,
+                        tx_index,
+                        &proof_options,
+                    )
+                    .await?;
+                    (bundle, None)
+                }
+            }
+        };
+        // Reject a proof/index mismatch here rather than after spending proving resources
+        bundle.check_index(tx_index)?;
+        if let Some(expected_root) = args.assert_root {
+            bundle.check_root(expected_root)?;
+            println!("✅ Computed transactions root matches --assert-root");
+        }
+        timings.lap("Merkle proof");
+        let encoded_tx_bytes = bundle.raw_transaction;
+        let sender_witness =
+            generate_sender_account_witness(&provider, block_number, &encoded_tx_bytes).await?;
+        timings.lap("Sender witness");
+
+        TransactionInclusionInput {
+            block_header: bundle.block_header,
+            parent_block_header: sender_witness.parent_block_header,
+            committed_raw_transaction: encoded_tx_bytes.clone(),
+            sender_account: sender_witness.account,
+            sender_account_proof: sender_witness.proof,
+            raw_transaction: encoded_tx_bytes,
+            transaction_index: tx_index,
+            merkle_proof: bundle.merkle_proof,
+            prove_absence: false,
+            trie_backend: TrieBackendKind::default(),
+            expected_block_hash,
+            adjacent_transaction: next_bundle.map(Into::into),
+            sender_ordering: None,
+            claimed_root: None,
+            gas_used: None,
+            transaction_count: None,
+            cross_block: None,
+            partial_header: None,
+            decode_mode: args.decode_mode,
+            slot: slot_block.map(|slot_block| slot_block.slot),
+            proved_header: ProvedHeader::default(),
+            access_list_entry: args.access_list_entry,
+        }
+    };
+    let input = if let Some(relayed_root) = args.relayed_root {
+        println!("⚠️  WARNING: relayed root mode; the proof is bound only to {:?}, whose provenance the verifying contract must prove itself", relayed_root);
+        input.into_relayed_root(relayed_root)?
+    } else if args.partial_header {
+        println!("⚠️  WARNING: partial header mode; the proof commits a zero block hash and is not bound to any canonical block");
+        input.into_partial_header()?
+    } else if args.prove_in_parent {
+        let child_header = fetch_child_header(&provider, &input.block_header).await?;
         println!(
-            "Transaction found in block: {}, index: {}",
-            block_number, tx_index
+            "Proving inclusion in block {} as the parent of block {} ({:?})",
+            input.block_header.number,
+            child_header.number,
+            child_header.hash_slow()
         );
-        (block_number, tx_index)
+        input.into_parent_inclusion(child_header)?
     } else {
-        let selection = select_first_transaction_from_recent_finalized_block(&provider).await?;
+        input
+    };
+    if let Some(dump_input) = &args.dump_input {
+        let bytes = if args.compress_input {
+            args.input_format.encode_compressed(&input)?
+        } else {
+            args.input_format.encode(&input)?
+        };
+        std::fs::write(dump_input, bytes)?;
         println!(
-            "Selected first transaction from block {} (finalized block {} - {}, {} transactions)",
-            selection.block_number,
-            selection.finalized_block_number,
-            RECENT_FINALIZED_OFFSET,
-            selection.transaction_count
+            "Wrote {} {:?} input to {:?}",
+            if args.compress_input {
+                "compressed"
+            } else {
+                "plain"
+            },
+            args.input_format,
+            dump_input
         );
-        (selection.block_number, selection.transaction_index)
-    };
+    }
 
-    // Get the block with all transactions
-    let block = provider
-        .get_block(BlockId::Number(block_number.into()))
-        .await?
-        .ok_or_else(|| eyre::eyre!("Block not found"))?;
-
-    // Generate Merkle proof which includes the actual encoded transaction
-    let (merkle_proof, encoded_tx_bytes) =
-        generate_merkle_proof(&provider, block_number, tx_index).await?;
-    let sender_witness =
-        generate_sender_account_witness(&provider, block_number, &encoded_tx_bytes).await?;
-
-    let input = TransactionInclusionInput {
-        block_header: block.header.clone().into(),
-        parent_block_header: sender_witness.parent_block_header,
-        committed_raw_transaction: encoded_tx_bytes.clone(),
-        sender_account: sender_witness.account,
-        sender_account_proof: sender_witness.proof,
-        raw_transaction: encoded_tx_bytes,
-        transaction_index: tx_index,
-        merkle_proof,
-        prove_absence: false,
-    };
+    if args.input_hash_only {
+        println!("{:?}", input.content_hash()?);
+        return Ok(());
+    }
+
+    let network_prover = std::env::var("SP1_PROVER").as_deref() == Ok("network");
+    let min_confirmations = args
+        .min_confirmations
+        .unwrap_or(if args.prove && network_prover {
+            NETWORK_MIN_CONFIRMATIONS
+        } else {
+            0
+        });
+    check_confirmations(&provider, input.block_number(), min_confirmations).await?;
+    // An input built here was fetched consistently; one read back may pair any number with any root
+    if args.input_file.is_some() || args.stdin {
+        check_input_transactions_root(&provider, &input).await?;
+    }
 
     // Serialize input
     let input_bytes = bincode::serialize(&input)?;
@@ -114,6 +752,7 @@ async fn main() -> Result<()> {
     stdin.write(&input_bytes);
 
     let client = ProverClient::from_env().await;
+    timings.lap("Prover client");
 
     if args.execute {
         // Execution branch
@@ -122,6 +761,7 @@ async fn main() -> Result<()> {
             .execute(ELF, stdin)
             .await
             .map_err(|e| eyre::eyre!("Execution failed: {}", e))?;
+        timings.lap("Execute");
         println!(
             "Program executed with {} cycles",
             report.total_instruction_count()
@@ -131,6 +771,7 @@ async fn main() -> Result<()> {
         let decoded = decode_public_values(output.as_slice())?;
 
         println!("\n=== EXECUTION RESULT ===");
+        println!("ABI Public Values: 0x{}", hex::encode(output.as_slice()));
         println!(
             "Block Hash: 0x{}",
             hex::encode(decoded.blockHash.as_slice())
@@ -155,11 +796,72 @@ async fn main() -> Result<()> {
             hex::encode(decoded.verifiedAgainstRoot.as_slice())
         );
 
-        // Verify the result
-        if decoded.isIncluded {
-            println!("✅ SUCCESS: Transaction correctly proved as INCLUDED");
-        } else {
-            println!("❌ FAILURE: Transaction should be included but was marked as excluded");
+        if let Some(extension) = decode_public_values_extension(output.as_slice())? {
+            println!("Header RLP Length: {}", extension.headerRlpLength);
+            println!("Transaction Type: {}", extension.txType);
+            println!("Effective Gas Price: {} wei", extension.effectiveGasPrice);
+            println!("Header Unverified: {}", extension.headerUnverified);
+            println!("Decode Failed: {}", extension.decodeFailed);
+            println!(
+                "Block Gas Used: {} / {}",
+                extension.blockGasUsed, extension.blockGasLimit
+            );
+            println!("Transaction Size: {} bytes", extension.transactionSizeBytes);
+            println!("Post-Merge: {}", extension.postMerge);
+            println!("Proof Depth: {} nodes", extension.proofDepth);
+            println!("Calldata Hash: {:?}", extension.calldataHash);
+            println!("Proved In Parent: {}", extension.provedInParent);
+            if extension.accessListAddress != Address::ZERO {
+                println!(
+                    "Access List Entry ({:?}, {:?}): {}",
+                    extension.accessListAddress,
+                    extension.accessListStorageKey,
+                    extension.hasAccessListEntry
+                );
+            }
+            if extension.beaconGenesisTime != 0 {
+                println!("Slot: {}", extension.slot);
+            }
+        }
+        if let Some(adjacent) = decode_adjacent_public_values(output.as_slice())? {
+            println!(
+                "Next Transaction Hash: 0x{}",
+                hex::encode(adjacent.nextTransactionHash.as_slice())
+            );
+            println!("Next Transaction Index: {}", adjacent.nextTransactionIndex);
+            println!("Are Adjacent: {}", adjacent.areAdjacent);
+        }
+
+        if args.json {
+            println!(
+                "{}",
+                serde_json::to_string(&public_values_json(output.as_slice())?)?
+            );
+        }
+
+        if args.explain {
+            println!("\n{}", explain_slashing(output.as_slice())?);
+        }
+
+        // Printed first: an unexpected outcome exits below
+        timings.print(args.json)?;
+
+        // Verify the result; a mismatch exits non-zero so monitoring scripts can detect it
+        match (args.expect, decoded.isIncluded) {
+            (ExpectedOutcome::Included, true) => {
+                println!("✅ SUCCESS: Transaction correctly proved as INCLUDED");
+            }
+            (ExpectedOutcome::Excluded, false) => {
+                println!("✅ SUCCESS: Transaction correctly proved as EXCLUDED");
+            }
+            (ExpectedOutcome::Included, false) => {
+                println!("❌ FAILURE: Transaction should be included but was marked as excluded");
+                std::process::exit(EXIT_UNEXPECTED_OUTCOME);
+            }
+            (ExpectedOutcome::Excluded, true) => {
+                println!("❌ FAILURE: Transaction should be excluded but was marked as included");
+                std::process::exit(EXIT_UNEXPECTED_OUTCOME);
+            }
         }
     } else {
         // Proof generation branch
@@ -168,14 +870,29 @@ async fn main() -> Result<()> {
             .setup(ELF)
             .await
             .map_err(|e| eyre::eyre!("Setup failed: {}", e))?;
+        timings.lap("Setup");
+        if let Some(expected_vkey) = args.expected_vkey {
+            check_pinned_vkey(&pk.verifying_key().bytes32(), expected_vkey)?;
+            println!("✅ Derived vkey matches the pinned vkey");
+        }
         let proof = client
             .prove(&pk, stdin)
             .await
             .map_err(|e| eyre::eyre!("Proof generation failed: {}", e))?;
+        timings.lap("Prove");
         println!("✅ Proof generated successfully!");
 
-        client.verify(&proof, pk.verifying_key(), None)?;
+        verify_inclusion_proof(&client, &proof, pk.verifying_key())?;
+        timings.lap("Verify");
         println!("✅ Proof verified successfully!");
+        println!(
+            "\n{}",
+            RunSummary::from_proof(&proof, pk.verifying_key())?.table()
+        );
+        if args.explain {
+            println!("\n{}", explain_slashing(proof.public_values.as_slice())?);
+        }
+        timings.print(args.json)?;
     }
 
     Ok(())
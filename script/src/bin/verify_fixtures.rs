@@ -0,0 +1,96 @@
+//! Verify every `*-fixture.json` in a directory against the vkey derived from the current ELF, so
+//! CI catches a stale or broken fixture before it ships. With `--expected-vkey`, the derived vkey
+//! must first match the pinned one.
+//!
+//! ```shell
+//! cargo run --release --bin verify_fixtures -- ../contracts/src/fixtures --expected-vkey 0x...
+//! ```
+
+use alloy::primitives::B256;
+use clap::Parser;
+use eyre::Result;
+use sp1_sdk::{include_elf, Elf, HashableKey, Prover, ProverClient, ProvingKey};
+use tx_inclusion_precise_index::{
+    check_pinned_vkey, default_fixture_dir, fixture_paths_in, format_table, read_fixture_file,
+    verify_fixture_against_vkey,
+};
+
+/// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
+const ELF: Elf = include_elf!("tx-inclusion-precise-index-client");
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[arg(help = "Directory to scan; defaults to contracts/src/fixtures")]
+    dir: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        help = "Pinned program vkey (bytes32); the vkey derived from the ELF must match it before any fixture is verified"
+    )]
+    expected_vkey: Option<B256>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let dir = args.dir.unwrap_or_else(default_fixture_dir);
+    let paths = fixture_paths_in(&dir)?;
+    if paths.is_empty() {
+        return Err(eyre::eyre!("No *-fixture.json files in {:?}", dir));
+    }
+
+    // One setup covers every fixture
+    let prover = ProverClient::builder().cpu().build().await;
+    let pk = prover
+        .setup(ELF)
+        .await
+        .map_err(|e| eyre::eyre!("Setup failed: {}", e))?;
+    let vkey = pk.verifying_key().bytes32();
+    if let Some(expected_vkey) = args.expected_vkey {
+        check_pinned_vkey(&vkey, expected_vkey)?;
+        println!("✅ Derived vkey matches the pinned vkey");
+    }
+    println!(
+        "Verifying {} fixtures in {:?} against vkey {}",
+        paths.len(),
+        dir,
+        vkey
+    );
+
+    let mut results = Vec::with_capacity(paths.len());
+    for path in &paths {
+        let name = path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+        let outcome = read_fixture_file(path)
+            .and_then(|fixture| verify_fixture_against_vkey(&fixture, &vkey, None));
+        results.push((name, outcome));
+    }
+
+    let rows: Vec<(&str, String)> = results
+        .iter()
+        .map(|(name, outcome)| {
+            let status = match outcome {
+                Ok(()) => "pass".to_string(),
+                Err(e) => format!("FAIL: {}", e),
+            };
+            (name.as_str(), status)
+        })
+        .collect();
+    println!("\n{}", format_table(&rows));
+
+    let failures = results
+        .iter()
+        .filter(|(_, outcome)| outcome.is_err())
+        .count();
+    if failures > 0 {
+        eprintln!(
+            "{} of {} fixtures failed verification",
+            failures,
+            results.len()
+        );
+        std::process::exit(1);
+    }
+    println!("✅ All {} fixtures verified", results.len());
+    Ok(())
+}
@@ -1,14 +1,64 @@
+use clap::{error::ErrorKind, CommandFactory, Parser};
+use eyre::Result;
 use sp1_sdk::{include_elf, Elf, HashableKey, Prover, ProverClient, ProvingKey};
+use tx_inclusion_precise_index::{write_vkey_file, VkeyFile, VkeyFormat};
 
 /// The ELF (executable and linkable format) file for the Succinct RISC-V zkVM.
 const ELF: Elf = include_elf!("tx-inclusion-precise-index-client");
 
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    #[arg(long, help = "Also write the vkey to this file")]
+    out: Option<std::path::PathBuf>,
+    #[arg(
+        long,
+        requires = "out",
+        help = "Encoding of --out: hex (the bare bytes32) or json; defaults to json with --full, hex otherwise"
+    )]
+    format: Option<VkeyFormat>,
+    #[arg(
+        long,
+        requires = "out",
+        help = "Also write the full serialized SP1VerifyingKey to --out as json"
+    )]
+    full: bool,
+}
+
+impl Args {
+    fn format(&self) -> VkeyFormat {
+        self.format.unwrap_or(if self.full {
+            VkeyFormat::Json
+        } else {
+            VkeyFormat::Hex
+        })
+    }
+}
+
 #[tokio::main]
-async fn main() {
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    // Rejected before setup, which takes a while
+    if args.full && args.format() == VkeyFormat::Hex {
+        Args::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "--full writes the full verifying key, which only --format json can hold",
+            )
+            .exit();
+    }
     let prover = ProverClient::builder().cpu().build().await;
     let pk = prover
         .setup(ELF)
         .await
         .expect("failed to derive proving key");
-    println!("{}", pk.verifying_key().bytes32());
+    let vk = pk.verifying_key();
+    println!("{}", vk.bytes32());
+
+    if let Some(path) = &args.out {
+        let file = VkeyFile::from_verifying_key(vk, args.full)?;
+        write_vkey_file(&file, path, args.format())?;
+        println!("Wrote the vkey to {:?}", path);
+    }
+    Ok(())
 }
@@ -1,14 +1,24 @@
 use eyre::Result;
 use serde::{Deserialize, Serialize};
-use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+use sp1_sdk::{
+    Elf, HashableKey, Prover, ProvingKey, SP1Proof, SP1ProofWithPublicValues, SP1VerifyingKey,
+};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::{keccak256, Address, B256};
 use alloy::providers::Provider;
+use alloy::signers::local::PrivateKeySigner;
 use alloy_rpc_types::{BlockId, BlockTransactions};
 use alloy_sol_types::SolType;
+use tx_inclusion_precise_index_lib::{
+    inclusion_input_at, verified_transaction_position, verified_transaction_type, BlockCache,
+    InclusionOutcome, ProofOptions, TransactionInclusionInput,
+};
 
 alloy_sol_types::sol! {
+    #[derive(Serialize, Deserialize)]
     struct PublicValuesStruct {
         bytes32 blockHash;
         uint64 blockNumber;
@@ -19,8 +29,83 @@ alloy_sol_types::sol! {
         bool transactionCanBeIncluded;
         bytes32 verifiedAgainstRoot;
     }
+
+    #[derive(Serialize, Deserialize)]
+    struct PublicValuesExtensionStruct {
+        uint32 headerRlpLength;
+        uint8 txType;
+        uint8 appendedSection;
+        bytes32 claimedTransactionsRoot;
+        uint256 effectiveGasPrice;
+        bool headerUnverified;
+        bool decodeFailed;
+        uint64 blockGasLimit;
+        uint64 blockGasUsed;
+        uint32 transactionSizeBytes;
+        uint64 slot;
+        uint64 beaconGenesisTime;
+        bool postMerge;
+        uint8 proofDepth;
+        bytes32 calldataHash;
+        bool provedInParent;
+        address accessListAddress;
+        bytes32 accessListStorageKey;
+        bool hasAccessListEntry;
+    }
+
+    struct AdjacentPublicValuesStruct {
+        bytes32 transactionHash;
+        bytes32 nextTransactionHash;
+        uint64 nextTransactionIndex;
+        bool areAdjacent;
+    }
+
+    struct SenderOrderingPublicValuesStruct {
+        address sender;
+        bytes32 firstTransactionHash;
+        uint64 firstIndex;
+        bytes32 secondTransactionHash;
+        uint64 secondIndex;
+        bool inOrder;
+    }
+
+    struct GasUsedPublicValuesStruct {
+        bytes32 transactionHash;
+        uint64 transactionIndex;
+        uint64 gasUsed;
+        bool transactionSucceeded;
+        bytes32 postStateRoot;
+    }
+
+    struct TransactionCountPublicValuesStruct {
+        bytes32 transactionHash;
+        uint64 transactionIndex;
+        uint64 transactionCount;
+        uint64 indexFromEnd;
+    }
+
+    struct CrossBlockPublicValuesStruct {
+        uint64 firstBlockNumber;
+        bytes32 firstTransactionHash;
+        uint64 secondBlockNumber;
+        bytes32 secondBlockHash;
+        bytes32 secondTransactionHash;
+        uint64 secondTransactionIndex;
+        bool aIsEarlierBlock;
+    }
 }
 
+/// `appendedSection` value for [`AdjacentPublicValuesStruct`].
+pub const APPENDED_SECTION_ADJACENT: u8 = 1;
+/// `appendedSection` value for [`SenderOrderingPublicValuesStruct`].
+pub const APPENDED_SECTION_SENDER_ORDERING: u8 = 2;
+/// `appendedSection` value for [`GasUsedPublicValuesStruct`].
+pub const APPENDED_SECTION_GAS_USED: u8 = 3;
+/// `appendedSection` value for [`TransactionCountPublicValuesStruct`].
+pub const APPENDED_SECTION_TRANSACTION_COUNT: u8 = 4;
+/// `appendedSection` value for [`CrossBlockPublicValuesStruct`].
+pub const APPENDED_SECTION_CROSS_BLOCK: u8 = 5;
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SP1TransactionInclusionProofFixture {
@@ -35,17 +120,176 @@ pub struct SP1TransactionInclusionProofFixture {
     pub vkey: String,
     pub public_values: String,
     pub proof: String,
+    /// Operator's 65-byte `r || s || v` signature over `keccak256(public_values)`, from
+    /// [`sign_public_values`]; absent unless the prover signed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub operator_signature: Option<String>,
 }
 
 pub fn decode_public_values(bytes: &[u8]) -> Result<PublicValuesStruct> {
     Ok(PublicValuesStruct::abi_decode(bytes)?)
 }
 
+/// Public values both as committed and decoded, for consumers that want one or the other: the
+/// contract takes `abiPublicValues` as is, an indexer reads `decoded`.
+pub fn public_values_json(bytes: &[u8]) -> Result<serde_json::Value> {
+    let mut decoded = serde_json::to_value(decode_public_values(bytes)?)?;
+    if let Some(extension) = decode_public_values_extension(bytes)? {
+        decoded["extension"] = serde_json::to_value(extension)?;
+    }
+    Ok(serde_json::json!({
+        "abiPublicValues": format!("0x{}", hex::encode(bytes)),
+        "decoded": decoded,
+    }))
+}
+
+/// A plain-English verdict on what committed public values establish, applying the proof-shape
+/// checks of `TxInclusionPreciseSlasher.slash`. The commitment signature, bond, slashing window and
+/// registered canonical block hash are checked on-chain and are not known here.
+pub fn explain_slashing(bytes: &[u8]) -> Result<String> {
+    let values = decode_public_values(bytes)?;
+    let block = format!("block {} (hash {:?})", values.blockNumber, values.blockHash);
+    let promised = values.committedTransactionHash;
+    let index = values.transactionIndex;
+
+    if decode_public_values_extension(bytes)?.is_some_and(|extension| extension.headerUnverified) {
+        return Ok(format!(
+            "The proof used a partial header, so nothing binds it to a canonical block — NOT SLASHABLE; promised transaction {:?} at index {} of block {}",
+            promised, index, values.blockNumber
+        ));
+    }
+    if !values.transactionCanBeIncluded {
+        return Ok(format!(
+            "Transaction {:?} could not be included at the start of {}, so missing index {} is excused — NOT SLASHABLE",
+            promised, block, index
+        ));
+    }
+    Ok(
+        match (values.isIncluded, values.transactionHash == B256::ZERO) {
+            (true, false) if values.transactionHash == promised => format!(
+                "Transaction {:?} is included at index {} of {}, which MATCHES the promised index — no slashing",
+                promised, index, block
+            ),
+            (true, false) => format!(
+                "Transaction {:?} is at index {} of {}, but {:?} was promised there — SLASHABLE",
+                values.transactionHash, index, block, promised
+            ),
+            (false, true) => format!(
+                "No transaction is at index {} of {}, but {:?} was promised there — SLASHABLE",
+                index, block, promised
+            ),
+            _ => format!(
+                "The proof for index {} of {} did not verify, so it establishes nothing — NOT SLASHABLE",
+                index, block
+            ),
+        },
+    )
+}
+
+/// Decode the extension values appended after [`PublicValuesStruct`]; `None` for proofs that predate them.
+pub fn decode_public_values_extension(bytes: &[u8]) -> Result<Option<PublicValuesExtensionStruct>> {
+    let offset = <PublicValuesStruct as SolType>::ENCODED_SIZE
+        .expect("PublicValuesStruct is statically sized");
+    if bytes.len() <= offset {
+        return Ok(None);
+    }
+    Ok(Some(PublicValuesExtensionStruct::abi_decode(
+        &bytes[offset..],
+    )?))
+}
+
+/// Decode the adjacency values appended after [`PublicValuesExtensionStruct`], if the proof carries them.
+pub fn decode_adjacent_public_values(bytes: &[u8]) -> Result<Option<AdjacentPublicValuesStruct>> {
+    decode_appended_section(bytes, APPENDED_SECTION_ADJACENT)
+}
+
+/// Decode the sender-ordering values appended after [`PublicValuesExtensionStruct`], if the proof carries them.
+pub fn decode_sender_ordering_public_values(
+    bytes: &[u8],
+) -> Result<Option<SenderOrderingPublicValuesStruct>> {
+    decode_appended_section(bytes, APPENDED_SECTION_SENDER_ORDERING)
+}
+
+/// Decode the gas-used values appended after [`PublicValuesExtensionStruct`], if the proof carries them.
+pub fn decode_gas_used_public_values(bytes: &[u8]) -> Result<Option<GasUsedPublicValuesStruct>> {
+    decode_appended_section(bytes, APPENDED_SECTION_GAS_USED)
+}
+
+/// Decode the transaction-count values appended after [`PublicValuesExtensionStruct`], if the proof carries them.
+pub fn decode_transaction_count_public_values(
+    bytes: &[u8],
+) -> Result<Option<TransactionCountPublicValuesStruct>> {
+    decode_appended_section(bytes, APPENDED_SECTION_TRANSACTION_COUNT)
+}
+
+/// Decode the cross-block ordering values appended after [`PublicValuesExtensionStruct`], if the proof carries them.
+pub fn decode_cross_block_public_values(
+    bytes: &[u8],
+) -> Result<Option<CrossBlockPublicValuesStruct>> {
+    decode_appended_section(bytes, APPENDED_SECTION_CROSS_BLOCK)
+}
+
+fn decode_appended_section<T: SolType>(bytes: &[u8], section: u8) -> Result<Option<T::RustType>> {
+    match decode_public_values_extension(bytes)? {
+        Some(extension) if extension.appendedSection == section => {}
+        _ => return Ok(None),
+    }
+    let offset = <PublicValuesStruct as SolType>::ENCODED_SIZE
+        .and_then(|base| Some(base + <PublicValuesExtensionStruct as SolType>::ENCODED_SIZE?))
+        .expect("public values structs are statically sized");
+    Ok(Some(T::abi_decode(&bytes[offset..])?))
+}
+
+/// Map decoded public values to their [`InclusionOutcome`].
+pub fn inclusion_outcome(public_values: &PublicValuesStruct) -> InclusionOutcome {
+    InclusionOutcome::from_committed(
+        public_values.transactionHash,
+        public_values.transactionIndex,
+        public_values.isIncluded,
+    )
+}
+
+/// Require the vkey derived from the ELF (`vk.bytes32()`) to equal the pinned `expected_vkey`, so a
+/// tampered ELF cannot stand in for the deployed program.
+pub fn check_pinned_vkey(derived_vkey: &str, expected_vkey: B256) -> Result<()> {
+    let derived: B256 = derived_vkey
+        .parse()
+        .map_err(|e| eyre::eyre!("Derived vkey {} is not bytes32: {}", derived_vkey, e))?;
+    if derived != expected_vkey {
+        return Err(eyre::eyre!(
+            "Derived vkey {:?} does not match the pinned vkey {:?}; refusing to verify against this ELF",
+            derived,
+            expected_vkey
+        ));
+    }
+    Ok(())
+}
+
+/// Verify an SP1 proof off-chain against `vk` and interpret its committed public values.
+pub fn verify_inclusion_proof(
+    prover: &impl Prover,
+    proof: &SP1ProofWithPublicValues,
+    vk: &SP1VerifyingKey,
+) -> Result<InclusionOutcome> {
+    prover
+        .verify(proof, vk, None)
+        .map_err(|e| eyre::eyre!("Proof verification failed: {}", e))?;
+    let public_values = decode_public_values(proof.public_values.as_slice())?;
+    Ok(inclusion_outcome(&public_values))
+}
+
 pub fn fixture_from_proof(
     proof: &SP1ProofWithPublicValues,
     vk: &SP1VerifyingKey,
 ) -> Result<SP1TransactionInclusionProofFixture> {
-    let bytes = proof.public_values.as_slice();
+    fixture_from_parts(proof.public_values.as_slice(), vk.bytes32(), &proof.bytes())
+}
+
+fn fixture_from_parts(
+    bytes: &[u8],
+    vkey: String,
+    proof: &[u8],
+) -> Result<SP1TransactionInclusionProofFixture> {
     let decoded = decode_public_values(bytes)?;
 
     Ok(SP1TransactionInclusionProofFixture {
@@ -60,24 +304,368 @@ pub fn fixture_from_proof(
         is_included: decoded.isIncluded,
         transaction_can_be_included: decoded.transactionCanBeIncluded,
         verified_against_root: format!("0x{}", hex::encode(decoded.verifiedAgainstRoot.as_slice())),
-        vkey: vk.bytes32().to_string(),
+        vkey,
         public_values: format!("0x{}", hex::encode(bytes)),
-        proof: format!("0x{}", hex::encode(proof.bytes())),
+        proof: format!("0x{}", hex::encode(proof)),
+        operator_signature: None,
     })
 }
 
+/// Sign `keccak256` of the fixture's ABI public values with an operator key, so an aggregator can
+/// attribute the proof to the prover that made it. This is an attestation by the host, not part of
+/// what the proof establishes.
+pub fn sign_public_values(
+    fixture: &mut SP1TransactionInclusionProofFixture,
+    signer: &PrivateKeySigner,
+) -> Result<()> {
+    use alloy::signers::SignerSync;
+
+    let digest = keccak256(decode_fixture_hex("publicValues", &fixture.public_values)?);
+    let signature = signer.sign_hash_sync(&digest)?;
+    fixture.operator_signature = Some(format!("0x{}", hex::encode(signature.as_bytes())));
+    Ok(())
+}
+
+/// Address whose key produced the fixture's `operator_signature`.
+pub fn public_values_signer(fixture: &SP1TransactionInclusionProofFixture) -> Result<Address> {
+    let signature = fixture
+        .operator_signature
+        .as_deref()
+        .ok_or_else(|| eyre::eyre!("Fixture has no operatorSignature"))?;
+    let signature = alloy::primitives::Signature::from_raw(&decode_fixture_hex(
+        "operatorSignature",
+        signature,
+    )?)
+    .map_err(|e| eyre::eyre!("operatorSignature is not a valid signature: {}", e))?;
+    let digest = keccak256(decode_fixture_hex("publicValues", &fixture.public_values)?);
+    Ok(signature.recover_address_from_prehash(&digest)?)
+}
+
+/// Check the fixture's public values were signed by `operator`.
+pub fn verify_public_values_signature(
+    fixture: &SP1TransactionInclusionProofFixture,
+    operator: Address,
+) -> Result<()> {
+    let signer = public_values_signer(fixture)?;
+    if signer != operator {
+        return Err(eyre::eyre!(
+            "Public values were signed by {}, not the expected operator {}",
+            signer,
+            operator
+        ));
+    }
+    Ok(())
+}
+
+fn decode_fixture_hex(field: &str, value: &str) -> Result<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x"))
+        .map_err(|e| eyre::eyre!("Fixture {} is not hex: {}", field, e))
+}
+
+/// Parse a fixture's `0x`-prefixed 32-byte hex field, naming the field and what was wrong with it.
+pub fn parse_fixture_bytes32(field: &str, value: &str) -> Result<B256> {
+    let digits = value
+        .strip_prefix("0x")
+        .ok_or_else(|| eyre::eyre!("Fixture {} must start with 0x, got {:?}", field, value))?;
+    let bytes =
+        hex::decode(digits).map_err(|e| eyre::eyre!("Fixture {} is not hex: {}", field, e))?;
+    let length = bytes.len();
+    <[u8; 32]>::try_from(bytes)
+        .map(B256::from)
+        .map_err(|_| eyre::eyre!("Fixture {} must be 32 bytes, got {} bytes", field, length))
+}
+
+/// Require a fixture's top-level fields to be exactly the ones its `publicValues` commit, so an
+/// edited field cannot disagree with what the proof attests.
+pub fn check_fixture_consistency(fixture: &SP1TransactionInclusionProofFixture) -> Result<()> {
+    // Malformed hand edits get a field-level error before the whole-fixture comparison
+    for (field, value) in [
+        ("blockHash", &fixture.block_hash),
+        (
+            "committedTransactionHash",
+            &fixture.committed_transaction_hash,
+        ),
+        ("transactionHash", &fixture.transaction_hash),
+        ("verifiedAgainstRoot", &fixture.verified_against_root),
+        ("vkey", &fixture.vkey),
+    ] {
+        parse_fixture_bytes32(field, value)?;
+    }
+    let public_values = decode_fixture_hex("publicValues", &fixture.public_values)?;
+    let proof = decode_fixture_hex("proof", &fixture.proof)?;
+    let mut rebuilt = fixture_from_parts(&public_values, fixture.vkey.clone(), &proof)?;
+    // The signature is not derived from the public values; it only has to recover to some signer
+    if fixture.operator_signature.is_some() {
+        public_values_signer(fixture)?;
+        rebuilt.operator_signature = fixture.operator_signature.clone();
+    }
+    if &rebuilt != fixture {
+        return Err(eyre::eyre!(
+            "Fixture fields do not match its public values; expected {:?}",
+            rebuilt
+        ));
+    }
+    Ok(())
+}
+
+/// Check a fixture end to end without a chain. The vkey derived from `elf` must equal
+/// `expected_vkey` when one is pinned and the fixture's, its fields must match its public values,
+/// and its Groth16 proof must verify against them the way the on-chain verifier checks it.
+///
+/// A fixture keeps only the on-chain proof bytes, not the full [`SP1ProofWithPublicValues`], so
+/// the proof is checked with the SDK's Groth16 verifier rather than `Prover::verify`.
+pub async fn verify_fixture(
+    prover: &impl Prover,
+    fixture: &SP1TransactionInclusionProofFixture,
+    elf: Elf,
+    expected_vkey: Option<B256>,
+) -> Result<()> {
+    let pk = prover
+        .setup(elf)
+        .await
+        .map_err(|e| eyre::eyre!("Setup failed: {}", e))?;
+    verify_fixture_against_vkey(fixture, &pk.verifying_key().bytes32(), expected_vkey)
+}
+
+/// [`verify_fixture`] with the vkey already derived from the ELF, so a batch needs one setup.
+pub fn verify_fixture_against_vkey(
+    fixture: &SP1TransactionInclusionProofFixture,
+    derived_vkey: &str,
+    expected_vkey: Option<B256>,
+) -> Result<()> {
+    if let Some(expected_vkey) = expected_vkey {
+        check_pinned_vkey(derived_vkey, expected_vkey)?;
+    }
+    let fixture_vkey = parse_fixture_bytes32("vkey", &fixture.vkey)?;
+    check_pinned_vkey(derived_vkey, fixture_vkey)?;
+    check_fixture_consistency(fixture)?;
+
+    let public_values = decode_fixture_hex("publicValues", &fixture.public_values)?;
+    let proof = decode_fixture_hex("proof", &fixture.proof)?;
+    sp1_verifier::Groth16Verifier::verify(
+        &proof,
+        &public_values,
+        &fixture.vkey,
+        &sp1_verifier::GROTH16_VK_BYTES,
+    )
+    .map_err(|e| eyre::eyre!("Fixture proof does not verify: {}", e))
+}
+
+/// Encoding of a proof bundle, the [`SP1TransactionInclusionProofFixture`] holding the vkey, public
+/// values and proof bytes.
+///
+/// JSON is what Foundry and the fixture tools read; CBOR is for embedded verifiers that cannot parse
+/// JSON efficiently. Both carry the same fields. MessagePack is not supported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BundleFormat {
+    #[default]
+    Json,
+    Cbor,
+}
+
+impl BundleFormat {
+    pub fn encode(self, fixture: &SP1TransactionInclusionProofFixture) -> Result<Vec<u8>> {
+        match self {
+            BundleFormat::Json => Ok(serde_json::to_vec_pretty(fixture)?),
+            BundleFormat::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::into_writer(fixture, &mut bytes)?;
+                Ok(bytes)
+            }
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> Result<SP1TransactionInclusionProofFixture> {
+        match self {
+            BundleFormat::Json => Ok(serde_json::from_slice(bytes)?),
+            BundleFormat::Cbor => Ok(ciborium::from_reader(bytes)?),
+        }
+    }
+
+    /// File extension for bundles in this format.
+    pub fn extension(self) -> &'static str {
+        match self {
+            BundleFormat::Json => "json",
+            BundleFormat::Cbor => "cbor",
+        }
+    }
+}
+
+impl std::str::FromStr for BundleFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "json" => Ok(BundleFormat::Json),
+            "cbor" => Ok(BundleFormat::Cbor),
+            _ => Err(eyre::eyre!(
+                "Unknown bundle format: {} (expected: json, cbor)",
+                s
+            )),
+        }
+    }
+}
+
 pub fn write_fixture_file(
     fixture: &SP1TransactionInclusionProofFixture,
     path: impl AsRef<Path>,
 ) -> Result<()> {
-    std::fs::write(path, serde_json::to_string_pretty(fixture)?)?;
+    write_bundle_file(fixture, path, BundleFormat::Json)
+}
+
+pub fn write_bundle_file(
+    fixture: &SP1TransactionInclusionProofFixture,
+    path: impl AsRef<Path>,
+    format: BundleFormat,
+) -> Result<()> {
+    std::fs::write(path, format.encode(fixture)?)?;
     Ok(())
 }
 
+pub fn read_fixture_file(path: impl AsRef<Path>) -> Result<SP1TransactionInclusionProofFixture> {
+    let path = path.as_ref();
+    let json = std::fs::read_to_string(path)
+        .map_err(|e| eyre::eyre!("Failed to read fixture {:?}: {}", path, e))?;
+    serde_json::from_str(&json).map_err(|e| eyre::eyre!("Fixture {:?} is not valid: {}", path, e))
+}
+
+/// The program vkey as `vkey --out` writes it for deployment tooling.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VkeyFile {
+    /// `vk.bytes32()`, the value contracts pin.
+    pub vkey: String,
+    /// The full serialized `SP1VerifyingKey`, when asked for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verifying_key: Option<serde_json::Value>,
+}
+
+impl VkeyFile {
+    pub fn from_verifying_key(vk: &SP1VerifyingKey, full: bool) -> Result<Self> {
+        Ok(VkeyFile {
+            vkey: vk.bytes32(),
+            verifying_key: full.then(|| serde_json::to_value(vk)).transpose()?,
+        })
+    }
+
+    /// The vkey as bytes32, checked to be well-formed.
+    pub fn bytes32(&self) -> Result<B256> {
+        parse_fixture_bytes32("vkey", &self.vkey)
+    }
+}
+
+/// Encoding of a [`VkeyFile`].
+///
+/// Hex is the bare bytes32 on one line, for scripts that read it verbatim; JSON also carries the
+/// full verifying key when one is included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VkeyFormat {
+    #[default]
+    Hex,
+    Json,
+}
+
+impl VkeyFormat {
+    pub fn encode(self, file: &VkeyFile) -> Result<Vec<u8>> {
+        file.bytes32()?;
+        match self {
+            VkeyFormat::Hex if file.verifying_key.is_some() => Err(eyre::eyre!(
+                "The full verifying key can only be written as json"
+            )),
+            VkeyFormat::Hex => Ok(format!("{}\n", file.vkey).into_bytes()),
+            VkeyFormat::Json => Ok(serde_json::to_vec_pretty(file)?),
+        }
+    }
+
+    pub fn decode(self, bytes: &[u8]) -> Result<VkeyFile> {
+        let file = match self {
+            VkeyFormat::Hex => VkeyFile {
+                vkey: std::str::from_utf8(bytes)?.trim().to_string(),
+                verifying_key: None,
+            },
+            VkeyFormat::Json => serde_json::from_slice(bytes)?,
+        };
+        file.bytes32()?;
+        Ok(file)
+    }
+}
+
+impl std::str::FromStr for VkeyFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "hex" => Ok(VkeyFormat::Hex),
+            "json" => Ok(VkeyFormat::Json),
+            _ => Err(eyre::eyre!(
+                "Unknown vkey format: {} (expected: hex, json)",
+                s
+            )),
+        }
+    }
+}
+
+pub fn write_vkey_file(file: &VkeyFile, path: impl AsRef<Path>, format: VkeyFormat) -> Result<()> {
+    std::fs::write(path, format.encode(file)?)?;
+    Ok(())
+}
+
+pub fn read_vkey_file(path: impl AsRef<Path>, format: VkeyFormat) -> Result<VkeyFile> {
+    let path = path.as_ref();
+    let bytes =
+        std::fs::read(path).map_err(|e| eyre::eyre!("Failed to read vkey {:?}: {}", path, e))?;
+    format
+        .decode(&bytes)
+        .map_err(|e| eyre::eyre!("Vkey {:?} is not valid: {}", path, e))
+}
+
+/// Every `*-fixture.json` directly under `dir`, in name order.
+pub fn fixture_paths_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)
+        .map_err(|e| eyre::eyre!("Failed to read fixture directory {:?}: {}", dir, e))?
+    {
+        let path = entry?.path();
+        let is_fixture = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with("-fixture.json"));
+        if is_fixture && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Where `evm` writes fixtures by default and the contract tests read them.
+pub fn default_fixture_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../contracts/src/fixtures")
+}
+
 pub fn default_fixture_output_path(system_name: &str) -> PathBuf {
-    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-        .join("../contracts/src/fixtures")
-        .join(format!("{system_name}-fixture.json"))
+    default_fixture_dir().join(format!("{system_name}-fixture.json"))
+}
+
+/// Default fixture path for a program version, named `{system}-{vkey8}-fixture.json` after the first
+/// eight hex digits of `vkey` so a fixture for an older ELF is never mistaken for the current one.
+pub fn vkey_fixture_output_path(system_name: &str, vkey: &str) -> PathBuf {
+    let vkey_hex = vkey.strip_prefix("0x").unwrap_or(vkey);
+    let vkey_prefix = vkey_hex.get(..8).unwrap_or(vkey_hex);
+    default_fixture_dir().join(format!("{system_name}-{vkey_prefix}-fixture.json"))
+}
+
+/// Path of the fixture for one transaction under `output_dir`, named `{block_number}-{tx_index}-{system}.json`
+/// so bulk jobs writing to the same directory do not overwrite each other.
+pub fn organized_fixture_output_path(
+    output_dir: &Path,
+    block_number: u64,
+    transaction_index: u64,
+    system_name: &str,
+) -> PathBuf {
+    output_dir.join(format!(
+        "{block_number}-{transaction_index}-{system_name}.json"
+    ))
 }
 
 pub fn load_repo_dotenv() {
@@ -140,9 +728,264 @@ pub async fn select_first_transaction_from_recent_finalized_block(
     })
 }
 
+/// Build the guest input proving that the mined transaction `transaction_hash` sits at its own index.
+///
+/// This is the plain inclusion input `local --execute` builds for `--transaction-hash`.
+pub async fn inclusion_input_for_transaction(
+    provider: &impl Provider,
+    transaction_hash: B256,
+    options: &ProofOptions,
+) -> Result<TransactionInclusionInput> {
+    let tx = provider
+        .get_transaction_by_hash(transaction_hash)
+        .await?
+        .ok_or_else(|| eyre::eyre!("Transaction not found: {:?}", transaction_hash))?;
+    let (block_number, tx_index) = verified_transaction_position(provider, &tx).await?;
+
+    inclusion_input_at(
+        provider,
+        &BlockCache::new(),
+        block_number,
+        tx_index,
+        options,
+    )
+    .await
+}
+
+/// Min/max/average guest cycle counts across several executions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleSummary {
+    pub runs: usize,
+    pub min: u64,
+    pub max: u64,
+    pub avg: u64,
+}
+
+impl CycleSummary {
+    /// Summarise `cycles`; `None` when there were no runs.
+    pub fn from_counts(cycles: &[u64]) -> Option<Self> {
+        let min = *cycles.iter().min()?;
+        let max = *cycles.iter().max()?;
+        let total: u128 = cycles.iter().map(|&c| c as u128).sum();
+        Some(Self {
+            runs: cycles.len(),
+            min,
+            max,
+            avg: (total / cycles.len() as u128) as u64,
+        })
+    }
+
+    /// Whether any run used more than `max_cycles`.
+    pub fn exceeds(&self, max_cycles: u64) -> bool {
+        self.max > max_cycles
+    }
+}
+
+/// Input shape and cycle count of one `--count-cycles-only` execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CycleSample {
+    /// EIP-2718 type of the proved transaction.
+    pub tx_type: u8,
+    /// Nodes in the transactions-trie proof, as returned in the proof bundle.
+    pub proof_nodes: usize,
+    /// Total encoded size of those nodes.
+    pub proof_bytes: usize,
+    pub cycles: u64,
+}
+
+impl CycleSample {
+    pub fn from_input(input: &TransactionInclusionInput, cycles: u64) -> Result<Self> {
+        Ok(Self {
+            tx_type: verified_transaction_type(&input.raw_transaction)?,
+            proof_nodes: input.merkle_proof.len(),
+            proof_bytes: input.merkle_proof.iter().map(|node| node.len()).sum(),
+            cycles,
+        })
+    }
+}
+
+/// Aggregate samples into CSV, one row per `(tx_type, proof_nodes)` in ascending order, so cycle
+/// cost can be modelled from input shape without running the guest.
+pub fn cycle_samples_csv(samples: &[CycleSample]) -> String {
+    let mut groups: std::collections::BTreeMap<(u8, usize), Vec<&CycleSample>> = Default::default();
+    for sample in samples {
+        groups
+            .entry((sample.tx_type, sample.proof_nodes))
+            .or_default()
+            .push(sample);
+    }
+
+    let mut csv =
+        String::from("tx_type,proof_nodes,runs,avg_proof_bytes,min_cycles,max_cycles,avg_cycles\n");
+    for ((tx_type, proof_nodes), group) in groups {
+        let cycles: Vec<u64> = group.iter().map(|sample| sample.cycles).collect();
+        let summary = CycleSummary::from_counts(&cycles).expect("groups are never empty");
+        let avg_proof_bytes =
+            group.iter().map(|sample| sample.proof_bytes).sum::<usize>() / group.len();
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            tx_type,
+            proof_nodes,
+            summary.runs,
+            avg_proof_bytes,
+            summary.min,
+            summary.max,
+            summary.avg
+        ));
+    }
+    csv
+}
+
+/// The fields printed in the table that closes a proving run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunSummary {
+    pub block_number: u64,
+    pub transaction_index: u64,
+    pub outcome: InclusionOutcome,
+    pub proof_system: String,
+    pub proof_size: usize,
+    pub vkey: String,
+}
+
+impl RunSummary {
+    pub fn from_proof(proof: &SP1ProofWithPublicValues, vk: &SP1VerifyingKey) -> Result<Self> {
+        let public_values = decode_public_values(proof.public_values.as_slice())?;
+        // Only on-chain proof systems have a byte encoding; size the others by their serialisation
+        let proof_size = match &proof.proof {
+            SP1Proof::Plonk(_) | SP1Proof::Groth16(_) => proof.bytes().len(),
+            other => bincode::serialize(other)?.len(),
+        };
+
+        Ok(Self {
+            block_number: public_values.blockNumber,
+            transaction_index: public_values.transactionIndex,
+            outcome: inclusion_outcome(&public_values),
+            proof_system: proof.proof.to_string(),
+            proof_size,
+            vkey: vk.bytes32(),
+        })
+    }
+
+    pub fn table(&self) -> String {
+        let outcome = match &self.outcome {
+            InclusionOutcome::IncludedAt(index) => format!("included at index {}", index),
+            InclusionOutcome::ExcludedProven => "excluded (proven)".to_string(),
+            InclusionOutcome::Indeterminate(reason) => format!("indeterminate: {}", reason),
+        };
+        format_table(&[
+            ("Block number", self.block_number.to_string()),
+            ("Tx index", self.transaction_index.to_string()),
+            ("Inclusion result", outcome),
+            ("Proof system", self.proof_system.clone()),
+            ("Proof size", format!("{} bytes", self.proof_size)),
+            ("Verification key", self.vkey.clone()),
+        ])
+    }
+}
+
+/// Wall-clock time of one phase of a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub millis: u64,
+}
+
+/// Wall-clock breakdown of a run, taken as laps so each phase starts where the previous one ended.
+#[derive(Debug, Clone)]
+pub struct PhaseTimings {
+    pub phases: Vec<PhaseTiming>,
+    lap_started: Instant,
+}
+
+impl PhaseTimings {
+    /// Start the first lap now.
+    pub fn start() -> Self {
+        Self {
+            phases: Vec::new(),
+            lap_started: Instant::now(),
+        }
+    }
+
+    /// Record the time since the previous lap as `phase` and start the next lap.
+    pub fn lap(&mut self, phase: &str) {
+        let now = Instant::now();
+        self.record(phase, now - self.lap_started);
+        self.lap_started = now;
+    }
+
+    pub fn record(&mut self, phase: &str, elapsed: Duration) {
+        self.phases.push(PhaseTiming {
+            phase: phase.to_string(),
+            millis: elapsed.as_millis() as u64,
+        });
+    }
+
+    pub fn total_millis(&self) -> u64 {
+        self.phases.iter().map(|timing| timing.millis).sum()
+    }
+
+    pub fn table(&self) -> String {
+        let mut rows: Vec<(&str, String)> = self
+            .phases
+            .iter()
+            .map(|timing| (timing.phase.as_str(), format!("{} ms", timing.millis)))
+            .collect();
+        rows.push(("Total", format!("{} ms", self.total_millis())));
+        format_table(&rows)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(&serde_json::json!({
+            "phases": self.phases,
+            "totalMillis": self.total_millis(),
+        }))?)
+    }
+
+    /// Print the breakdown that closes a run, followed by its JSON line when `json` is set.
+    pub fn print(&self, json: bool) -> Result<()> {
+        println!("\n=== TIMING ===\n{}", self.table());
+        if json {
+            println!("{}", self.to_json()?);
+        }
+        Ok(())
+    }
+}
+
+/// Render `(label, value)` rows as a bordered two-column table with aligned columns.
+pub fn format_table(rows: &[(&str, String)]) -> String {
+    let label_width = rows
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let value_width = rows
+        .iter()
+        .map(|(_, value)| value.chars().count())
+        .max()
+        .unwrap_or(0);
+    let border = format!(
+        "+-{}-+-{}-+",
+        "-".repeat(label_width),
+        "-".repeat(value_width)
+    );
+
+    let mut table = border.clone();
+    for (label, value) in rows {
+        table.push_str(&format!(
+            "\n| {:<label_width$} | {:<value_width$} |",
+            label, value
+        ));
+    }
+    table.push('\n');
+    table.push_str(&border);
+    table
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloy::primitives::{Address, U256};
 
     fn load_fixture(name: &str) -> SP1TransactionInclusionProofFixture {
         let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -152,6 +995,102 @@ mod tests {
         serde_json::from_str(&json).expect("fixture json should decode")
     }
 
+    #[test]
+    fn explanations_follow_the_slasher_proof_shapes() {
+        let promised = B256::repeat_byte(1);
+        let explain = |transaction_hash: B256, is_included: bool, can_be_included: bool| {
+            explain_slashing(&PublicValuesStruct::abi_encode(&PublicValuesStruct {
+                blockHash: B256::repeat_byte(3),
+                blockNumber: 12_345,
+                committedTransactionHash: promised,
+                transactionHash: transaction_hash,
+                transactionIndex: 5,
+                isIncluded: is_included,
+                transactionCanBeIncluded: can_be_included,
+                verifiedAgainstRoot: B256::repeat_byte(4),
+            }))
+            .unwrap()
+        };
+
+        let matched = explain(promised, true, true);
+        assert!(matched.contains("index 5 of block 12345"), "{}", matched);
+        assert!(matched.ends_with("MATCHES the promised index — no slashing"));
+        assert!(explain(B256::repeat_byte(2), true, true).ends_with("— SLASHABLE"));
+        assert!(explain(B256::ZERO, false, true).starts_with("No transaction is at index 5"));
+        assert!(explain(B256::repeat_byte(2), true, false).ends_with("NOT SLASHABLE"));
+        assert!(explain(promised, false, true).contains("did not verify"));
+    }
+
+    #[test]
+    fn signed_public_values_recover_to_the_operator() {
+        let mut fixture = load_fixture("groth16-fixture-for-tests.json");
+        assert!(public_values_signer(&fixture).is_err());
+
+        let operator = PrivateKeySigner::random();
+        sign_public_values(&mut fixture, &operator).unwrap();
+        assert_eq!(public_values_signer(&fixture).unwrap(), operator.address());
+        verify_public_values_signature(&fixture, operator.address()).unwrap();
+        assert!(verify_public_values_signature(&fixture, Address::repeat_byte(1)).is_err());
+
+        let signed = BundleFormat::Json.encode(&fixture).unwrap();
+        assert_eq!(BundleFormat::Json.decode(&signed).unwrap(), fixture);
+
+        // The signature covers the public values, so changing them changes the recovered signer
+        let mut tampered = fixture.clone();
+        tampered.public_values.push_str("00");
+        assert!(verify_public_values_signature(&tampered, operator.address()).is_err());
+    }
+
+    #[test]
+    fn bundles_round_trip_in_every_format() {
+        let fixture = load_fixture("groth16-fixture-for-tests.json");
+        for format in [BundleFormat::Json, BundleFormat::Cbor] {
+            let bytes = format.encode(&fixture).unwrap();
+            assert_eq!(format.decode(&bytes).unwrap(), fixture, "{:?}", format);
+        }
+
+        let cbor = BundleFormat::Cbor.encode(&fixture).unwrap();
+        assert!(BundleFormat::Json.decode(&cbor).is_err());
+        assert_eq!("cbor".parse::<BundleFormat>().unwrap(), BundleFormat::Cbor);
+        assert!("yaml".parse::<BundleFormat>().is_err());
+    }
+
+    #[test]
+    fn written_vkey_files_parse_back_to_the_same_vkey() {
+        let vkey = load_fixture("groth16-fixture-for-tests.json").vkey;
+        let dir = std::env::temp_dir().join(format!("vkey-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let file = VkeyFile {
+            vkey: vkey.clone(),
+            verifying_key: None,
+        };
+        for format in [VkeyFormat::Hex, VkeyFormat::Json] {
+            let path = dir.join(format!("vkey-{:?}", format));
+            write_vkey_file(&file, &path, format).unwrap();
+            let read = read_vkey_file(&path, format).unwrap();
+            assert_eq!(read, file, "{:?}", format);
+            assert_eq!(read.bytes32().unwrap(), file.bytes32().unwrap());
+        }
+        assert_eq!(
+            std::fs::read_to_string(dir.join("vkey-Hex")).unwrap(),
+            format!("{}\n", vkey)
+        );
+
+        // Only json carries the full key
+        let full = VkeyFile {
+            vkey,
+            verifying_key: Some(serde_json::json!({ "vk": [1, 2, 3] })),
+        };
+        assert!(VkeyFormat::Hex.encode(&full).is_err());
+        let json = VkeyFormat::Json.encode(&full).unwrap();
+        assert_eq!(VkeyFormat::Json.decode(&json).unwrap(), full);
+
+        assert!(VkeyFormat::Hex.decode(b"0x1234").is_err());
+        assert_eq!("json".parse::<VkeyFormat>().unwrap(), VkeyFormat::Json);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     fn decode_fixture_public_values(
         fixture: &SP1TransactionInclusionProofFixture,
     ) -> PublicValuesStruct {
@@ -160,6 +1099,386 @@ mod tests {
         decode_public_values(&bytes).expect("public values should decode")
     }
 
+    #[test]
+    fn cycle_samples_csv_groups_by_type_and_proof_nodes() {
+        let sample = |tx_type, proof_nodes, proof_bytes, cycles| CycleSample {
+            tx_type,
+            proof_nodes,
+            proof_bytes,
+            cycles,
+        };
+        let csv = cycle_samples_csv(&[
+            sample(2, 3, 600, 1_200),
+            sample(0, 3, 500, 1_000),
+            sample(2, 3, 700, 1_400),
+            sample(2, 2, 400, 900),
+        ]);
+        assert_eq!(
+            csv,
+            "tx_type,proof_nodes,runs,avg_proof_bytes,min_cycles,max_cycles,avg_cycles\n\
+             0,3,1,500,1000,1000,1000\n\
+             2,2,1,400,900,900,900\n\
+             2,3,2,650,1200,1400,1300\n"
+        );
+    }
+
+    #[test]
+    fn pinned_vkey_must_match_derived_vkey() {
+        let derived = format!("0x{}", "ab".repeat(32));
+        check_pinned_vkey(&derived, B256::repeat_byte(0xab)).expect("matching vkey should pass");
+
+        let err = check_pinned_vkey(&derived, B256::repeat_byte(0xcd)).unwrap_err();
+        assert!(err.to_string().contains("does not match the pinned vkey"));
+    }
+
+    #[test]
+    fn fixture_verification_checks_the_pinned_vkey() {
+        let fixture = load_fixture("groth16-fixture-for-tests.json");
+        let vkey = parse_fixture_bytes32("vkey", &fixture.vkey).unwrap();
+
+        let err =
+            verify_fixture_against_vkey(&fixture, &fixture.vkey, Some(B256::repeat_byte(0xcd)))
+                .unwrap_err();
+        assert!(err.to_string().contains("does not match the pinned vkey"));
+
+        // Pinning the vkey the ELF derives leaves the rest of the verification unchanged
+        let unpinned =
+            verify_fixture_against_vkey(&fixture, &fixture.vkey, None).map_err(|e| e.to_string());
+        let pinned = verify_fixture_against_vkey(&fixture, &fixture.vkey, Some(vkey))
+            .map_err(|e| e.to_string());
+        assert_eq!(pinned, unpinned);
+    }
+
+    #[test]
+    fn organized_fixture_path_names_block_index_and_system() {
+        let path = organized_fixture_output_path(Path::new("fixtures"), 21_000_000, 42, "groth16");
+        assert_eq!(path, PathBuf::from("fixtures/21000000-42-groth16.json"));
+    }
+
+    #[test]
+    fn vkey_fixture_path_names_the_vkey_prefix() {
+        let vkey = "0x00a1b2c3d4e5f60718293a4b5c6d7e8f9a0b1c2d3e4f5a6b7c8d9e0f1a2b3c4d";
+        let path = vkey_fixture_output_path("groth16", vkey);
+        assert_eq!(path.file_name().unwrap(), "groth16-00a1b2c3-fixture.json");
+        assert_eq!(
+            path.parent(),
+            default_fixture_output_path("groth16").parent()
+        );
+        assert_ne!(
+            path,
+            vkey_fixture_output_path("groth16", "0x00a1b2c4d4e5f607")
+        );
+    }
+
+    #[test]
+    fn format_table_aligns_columns() {
+        let table = format_table(&[
+            ("Block number", "7".to_string()),
+            ("Proof system", "Groth16".to_string()),
+        ]);
+        assert_eq!(
+            table,
+            "+--------------+---------+\n\
+             | Block number | 7       |\n\
+             | Proof system | Groth16 |\n\
+             +--------------+---------+"
+        );
+    }
+
+    #[test]
+    fn phase_timings_total_the_recorded_phases() {
+        let mut timings = PhaseTimings::start();
+        timings.record("RPC fetch", Duration::from_millis(1_250));
+        timings.record("Merkle proof", Duration::from_micros(40_900));
+        timings.lap("Execute");
+
+        assert_eq!(timings.phases[1].millis, 40);
+        assert_eq!(timings.phases[2].phase, "Execute");
+        assert_eq!(timings.total_millis(), 1_290 + timings.phases[2].millis);
+        assert!(timings.table().contains("| RPC fetch    | 1250 ms"));
+
+        let json: serde_json::Value = serde_json::from_str(&timings.to_json().unwrap()).unwrap();
+        assert_eq!(json["phases"][0]["phase"], "RPC fetch");
+        assert_eq!(json["phases"][0]["millis"], 1_250);
+        assert_eq!(json["totalMillis"], timings.total_millis());
+    }
+
+    #[test]
+    fn cycle_summary_aggregates_and_checks_threshold() {
+        assert_eq!(CycleSummary::from_counts(&[]), None);
+
+        let summary = CycleSummary::from_counts(&[300, 100, 201]).unwrap();
+        assert_eq!(
+            summary,
+            CycleSummary {
+                runs: 3,
+                min: 100,
+                max: 300,
+                avg: 200,
+            }
+        );
+        assert!(!summary.exceeds(300));
+        assert!(summary.exceeds(299));
+    }
+
+    #[test]
+    fn appended_public_values_are_read_after_base_values() {
+        let fixture = load_fixture("groth16-fixture-for-tests.json");
+        let base = hex::decode(fixture.public_values.trim_start_matches("0x"))
+            .expect("public values should be valid hex");
+        assert!(decode_adjacent_public_values(&base)
+            .expect("base values should decode")
+            .is_none());
+
+        let with_extension = |appended_section: u8| {
+            let mut bytes = base.clone();
+            bytes.extend(PublicValuesExtensionStruct::abi_encode(
+                &PublicValuesExtensionStruct {
+                    headerRlpLength: 540,
+                    txType: 2,
+                    appendedSection: appended_section,
+                    claimedTransactionsRoot: B256::repeat_byte(9),
+                    effectiveGasPrice: U256::from(15_000_000_000u64),
+                    headerUnverified: false,
+                    decodeFailed: false,
+                    blockGasLimit: 30_000_000,
+                    blockGasUsed: 12_000_000,
+                    transactionSizeBytes: 112,
+                    slot: 4_700_013,
+                    beaconGenesisTime: 1_606_824_023,
+                    postMerge: true,
+                    proofDepth: 4,
+                    calldataHash: B256::repeat_byte(0xcd),
+                    provedInParent: false,
+                    accessListAddress: Address::ZERO,
+                    accessListStorageKey: B256::ZERO,
+                    hasAccessListEntry: false,
+                },
+            ));
+            bytes
+        };
+
+        let bytes = with_extension(0);
+        let json = public_values_json(&bytes).unwrap();
+        let abi = hex::decode(
+            json["abiPublicValues"]
+                .as_str()
+                .unwrap()
+                .trim_start_matches("0x"),
+        )
+        .unwrap();
+        assert_eq!(abi, bytes);
+        let decoded: PublicValuesStruct = serde_json::from_value(json["decoded"].clone()).unwrap();
+        let extension: PublicValuesExtensionStruct =
+            serde_json::from_value(json["decoded"]["extension"].clone()).unwrap();
+        let mut reencoded = PublicValuesStruct::abi_encode(&decoded);
+        reencoded.extend(PublicValuesExtensionStruct::abi_encode(&extension));
+        assert_eq!(reencoded, bytes);
+        assert!(public_values_json(&base).unwrap()["decoded"]
+            .get("extension")
+            .is_none());
+
+        assert!(decode_adjacent_public_values(&bytes)
+            .expect("extension values should decode")
+            .is_none());
+        let extension = decode_public_values_extension(&bytes)
+            .expect("extension values should decode")
+            .expect("extension values should be present");
+        assert_eq!(extension.headerRlpLength, 540);
+        assert_eq!(extension.claimedTransactionsRoot, B256::repeat_byte(9));
+        assert_eq!(extension.blockGasUsed, 12_000_000);
+        assert_eq!(extension.transactionSizeBytes, 112);
+        assert_eq!(extension.slot, 4_700_013);
+        assert!(extension.postMerge);
+        assert_eq!(extension.proofDepth, 4);
+        assert_eq!(extension.calldataHash, B256::repeat_byte(0xcd));
+        assert_eq!(extension.effectiveGasPrice, U256::from(15_000_000_000u64));
+
+        let mut ordering_bytes = with_extension(APPENDED_SECTION_SENDER_ORDERING);
+        ordering_bytes.extend(SenderOrderingPublicValuesStruct::abi_encode(
+            &SenderOrderingPublicValuesStruct {
+                sender: Address::repeat_byte(3),
+                firstTransactionHash: B256::repeat_byte(4),
+                firstIndex: 5,
+                secondTransactionHash: B256::repeat_byte(6),
+                secondIndex: 2,
+                inOrder: false,
+            },
+        ));
+        let ordering = decode_sender_ordering_public_values(&ordering_bytes)
+            .expect("sender ordering values should decode")
+            .expect("sender ordering values should be present");
+        assert_eq!(ordering.sender, Address::repeat_byte(3));
+        assert_eq!((ordering.firstIndex, ordering.secondIndex), (5, 2));
+        assert!(!ordering.inOrder);
+        assert!(decode_adjacent_public_values(&ordering_bytes)
+            .expect("sender ordering values should decode")
+            .is_none());
+
+        let mut gas_used_bytes = with_extension(APPENDED_SECTION_GAS_USED);
+        gas_used_bytes.extend(GasUsedPublicValuesStruct::abi_encode(
+            &GasUsedPublicValuesStruct {
+                transactionHash: B256::repeat_byte(7),
+                transactionIndex: 4,
+                gasUsed: 21_000,
+                transactionSucceeded: false,
+                postStateRoot: B256::repeat_byte(0x40),
+            },
+        ));
+        let gas_used = decode_gas_used_public_values(&gas_used_bytes)
+            .expect("gas used values should decode")
+            .expect("gas used values should be present");
+        assert_eq!(gas_used.gasUsed, 21_000);
+        assert!(!gas_used.transactionSucceeded);
+        assert_eq!(gas_used.postStateRoot, B256::repeat_byte(0x40));
+        assert!(decode_sender_ordering_public_values(&gas_used_bytes)
+            .expect("gas used values should decode")
+            .is_none());
+
+        let mut count_bytes = with_extension(APPENDED_SECTION_TRANSACTION_COUNT);
+        count_bytes.extend(TransactionCountPublicValuesStruct::abi_encode(
+            &TransactionCountPublicValuesStruct {
+                transactionHash: B256::repeat_byte(7),
+                transactionIndex: 9,
+                transactionCount: 10,
+                indexFromEnd: 0,
+            },
+        ));
+        let count = decode_transaction_count_public_values(&count_bytes)
+            .expect("transaction count values should decode")
+            .expect("transaction count values should be present");
+        assert_eq!((count.transactionCount, count.indexFromEnd), (10, 0));
+        assert!(decode_gas_used_public_values(&count_bytes)
+            .expect("transaction count values should decode")
+            .is_none());
+
+        let mut cross_block_bytes = with_extension(APPENDED_SECTION_CROSS_BLOCK);
+        cross_block_bytes.extend(CrossBlockPublicValuesStruct::abi_encode(
+            &CrossBlockPublicValuesStruct {
+                firstBlockNumber: 100,
+                firstTransactionHash: B256::repeat_byte(7),
+                secondBlockNumber: 101,
+                secondBlockHash: B256::repeat_byte(8),
+                secondTransactionHash: B256::repeat_byte(9),
+                secondTransactionIndex: 0,
+                aIsEarlierBlock: true,
+            },
+        ));
+        let cross_block = decode_cross_block_public_values(&cross_block_bytes)
+            .expect("cross-block values should decode")
+            .expect("cross-block values should be present");
+        assert_eq!(
+            (cross_block.firstBlockNumber, cross_block.secondBlockNumber),
+            (100, 101)
+        );
+        assert!(cross_block.aIsEarlierBlock);
+        assert!(decode_transaction_count_public_values(&cross_block_bytes)
+            .expect("cross-block values should decode")
+            .is_none());
+
+        let mut bytes = with_extension(APPENDED_SECTION_ADJACENT);
+        let adjacent = AdjacentPublicValuesStruct {
+            transactionHash: B256::repeat_byte(1),
+            nextTransactionHash: B256::repeat_byte(2),
+            nextTransactionIndex: fixture.transaction_index + 1,
+            areAdjacent: true,
+        };
+        bytes.extend(AdjacentPublicValuesStruct::abi_encode(&adjacent));
+
+        let decoded = decode_adjacent_public_values(&bytes)
+            .expect("adjacent values should decode")
+            .expect("adjacent values should be present");
+        assert_eq!(decoded.nextTransactionHash, B256::repeat_byte(2));
+        assert_eq!(decoded.nextTransactionIndex, fixture.transaction_index + 1);
+        assert!(decoded.areAdjacent);
+        assert_eq!(
+            decode_public_values(&bytes).unwrap().blockNumber,
+            fixture.block_number
+        );
+    }
+
+    #[test]
+    fn fixture_consistency_rejects_edited_fields() {
+        let fixture = load_fixture("groth16-fixture-for-tests.json");
+        check_fixture_consistency(&fixture).expect("checked-in fixture should be consistent");
+
+        let mut edited = fixture.clone();
+        edited.transaction_index += 1;
+        assert!(check_fixture_consistency(&edited).is_err());
+
+        let mut edited = fixture.clone();
+        edited.proof = "0xzz".to_string();
+        assert!(check_fixture_consistency(&edited).is_err());
+
+        let mut signed = fixture;
+        sign_public_values(&mut signed, &PrivateKeySigner::random()).unwrap();
+        check_fixture_consistency(&signed).expect("signed fixture should be consistent");
+        signed.operator_signature = Some("0x1234".to_string());
+        assert!(check_fixture_consistency(&signed).is_err());
+    }
+
+    #[test]
+    fn fixture_paths_in_lists_only_fixture_files() {
+        let dir = std::env::temp_dir().join(format!("fixture-paths-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested-fixture.json")).unwrap();
+        let fixture =
+            serde_json::to_string(&load_fixture("groth16-fixture-for-tests.json")).unwrap();
+        for name in [
+            "b-fixture.json",
+            "a-fixture.json",
+            "groth16-fixture-for-tests.json",
+            "README.md",
+        ] {
+            std::fs::write(dir.join(name), &fixture).unwrap();
+        }
+
+        let paths = fixture_paths_in(&dir).unwrap();
+        let names: Vec<_> = paths
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        // Other files and directories, even ones named like fixtures, are skipped
+        assert_eq!(names, ["a-fixture.json", "b-fixture.json"]);
+        read_fixture_file(&paths[0]).expect("listed fixture should load");
+        assert!(fixture_paths_in(Path::new("/nonexistent-fixture-dir")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn malformed_fixture_hashes_are_errors_naming_the_field() {
+        let fixture = load_fixture("groth16-fixture-for-tests.json");
+
+        let mut too_short = fixture.clone();
+        too_short.block_hash.truncate(2 + 62);
+        let error = check_fixture_consistency(&too_short)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(error, "Fixture blockHash must be 32 bytes, got 31 bytes");
+
+        let mut unprefixed = fixture;
+        unprefixed.transaction_hash = unprefixed.transaction_hash[2..].to_string();
+        let error = check_fixture_consistency(&unprefixed)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with("Fixture transactionHash must start with 0x"),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn groth16_fixture_maps_to_inclusion_outcome() {
+        let fixture = load_fixture("groth16-fixture-for-tests.json");
+        let decoded = decode_fixture_public_values(&fixture);
+
+        assert!(fixture.is_included);
+        assert_eq!(
+            inclusion_outcome(&decoded),
+            InclusionOutcome::IncludedAt(fixture.transaction_index)
+        );
+    }
+
     #[test]
     fn groth16_fixture_public_values_match_top_level_fields() {
         let fixture = load_fixture("groth16-fixture-for-tests.json");